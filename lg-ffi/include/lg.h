@@ -0,0 +1,53 @@
+/*
+ * C bindings for lg: find Git repositories in a directory tree and read their remotes.
+ *
+ * Link against liblg_ffi (built by `cargo build -p lg-ffi --release`). All strings are
+ * NUL-terminated UTF-8. Strings returned by a result live until it is freed.
+ */
+#ifndef LG_H
+#define LG_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Search all subdirectories rather than only the immediate children of the root. */
+#define LG_RECURSE ((uint32_t)1)
+/* Fail on the first unreadable path instead of listing it under "errors". */
+#define LG_STRICT ((uint32_t)1 << 1)
+/* Record the checked-out branch of each repository as "head". */
+#define LG_HEAD ((uint32_t)1 << 2)
+
+/* The outcome of a scan: the JSON document, or why the scan failed. */
+typedef struct LgScanResult LgScanResult;
+
+/*
+ * Scan `dir` for repositories. `flags` combines the LG_* flags above; with LG_RECURSE,
+ * `max_depth` limits how deep the scan goes, or 0 for no limit.
+ *
+ * Never returns NULL; free the result with lg_result_free.
+ */
+LgScanResult *lg_scan(const char *dir, uint32_t flags, uint32_t max_depth);
+
+/*
+ * The document `lg -f json` would print for the scan, or NULL if it failed. Its format is
+ * described by the JSON Schema printed by `lg --schema`.
+ */
+const char *lg_result_json(const LgScanResult *result);
+
+/* Why the scan failed, or NULL if it succeeded. */
+const char *lg_result_error(const LgScanResult *result);
+
+/* Free a result and its strings. Does nothing if `result` is NULL. */
+void lg_result_free(LgScanResult *result);
+
+/* The version of the library. */
+const char *lg_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* LG_H */
@@ -0,0 +1,192 @@
+//! C bindings for the `lg` scanner.
+//!
+//! [`lg_scan`] scans a directory and returns an opaque result holding either the JSON document
+//! `lg -f json` would print or an error message; see `include/lg.h` for the C declarations.
+//! Every string passed in or out is NUL-terminated UTF-8, and strings returned by a result
+//! live as long as the result.
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+
+use lg::output::{self, OutputFormat};
+use lg::scan::{Metadata, Scanner};
+use lg::time::TimeFormat;
+
+/// Search all subdirectories rather than only the immediate children of the root.
+pub const LG_RECURSE: u32 = 1;
+/// Fail on the first unreadable path instead of listing it under `errors`.
+pub const LG_STRICT: u32 = 1 << 1;
+/// Record the checked-out branch of each repository as `head`.
+pub const LG_HEAD: u32 = 1 << 2;
+
+/// The outcome of [`lg_scan`]: the JSON document, or why the scan failed.
+pub struct LgScanResult {
+    json: Option<CString>,
+    error: Option<CString>,
+}
+
+impl LgScanResult {
+    fn from_error(message: String) -> Self {
+        LgScanResult {
+            json: None,
+            // Interior NULs cannot be represented; drop them rather than lose the message.
+            error: Some(CString::new(message.replace('\0', "")).unwrap_or_default()),
+        }
+    }
+}
+
+/// Scan `dir` and write the document into the result.
+/// * `dir` - The directory to scan.
+/// * `flags` - A combination of `LG_RECURSE`, `LG_STRICT` and `LG_HEAD`.
+/// * `max_depth` - With `LG_RECURSE`, the deepest level to descend to; 0 for no limit.
+fn scan(dir: &str, flags: u32, max_depth: u32) -> Result<CString, String> {
+    let scanner = Scanner::builder()
+        .recurse(flags & LG_RECURSE != 0)
+        .max_depth((max_depth > 0).then_some(max_depth as usize))
+        .strict(flags & LG_STRICT != 0)
+        .metadata(Metadata {
+            head: flags & LG_HEAD != 0,
+            ..Metadata::default()
+        })
+        .build()
+        .map_err(|e| format!("{:#}", e))?;
+    let tree = scanner
+        .scan(Path::new(dir))
+        .map_err(|e| format!("{:#}", e))?;
+    let mut json = Vec::new();
+    output::write(
+        &tree,
+        OutputFormat::Json,
+        &[],
+        TimeFormat::Iso,
+        false,
+        &mut json,
+    )
+    .map_err(|e| format!("{:#}", e))?;
+    CString::new(json).map_err(|e| e.to_string())
+}
+
+/// Scan `dir` for repositories. Never returns null; free the result with [`lg_result_free`].
+///
+/// # Safety
+///
+/// `dir` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn lg_scan(
+    dir: *const c_char,
+    flags: u32,
+    max_depth: u32,
+) -> *mut LgScanResult {
+    let result = if dir.is_null() {
+        LgScanResult::from_error("dir is null".to_string())
+    } else {
+        match CStr::from_ptr(dir).to_str() {
+            Ok(dir) => {
+                // Unwinding into C is undefined behaviour, so a panic becomes an error.
+                match panic::catch_unwind(AssertUnwindSafe(|| scan(dir, flags, max_depth))) {
+                    Ok(Ok(json)) => LgScanResult {
+                        json: Some(json),
+                        error: None,
+                    },
+                    Ok(Err(message)) => LgScanResult::from_error(message),
+                    Err(_) => LgScanResult::from_error("lg panicked during the scan".to_string()),
+                }
+            }
+            Err(_) => LgScanResult::from_error("dir is not valid UTF-8".to_string()),
+        }
+    };
+    Box::into_raw(Box::new(result))
+}
+
+/// The JSON document of a successful scan, or null if it failed.
+///
+/// # Safety
+///
+/// `result` must be null or a live result from [`lg_scan`].
+#[no_mangle]
+pub unsafe extern "C" fn lg_result_json(result: *const LgScanResult) -> *const c_char {
+    result
+        .as_ref()
+        .and_then(|result| result.json.as_deref())
+        .map_or(ptr::null(), CStr::as_ptr)
+}
+
+/// Why a scan failed, or null if it succeeded.
+///
+/// # Safety
+///
+/// `result` must be null or a live result from [`lg_scan`].
+#[no_mangle]
+pub unsafe extern "C" fn lg_result_error(result: *const LgScanResult) -> *const c_char {
+    result
+        .as_ref()
+        .and_then(|result| result.error.as_deref())
+        .map_or(ptr::null(), CStr::as_ptr)
+}
+
+/// Free a result and the strings it holds. Does nothing if `result` is null.
+///
+/// # Safety
+///
+/// `result` must be null or a result from [`lg_scan`] that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn lg_result_free(result: *mut LgScanResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
+
+/// The version of the library, as a static string.
+#[no_mangle]
+pub extern "C" fn lg_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn string(ptr: *const c_char) -> Option<String> {
+        (!ptr.is_null()).then(|| CStr::from_ptr(ptr).to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn test_scan() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let repo = temp_dir.path().join("a/b");
+        std::fs::create_dir_all(repo.join(".git"))?;
+        std::fs::write(
+            repo.join(".git/config"),
+            "[remote \"origin\"]\n    url = https://github.com/user/b.git\n",
+        )?;
+        let dir = CString::new(temp_dir.path().to_str().unwrap())?;
+
+        unsafe {
+            let result = lg_scan(dir.as_ptr(), LG_RECURSE, 0);
+            assert_eq!(string(lg_result_error(result)), None);
+            let json: serde_json::Value =
+                serde_json::from_str(&string(lg_result_json(result)).unwrap())?;
+            assert_eq!(json["schema_version"], 1);
+            assert_eq!(json["children"][0]["children"][0]["path"], "b");
+            lg_result_free(result);
+
+            let missing = CString::new(temp_dir.path().join("missing").to_str().unwrap())?;
+            let result = lg_scan(missing.as_ptr(), 0, 0);
+            assert_eq!(string(lg_result_json(result)), None);
+            assert!(string(lg_result_error(result)).is_some());
+            lg_result_free(result);
+
+            let result = lg_scan(ptr::null(), 0, 0);
+            assert_eq!(
+                string(lg_result_error(result)).as_deref(),
+                Some("dir is null")
+            );
+            lg_result_free(result);
+            lg_result_free(ptr::null_mut());
+
+            assert_eq!(string(lg_version()).as_deref(), Some("0.0.0"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+//! Python bindings for the `lg` scanner, built as the `lg` module with maturin.
+//!
+//! ```python
+//! import lg
+//!
+//! tree = lg.scan("~/work", recurse=True)
+//! for path, repo in tree.repos():
+//!     print(path, repo.primary_remote())
+//! ```
+//!
+//! The scan runs without holding the GIL, and returns a tree of [`PyGitDirectory`] objects
+//! wrapping the Rust [`GitDirectory`].
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+
+use lg::dirs;
+use lg::model::ScanError;
+use lg::scan::{Metadata, Scanner};
+use lg::GitDirectory;
+
+create_exception!(
+    lg,
+    Error,
+    PyException,
+    "A scan or config file could not be read."
+);
+
+fn to_py_err(error: anyhow::Error) -> PyErr {
+    Error::new_err(format!("{:#}", error))
+}
+
+/// A directory with a Git config, or one containing such directories.
+#[pyclass(name = "GitDirectory", module = "lg", frozen)]
+pub struct PyGitDirectory(GitDirectory);
+
+#[pymethods]
+impl PyGitDirectory {
+    /// The directory, relative to its parent; the root holds the scanned path as given.
+    #[getter]
+    fn path(&self) -> PathBuf {
+        self.0.path.clone()
+    }
+
+    /// Remote names and URLs; empty if the directory is not a repository.
+    #[getter]
+    fn remotes(&self) -> HashMap<String, String> {
+        self.0.remotes.clone()
+    }
+
+    /// The checked-out branch, or commit if detached; only read when requested.
+    #[getter]
+    fn head(&self) -> Option<String> {
+        self.0.head.clone()
+    }
+
+    /// Subdirectories that are, or contain, repositories.
+    #[getter]
+    fn children(&self) -> Vec<PyGitDirectory> {
+        self.0
+            .children
+            .iter()
+            .cloned()
+            .map(PyGitDirectory)
+            .collect()
+    }
+
+    /// Paths the scan could not read and skipped; only set on the root of a tree.
+    #[getter]
+    fn errors(&self) -> Vec<PyScanError> {
+        self.0.errors.iter().cloned().map(PyScanError).collect()
+    }
+
+    /// The remote that identifies this repository as a `(name, url)` pair: the one
+    /// `checkout.defaultRemoteName` names, else `origin`, otherwise the first by name.
+    fn primary_remote(&self) -> Option<(String, String)> {
+        self.0
+            .primary_remote()
+            .map(|(name, url)| (name.to_string(), url.to_string()))
+    }
+
+    /// Every directory in the tree that has remotes, as `(path, GitDirectory)` pairs.
+    fn repos(&self) -> Vec<(PathBuf, PyGitDirectory)> {
+        self.0
+            .repos()
+            .into_iter()
+            .map(|(path, repo)| (path, PyGitDirectory(repo.clone())))
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "GitDirectory(path={:?}, remotes={}, children={})",
+            self.0.path,
+            self.0.remotes.len(),
+            self.0.children.len()
+        )
+    }
+}
+
+/// A path a scan skipped because it could not be read.
+#[pyclass(name = "ScanError", module = "lg", frozen)]
+pub struct PyScanError(ScanError);
+
+#[pymethods]
+impl PyScanError {
+    /// The directory or repository that could not be read.
+    #[getter]
+    fn path(&self) -> PathBuf {
+        self.0.path.clone()
+    }
+
+    /// The kind of failure: `not_found`, `permission_denied`, `invalid_data` or `other`.
+    #[getter]
+    fn code(&self) -> &str {
+        &self.0.code
+    }
+
+    /// What went wrong, including its causes.
+    #[getter]
+    fn message(&self) -> &str {
+        &self.0.message
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ScanError(path={:?}, code={:?})", self.0.path, self.0.code)
+    }
+}
+
+/// Scan `path` for Git repositories, expanding a leading `~`, and return the tree.
+///
+/// Unreadable paths are skipped and listed in the root's `errors`, unless `strict` is set, in
+/// which case the first one raises `lg.Error`.
+#[pyfunction]
+#[pyo3(signature = (path, *, recurse=false, max_depth=None, exclude=Vec::new(), strict=false, head=false, one_file_system=false))]
+#[allow(clippy::too_many_arguments)]
+fn scan(
+    py: Python<'_>,
+    path: PathBuf,
+    recurse: bool,
+    max_depth: Option<usize>,
+    exclude: Vec<String>,
+    strict: bool,
+    head: bool,
+    one_file_system: bool,
+) -> PyResult<PyGitDirectory> {
+    let mut builder = Scanner::builder()
+        .recurse(recurse)
+        .max_depth(max_depth)
+        .strict(strict)
+        .one_file_system(one_file_system)
+        .metadata(Metadata {
+            head,
+            ..Metadata::default()
+        });
+    for pattern in exclude {
+        builder = builder.exclude(pattern);
+    }
+    let scanner = builder.build().map_err(to_py_err)?;
+    let dir = dirs::expand_tilde(&path);
+    py.allow_threads(|| scanner.scan(&dir))
+        .map(PyGitDirectory)
+        .map_err(to_py_err)
+}
+
+/// Read the remote names and URLs from a Git config file.
+#[pyfunction]
+fn parse_git_config(path: PathBuf) -> PyResult<HashMap<String, String>> {
+    lg::parse_git_config(Path::new(&path)).map_err(to_py_err)
+}
+
+#[pymodule]
+#[pyo3(name = "lg")]
+fn lg_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add("Error", m.py().get_type::<Error>())?;
+    m.add_class::<PyGitDirectory>()?;
+    m.add_class::<PyScanError>()?;
+    m.add_function(wrap_pyfunction!(scan, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_git_config, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::types::IntoPyDict;
+
+    use super::*;
+
+    #[test]
+    fn test_scan() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let repo = temp_dir.path().join("a/b");
+        std::fs::create_dir_all(repo.join(".git"))?;
+        std::fs::write(
+            repo.join(".git/config"),
+            "[remote \"origin\"]\n    url = https://github.com/user/b.git\n",
+        )?;
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let lg = PyModule::new(py, "lg")?;
+            lg_module(&lg)?;
+            let tree = lg.getattr("scan")?.call(
+                (temp_dir.path(),),
+                Some(&[("recurse", true)].into_py_dict(py)?),
+            )?;
+            let repos: Vec<(PathBuf, Bound<'_, PyGitDirectory>)> =
+                tree.call_method0("repos")?.extract()?;
+            assert_eq!(repos.len(), 1);
+            assert_eq!(repos[0].0, repo);
+            assert_eq!(
+                repos[0].1.get().primary_remote(),
+                Some((
+                    "origin".to_string(),
+                    "https://github.com/user/b.git".to_string()
+                ))
+            );
+
+            let broken = temp_dir.path().join("c/.git");
+            std::fs::create_dir_all(&broken)?;
+            std::fs::write(broken.join("config"), b"[remote \"origin\"]\n\xff\n")?;
+            let tree = lg.getattr("scan")?.call1((temp_dir.path().join("c"),))?;
+            let errors: Vec<Bound<'_, PyScanError>> = tree.getattr("errors")?.extract()?;
+            assert_eq!(errors[0].get().code(), "invalid_data");
+
+            let missing = temp_dir.path().join("missing");
+            let err = lg.getattr("scan")?.call1((&missing,)).unwrap_err();
+            assert!(err.is_instance_of::<Error>(py));
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
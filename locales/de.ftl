@@ -0,0 +1,310 @@
+# Messages shown by lg, in German.
+error = Fehler: { $error }
+no-match = Kein indiziertes Repository passt
+no-web-page = { $path } hat keinen Remote mit einer Webseite
+repositories-failed = { $failed } von { $total } Repositories fehlgeschlagen
+export-wrote = { $count } Notizen nach { $dest } geschrieben
+notify-baseline = Ausgangsstand für { $root } gespeichert
+snapshot-saved = { $count } Repositories unter { $root } als { $name } gespeichert
+report-wrote = { $path } geschrieben ({ $count } Repositories, { $added } hinzugefügt, { $removed } entfernt, { $changed } geändert)
+stashes-summary = { $count } Stash-Einträge in { $repos } Repositories
+summary-branches = Branches: { $counts }
+summary-repositories = Repositories: { $counts }
+outcome-pushed = gepusht
+outcome-switched = gewechselt
+outcome-ok = in Ordnung
+outcome-corrupt = beschädigt
+outcome-skipped = übersprungen
+outcome-failed = fehlgeschlagen
+push-failed = { $count } Pushes fehlgeschlagen
+switch-failed = { $count } Wechsel fehlgeschlagen
+verify-failed = { $corrupt } Repositories beschädigt, { $failed } fehlgeschlagen
+self-update-current = lg { $version } ist aktuell
+self-update-available = lg { $latest } ist verfügbar; dies ist { $version }
+self-update-done = lg von { $version } auf { $latest } aktualisiert
+credentials-summary = URLs: { $fixed } bereinigt, { $failed } fehlgeschlagen
+credentials-failed = { $count } URLs konnten nicht bereinigt werden
+audit-summary = { $count } Repositories in { $org }: { $missing } fehlen, { $removed } entfernt, { $archived } archiviert
+audit-differ = Klone weichen von { $org } ab
+organize-summary = Repositories: { $moved } verschoben, { $in_place } am Platz, { $skipped } übersprungen, { $failed } fehlgeschlagen
+organize-summary-dry-run = Repositories: { $moved } zu verschieben, { $in_place } am Platz, { $skipped } übersprungen, { $failed } fehlgeschlagen
+organize-undo-hint = rückgängig machen mit: lg organize --undo { $manifest }
+organize-failed = { $count } Verschiebungen fehlgeschlagen
+organize-undo-failed = { $count } Verschiebungen konnten nicht rückgängig gemacht werden
+backup-summary = { $sent } Repositories gesendet, { $unchanged } unverändert
+zoxide-added = { $count } Repositories zu zoxide hinzugefügt
+auth-failed = { $failed } von { $total } Hosts fehlgeschlagen
+config-none = keine Konfigurationsdatei
+config-ok = { $path }: in Ordnung
+config-errors = { $count } Fehler in { $path }
+action-fetch = Abrufen
+action-pull = Pullen
+action-archive = Archivieren
+action-delete = Löschen
+apply-done = { $action } { $path }: erledigt
+pick-status = { $matches }/{ $total } Repositories, { $marked } markiert  { $keys }
+pick-loading = Wird geladen...
+pick-confirm-hint = y zum Bestätigen, n zum Abbrechen
+pick-confirm-one = { $count } Repository { $action }?
+pick-confirm-other = { $count } Repositories { $action }?
+pick-key-mark = markieren
+pick-key-filter = filtern
+pick-key-fetch = abrufen
+pick-key-pull = pullen
+pick-key-archive = archivieren
+pick-key-delete = löschen
+pick-key-preview = Vorschau
+pick-key-choose = auswählen
+pick-key-quit = beenden
+
+# The fields of the plain listing
+field-path = Pfad
+field-vcs = VCS
+field-git-dir = Git-Verzeichnis
+field-annex = Annex
+field-uuid = UUID
+field-remotes = Remotes
+field-size = Größe
+size-bytes-one = { $count } Byte
+size-bytes-other = { $count } Bytes
+field-sparse-checkout = Sparse-Checkout
+field-cone = Cone
+field-patterns = Muster
+field-ci = CI
+field-license = Lizenz
+field-unknown = unbekannt
+field-push = Push
+field-default = Standard
+push-nothing = { $remote } (nichts)
+field-tracking = Tracking
+field-gone = entfernt
+field-last-fetch = letzter Fetch
+field-remote-checks = Remote-Prüfungen
+field-ok = ok
+field-first-commit = erster Commit
+field-in-progress = in Arbeit
+field-partial-clone = partieller Klon
+field-primary-remote = primäres Remote
+field-fetch = Fetch
+field-children = Unterverzeichnisse
+
+# Working tree status
+ahead = { $count } voraus
+behind = { $count } zurück
+status-detached = HEAD (losgelöst)
+status-staged = { $count } vorgemerkt
+status-modified = { $count } geändert
+status-conflicted = { $count } im Konflikt
+status-untracked = { $count } unversioniert
+status-clean = sauber
+status-in-progress = { $operation } läuft
+no-commits = Keine Commits
+
+# How long ago something happened
+ago-future = in der Zukunft
+ago-second-one = vor { $count } Sekunde
+ago-second-other = vor { $count } Sekunden
+ago-minute-one = vor { $count } Minute
+ago-minute-other = vor { $count } Minuten
+ago-hour-one = vor { $count } Stunde
+ago-hour-other = vor { $count } Stunden
+ago-day-one = vor { $count } Tag
+ago-day-other = vor { $count } Tagen
+ago-week-one = vor { $count } Woche
+ago-week-other = vor { $count } Wochen
+ago-month-one = vor { $count } Monat
+ago-month-other = vor { $count } Monaten
+ago-year-one = vor { $count } Jahr
+ago-year-other = vor { $count } Jahren
+
+# Outcomes of bulk commands
+outcome-failed-with = fehlgeschlagen: { $message }
+skipped-detached = übersprungen: HEAD losgelöst
+pushed-commits-one = { $count } Commit gepusht
+pushed-commits-other = { $count } Commits gepusht
+pushed-up-to-date = aktuell
+pushed-behind = übersprungen: { $count } zurück
+pushed-diverged = übersprungen: auseinandergelaufen ({ $ahead } voraus, { $behind } zurück)
+pushed-no-upstream = übersprungen: kein Upstream
+pushed-gone = übersprungen: Upstream entfernt
+switched-to = zu { $branch } gewechselt
+switched-to-stashed = zu { $branch } gewechselt, Änderungen gestasht
+switched-already = bereits auf { $branch }
+switched-dirty = übersprungen: nicht committete Änderungen
+switched-in-progress = übersprungen: { $operation } läuft
+switched-no-default = übersprungen: Standardbranch unbekannt
+mirrored-created = Repository erstellt
+mirrored-added = Remote hinzugefügt
+mirrored-no-url = übersprungen: keine URL für das Remote
+mirrored-conflict = übersprungen: Remote existiert mit URL { $url }
+planned-move = nach { $path } verschieben
+planned-in-place = am Platz
+planned-no-url = übersprungen: keine URL für das Layout
+planned-collision = übersprungen: { $path } ist belegt
+planned-linked = übersprungen: Worktree- oder Submodul-Verweise
+verified-corrupt-one = beschädigt: { $count } Problem
+verified-corrupt-other = beschädigt: { $count } Probleme
+credentials-fixed = behoben
+organize-moved-to = nach { $path } verschoben
+organize-moved-back = zurückverschoben
+backup-sent = { $name } gesendet
+auth-ok-one = ok    { $base } ({ $count } Repository)
+auth-ok-other = ok    { $base } ({ $count } Repositories)
+auth-fail-one = FEHLER  { $base } ({ $count } Repository): { $reason }
+auth-fail-other = FEHLER  { $base } ({ $count } Repositories): { $reason }
+
+# Clone audits
+audit-missing = fehlt
+audit-removed = entfernt
+audit-archived = archiviert
+
+# Differences between snapshots
+diff-none = (keiner)
+diff-head = HEAD { $before } -> { $after }
+diff-remote-removed = { $name } { $url } entfernt
+diff-remote-added = { $name } { $url } hinzugefügt
+
+# Configuration diagnostics
+diagnostic-error = Fehler: { $message }
+diagnostic-warning = Warnung: { $message }
+
+# Errors
+no-command = Kein Befehl zum Ausführen
+failed-run-program = { $program } konnte nicht ausgeführt werden
+exited-with = { $command } endete mit { $status }
+command-failed = { $command } fehlgeschlagen: { $reason }
+failed-read-scan = Die Scan-Ausgabe konnte nicht gelesen werden
+failed-parse-scan = Die Scan-Ausgabe konnte nicht geparst werden
+scan-schema-version = Die Scan-Ausgabe hat Schemaversion { $version }, unterstützt wird höchstens { $supported }
+unknown-key = Unbekannte Taste { $key }
+in-config-section = Im Konfigurationsabschnitt [{ $section }]
+pick-needs-terminal = lg pick braucht ein Terminal
+unterminated-quote = Nicht abgeschlossenes Anführungszeichen in { $text }
+no-cache-dir = Das Cache-Verzeichnis konnte nicht bestimmt werden; setzen Sie LG_CACHE_DIR
+no-data-dir = Das Datenverzeichnis konnte nicht bestimmt werden; setzen Sie LG_DATA_DIR
+daemon-unsupported = lg daemon braucht Unix-Domain-Sockets, die diese Plattform nicht hat
+daemon-listening = lg daemon lauscht bereits auf { $path }
+failed-read-index = { $what } konnte nicht gelesen werden; erstellen Sie ihn neu mit `lg index`
+index-unsupported-version = { $path } hat die nicht unterstützte Version { $version }; erstellen Sie ihn neu mit `lg index`
+index-not-lg = Kein lg-Index
+index-unsupported-format = Nicht unterstützte Indexversion { $version }
+index-truncated = Index ist abgeschnitten: { $expected } Bytes erwartet, { $found } gefunden
+index-checksum = Index ist beschädigt: seine Prüfsumme stimmt nicht
+index-left-over = Index ist beschädigt: { $count } Bytes übrig
+index-ends-early = Index ist beschädigt: er endet mitten in einem Eintrag
+index-not-utf8 = Index ist beschädigt: eine Zeichenkette ist kein UTF-8
+index-unexpected-tag = Index ist beschädigt: unerwartete Markierung { $tag }
+index-bad-path = Index enthält einen Pfad, der hier nicht gültig ist
+no-ghq-root = Das ghq-Wurzelverzeichnis konnte nicht bestimmt werden; setzen Sie GHQ_ROOT
+failed-read-dir-entry = Verzeichniseintrag konnte nicht gelesen werden
+failed-read-import-list = Importliste konnte nicht gelesen werden
+clone-failed = { $count } Repositories konnten nicht geklont werden
+forge-no-token = Kein Token für { $url }; setzen Sie { $var } oder speichern Sie eines mit einem Git-Credential-Helper
+forge-no-owner = { $path } hat keinen Besitzer
+gitlab-no-namespace = Kein GitLab-Namespace { $owner }
+gitlab-no-namespace-id = GitLab-Namespace { $owner } hat keine ID
+forge-no-user = { $api }/user nicht gefunden
+forge-no-login = { $api }/user hat keinen Login
+failed-parse-response = Die Antwort von { $url } konnte nicht geparst werden
+failed-get = GET { $url } fehlgeschlagen
+failed-post = POST an { $url } fehlgeschlagen
+pijul-unsupported = lg wurde ohne Pijul-Unterstützung gebaut; bauen Sie es mit `--features pijul` neu
+status-unsupported = Den Status von { $vcs }-Repositories zu lesen wird nicht unterstützt
+failed-parse-line = Zeile { $line } von { $path } konnte nicht geparst werden
+backup-expected-transport = rclone:ZIEL, rsync:ZIEL oder exec:BEFEHL erwartet
+backup-unknown-transport = Unbekannter Transport { $transport }; erwartet wird rclone, rsync oder exec
+failed-create-temp-dir = Ein temporäres Verzeichnis konnte nicht erstellt werden
+git-failed = git { $command } fehlgeschlagen: { $error }
+git-failed-in = git { $command } in { $path } fehlgeschlagen: { $error }
+snapshot-invalid-name = Ungültiger Snapshot-Name { $name }
+snapshot-missing-save = Kein Snapshot namens { $name }; speichern Sie einen mit `lg snapshot save`
+snapshot-unsupported-version = { $path } hat die nicht unterstützte Version { $version }; speichern Sie ihn erneut mit `lg snapshot save`
+snapshot-missing = Kein Snapshot namens { $name }
+plugin-missing = Kein Plugin namens { $name }: { $program } ist nicht im PATH
+plugin-invalid-json = Plugin { $name } hat ungültiges JSON ausgegeben
+plugin-value-count = Plugin { $name } lieferte { $values } Werte für { $repositories } Repositories
+audit-not-api = { $url } ist keine API-URL
+audit-no-org = Keine GitHub-Organisation { $org }
+already-exists = { $path } existiert bereits
+credential-unstorable = die Zugangsdaten enthalten einen kodierten Zeilenumbruch oder NUL, den Git nicht speichern kann
+credential-no-helper = kein Credential-Helper ist konfiguriert; setzen Sie zuerst credential.helper
+export-obsidian-dest = Das Format obsidian schreibt ein Verzeichnis; geben Sie es mit --dest an
+failed-run-zoxide = zoxide konnte nicht ausgeführt werden; ist es installiert?
+unsupported-version = { $path } hat die nicht unterstützte Version { $version }
+config-no-roots-named = Keine Wurzel namens { $name }: die Konfiguration definiert keine Wurzeln
+config-no-root-named = Keine Wurzel namens { $name }; konfigurierte Wurzeln sind: { $roots }
+alias-recursive = Alias { $name } expandiert zu sich selbst
+in-alias = Im Alias { $name }
+in-var = In ${ $var }
+expected-root = NAME=PFAD erwartet, nicht { $root }
+expected-bool = true oder false erwartet, nicht { $value }
+unsupported-value = Nicht unterstützter Wert für { $key }: { $value }
+git2-unsupported = lg wurde ohne libgit2-Unterstützung gebaut; bauen Sie es mit `--features git2` neu
+gix-unsupported = lg wurde ohne gitoxide-Unterstützung gebaut; bauen Sie es mit `--features gix` neu
+vcstool-outside-root = { $path } wird nicht geklont, da es außerhalb der Wurzel des Manifests liegt
+invalid-exclude = Ungültiges Ausschlussmuster { $pattern }
+failed-start-watching = Die Überwachung konnte nicht gestartet werden
+watcher-stopped = Die Überwachung wurde beendet
+invalid-date = Ungültiges Datum { $date }; erwartet z. B. 2018 oder 2018-06-30
+invalid-duration = Ungültige Dauer { $duration }; erwartet z. B. 30d
+invalid-duration-unit = Ungültige Dauer { $duration }; erwartet eine Zahl gefolgt von s, m, h, d oder w
+release-no-binary = Release { $release } hat keine Binärdatei für { $name }
+release-no-checksums = Release { $release } hat keine { $file }; eine ungeprüfte Binärdatei wird nicht installiert
+release-checksums-not-text = { $file } ist kein Text
+release-checksum-missing = { $file } führt { $name } nicht auf
+release-checksum-mismatch = Prüfsumme von { $name } stimmt nicht: { $expected } erwartet, { $actual } erhalten
+failed-parse-release = Das Release von { $url } konnte nicht geparst werden
+failed-download = { $url } konnte nicht heruntergeladen werden
+failed-ctrl-c = Der Strg-C-Handler konnte nicht installiert werden
+failed-move = { $from } konnte nicht nach { $to } verschoben werden
+cannot-archive = { $path } kann nicht archiviert werden
+failed-resolve-search = Das zu durchsuchende Verzeichnis konnte nicht aufgelöst werden
+failed-search = Fehler bei der Suche nach .git/config-Dateien
+export-dest-obsidian-only = --dest wird nur von --format obsidian verwendet
+forge-url-unknown = Die URL der Forge ist unbekannt; geben Sie --forge-url an
+failed-find-exe = Das laufende Programm konnte nicht gefunden werden
+no-subcommand = Kein Unterbefehl namens { $name }
+config-no-roots = Die Konfiguration definiert keine Wurzeln
+failed-current-dir = Das aktuelle Verzeichnis konnte nicht ermittelt werden
+failed-run-git = git { $args } konnte nicht ausgeführt werden
+failed-read = { $what } konnte nicht gelesen werden
+failed-write = { $what } konnte nicht geschrieben werden
+failed-create = { $what } konnte nicht erstellt werden
+failed-resolve = { $what } konnte nicht aufgelöst werden
+failed-parse = { $what } konnte nicht geparst werden
+failed-remove = { $what } konnte nicht entfernt werden
+failed-delete = { $what } konnte nicht gelöscht werden
+failed-open = { $what } konnte nicht geöffnet werden
+failed-run = { $what } konnte nicht ausgeführt werden
+failed-run-plugin = Plugin { $what } konnte nicht ausgeführt werden
+failed-read-dir = Verzeichnis { $what } konnte nicht gelesen werden
+failed-create-temp-file = In { $what } konnte keine temporäre Datei erstellt werden
+failed-create-tmux-session = tmux-Sitzung { $what } konnte nicht erstellt werden
+failed-attach-tmux-session = Verbindung zur tmux-Sitzung { $what } fehlgeschlagen
+failed-listen = Lauschen auf { $what } fehlgeschlagen
+failed-watch = { $what } konnte nicht überwacht werden
+failed-make-executable = { $what } konnte nicht ausführbar gemacht werden
+failed-move-aside = { $what } konnte nicht beiseitegeschoben werden
+failed-replace = { $what } konnte nicht ersetzt werden
+failed-write-man = Manpages konnten nicht nach { $what } geschrieben werden
+
+# Problems lg config check finds
+check-unknown = unbekannt: { $what } { $key }
+check-unknown-suggest = unbekannt: { $what } { $key }; meinten Sie { $close }?
+check-scan-key = [scan]-Schlüssel
+check-root-key = [roots.{ $root }]-Schlüssel
+check-section = Abschnitt
+check-option = Option von lg { $command }
+check-key = Schlüssel
+check-bad-glob = ungültiges Glob-Muster { $glob }: { $error }
+check-not-dir = { $path } ist kein Verzeichnis
+check-max-depth = max_depth der Wurzel { $root } wirkt nur mit tree = true
+check-alias-unused = Alias { $alias } wird nie verwendet: ein Unterbefehl hat diesen Namen
+check-alias = Alias { $alias }: { $error }
+check-conflict = --{ $first } und --{ $second } von lg { $command } können nicht zusammen verwendet werden
+
+# Progress
+listening = lausche auf { $address }
+clone-exists = existiert: { $path }
+clone-dry-run = würde { $url } nach { $path } klonen
+clone-cloning = klone { $url } nach { $path }
@@ -0,0 +1,310 @@
+# Messages shown by lg, in English. Every other language has the same ids in the same order.
+error = Error: { $error }
+no-match = No indexed repository matches
+no-web-page = { $path } has no remote with a web page
+repositories-failed = { $failed } of { $total } repositories failed
+export-wrote = wrote { $count } notes to { $dest }
+notify-baseline = recorded baseline for { $root }
+snapshot-saved = saved { $count } repositories under { $root } as { $name }
+report-wrote = wrote { $path } ({ $count } repositories, { $added } added, { $removed } removed, { $changed } changed)
+stashes-summary = { $count } stash entries in { $repos } repositories
+summary-branches = branches: { $counts }
+summary-repositories = repositories: { $counts }
+outcome-pushed = pushed
+outcome-switched = switched
+outcome-ok = ok
+outcome-corrupt = corrupt
+outcome-skipped = skipped
+outcome-failed = failed
+push-failed = { $count } pushes failed
+switch-failed = { $count } switches failed
+verify-failed = { $corrupt } repositories corrupt, { $failed } failed
+self-update-current = lg { $version } is up to date
+self-update-available = lg { $latest } is available; this is { $version }
+self-update-done = updated lg from { $version } to { $latest }
+credentials-summary = urls: { $fixed } fixed, { $failed } failed
+credentials-failed = { $count } URLs could not be fixed
+audit-summary = { $count } repositories in { $org }: { $missing } missing, { $removed } removed, { $archived } archived
+audit-differ = clones differ from { $org }
+organize-summary = repositories: { $moved } moved, { $in_place } in place, { $skipped } skipped, { $failed } failed
+organize-summary-dry-run = repositories: { $moved } to move, { $in_place } in place, { $skipped } skipped, { $failed } failed
+organize-undo-hint = undo with: lg organize --undo { $manifest }
+organize-failed = { $count } moves failed
+organize-undo-failed = { $count } moves failed to undo
+backup-summary = sent { $sent } repositories, { $unchanged } unchanged
+zoxide-added = added { $count } repositories to zoxide
+auth-failed = { $failed } of { $total } hosts failed
+config-none = no configuration file
+config-ok = { $path }: ok
+config-errors = { $count } errors in { $path }
+action-fetch = Fetch
+action-pull = Pull
+action-archive = Archive
+action-delete = Delete
+apply-done = { $action } { $path }: done
+pick-status = { $matches }/{ $total } repositories, { $marked } marked  { $keys }
+pick-loading = Loading...
+pick-confirm-hint = y to confirm, n to cancel
+pick-confirm-one = { $action } { $count } repository?
+pick-confirm-other = { $action } { $count } repositories?
+pick-key-mark = mark
+pick-key-filter = filter
+pick-key-fetch = fetch
+pick-key-pull = pull
+pick-key-archive = archive
+pick-key-delete = delete
+pick-key-preview = preview
+pick-key-choose = choose
+pick-key-quit = quit
+
+# The fields of the plain listing
+field-path = path
+field-vcs = vcs
+field-git-dir = git_dir
+field-annex = annex
+field-uuid = uuid
+field-remotes = remotes
+field-size = size
+size-bytes-one = { $count } byte
+size-bytes-other = { $count } bytes
+field-sparse-checkout = sparse_checkout
+field-cone = cone
+field-patterns = patterns
+field-ci = ci
+field-license = license
+field-unknown = unknown
+field-push = push
+field-default = default
+push-nothing = { $remote } (nothing)
+field-tracking = tracking
+field-gone = gone
+field-last-fetch = last_fetch
+field-remote-checks = remote_checks
+field-ok = ok
+field-first-commit = first_commit
+field-in-progress = in_progress
+field-partial-clone = partial_clone
+field-primary-remote = primary_remote
+field-fetch = fetch
+field-children = children
+
+# Working tree status
+ahead = ahead { $count }
+behind = behind { $count }
+status-detached = HEAD (detached)
+status-staged = { $count } staged
+status-modified = { $count } modified
+status-conflicted = { $count } conflicted
+status-untracked = { $count } untracked
+status-clean = clean
+status-in-progress = { $operation } in progress
+no-commits = No commits
+
+# How long ago something happened
+ago-future = in the future
+ago-second-one = { $count } second ago
+ago-second-other = { $count } seconds ago
+ago-minute-one = { $count } minute ago
+ago-minute-other = { $count } minutes ago
+ago-hour-one = { $count } hour ago
+ago-hour-other = { $count } hours ago
+ago-day-one = { $count } day ago
+ago-day-other = { $count } days ago
+ago-week-one = { $count } week ago
+ago-week-other = { $count } weeks ago
+ago-month-one = { $count } month ago
+ago-month-other = { $count } months ago
+ago-year-one = { $count } year ago
+ago-year-other = { $count } years ago
+
+# Outcomes of bulk commands
+outcome-failed-with = failed: { $message }
+skipped-detached = skipped: HEAD detached
+pushed-commits-one = pushed { $count } commit
+pushed-commits-other = pushed { $count } commits
+pushed-up-to-date = up to date
+pushed-behind = skipped: behind { $count }
+pushed-diverged = skipped: diverged (ahead { $ahead }, behind { $behind })
+pushed-no-upstream = skipped: no upstream
+pushed-gone = skipped: upstream gone
+switched-to = switched to { $branch }
+switched-to-stashed = switched to { $branch }, changes stashed
+switched-already = already on { $branch }
+switched-dirty = skipped: uncommitted changes
+switched-in-progress = skipped: { $operation } in progress
+switched-no-default = skipped: default branch unknown
+mirrored-created = created repository
+mirrored-added = added remote
+mirrored-no-url = skipped: no URL for the remote
+mirrored-conflict = skipped: remote exists with URL { $url }
+planned-move = move to { $path }
+planned-in-place = in place
+planned-no-url = skipped: no URL for the layout
+planned-collision = skipped: { $path } is taken
+planned-linked = skipped: worktree or submodule links
+verified-corrupt-one = corrupt: { $count } problem
+verified-corrupt-other = corrupt: { $count } problems
+credentials-fixed = fixed
+organize-moved-to = moved to { $path }
+organize-moved-back = moved back
+backup-sent = sent { $name }
+auth-ok-one = ok    { $base } ({ $count } repository)
+auth-ok-other = ok    { $base } ({ $count } repositories)
+auth-fail-one = FAIL  { $base } ({ $count } repository): { $reason }
+auth-fail-other = FAIL  { $base } ({ $count } repositories): { $reason }
+
+# Clone audits
+audit-missing = missing
+audit-removed = removed
+audit-archived = archived
+
+# Differences between snapshots
+diff-none = (none)
+diff-head = head { $before } -> { $after }
+diff-remote-removed = { $name } { $url } removed
+diff-remote-added = { $name } { $url } added
+
+# Configuration diagnostics
+diagnostic-error = error: { $message }
+diagnostic-warning = warning: { $message }
+
+# Errors
+no-command = No command to run
+failed-run-program = Failed to run { $program }
+exited-with = { $command } exited with { $status }
+command-failed = { $command } failed: { $reason }
+failed-read-scan = Failed to read scan output
+failed-parse-scan = Failed to parse scan output
+scan-schema-version = Scan output has schema version { $version }, but at most { $supported } is supported
+unknown-key = Unknown key { $key }
+in-config-section = In config section [{ $section }]
+pick-needs-terminal = lg pick needs a terminal
+unterminated-quote = Unterminated quote in { $text }
+no-cache-dir = Could not determine the cache directory; set LG_CACHE_DIR
+no-data-dir = Could not determine the data directory; set LG_DATA_DIR
+daemon-unsupported = lg daemon needs Unix domain sockets, which this platform does not have
+daemon-listening = lg daemon is already listening on { $path }
+failed-read-index = Failed to read { $what }; rebuild it with `lg index`
+index-unsupported-version = { $path } has unsupported version { $version }; rebuild it with `lg index`
+index-not-lg = Not an lg index
+index-unsupported-format = Unsupported index version { $version }
+index-truncated = Index is truncated: expected { $expected } bytes, found { $found }
+index-checksum = Index is corrupt: its checksum does not match
+index-left-over = Index is corrupt: { $count } bytes left over
+index-ends-early = Index is corrupt: it ends in the middle of a record
+index-not-utf8 = Index is corrupt: a string is not UTF-8
+index-unexpected-tag = Index is corrupt: unexpected tag { $tag }
+index-bad-path = Index holds a path that is not valid here
+no-ghq-root = Could not determine the ghq root; set GHQ_ROOT
+failed-read-dir-entry = Failed to read directory entry
+failed-read-import-list = Failed to read import list
+clone-failed = { $count } repositories failed to clone
+forge-no-token = No token for { $url }; set { $var } or store one with a Git credential helper
+forge-no-owner = { $path } has no owner
+gitlab-no-namespace = No GitLab namespace { $owner }
+gitlab-no-namespace-id = GitLab namespace { $owner } has no id
+forge-no-user = { $api }/user not found
+forge-no-login = { $api }/user has no login
+failed-parse-response = Failed to parse the response from { $url }
+failed-get = Failed to GET { $url }
+failed-post = Failed to POST to { $url }
+pijul-unsupported = lg was built without Pijul support; rebuild with `--features pijul`
+status-unsupported = Reading the status of { $vcs } repositories is not supported
+failed-parse-line = Failed to parse line { $line } of { $path }
+backup-expected-transport = Expected rclone:DEST, rsync:DEST or exec:COMMAND
+backup-unknown-transport = Unknown transport { $transport }; expected rclone, rsync or exec
+failed-create-temp-dir = Failed to create a temporary directory
+git-failed = git { $command } failed: { $error }
+git-failed-in = git { $command } failed in { $path }: { $error }
+snapshot-invalid-name = Invalid snapshot name { $name }
+snapshot-missing-save = No snapshot called { $name }; save one with `lg snapshot save`
+snapshot-unsupported-version = { $path } has unsupported version { $version }; save it again with `lg snapshot save`
+snapshot-missing = No snapshot called { $name }
+plugin-missing = No plugin named { $name }: { $program } is not on PATH
+plugin-invalid-json = Plugin { $name } printed invalid JSON
+plugin-value-count = Plugin { $name } returned { $values } values for { $repositories } repositories
+audit-not-api = { $url } is not an API URL
+audit-no-org = No GitHub organization { $org }
+already-exists = { $path } already exists
+credential-unstorable = the credential contains an escaped newline or NUL, which Git cannot store
+credential-no-helper = no credential helper is configured; set credential.helper first
+export-obsidian-dest = The obsidian format writes a directory; give it with --dest
+failed-run-zoxide = Failed to run zoxide; is it installed?
+unsupported-version = { $path } has unsupported version { $version }
+config-no-roots-named = No root named { $name }: the configuration defines no roots
+config-no-root-named = No root named { $name }; configured roots are: { $roots }
+alias-recursive = Alias { $name } expands to itself
+in-alias = In alias { $name }
+in-var = In ${ $var }
+expected-root = Expected NAME=PATH, not { $root }
+expected-bool = Expected true or false, not { $value }
+unsupported-value = Unsupported value for { $key }: { $value }
+git2-unsupported = lg was built without libgit2 support; rebuild with `--features git2`
+gix-unsupported = lg was built without gitoxide support; rebuild with `--features gix`
+vcstool-outside-root = Refusing to clone { $path }, which is outside the manifest's root
+invalid-exclude = Invalid exclude pattern { $pattern }
+failed-start-watching = Failed to start watching
+watcher-stopped = The watcher stopped
+invalid-date = Invalid date { $date }; expected e.g. 2018 or 2018-06-30
+invalid-duration = Invalid duration { $duration }; expected e.g. 30d
+invalid-duration-unit = Invalid duration { $duration }; expected a number followed by s, m, h, d or w
+release-no-binary = Release { $release } has no binary for { $name }
+release-no-checksums = Release { $release } has no { $file }; not installing an unverified binary
+release-checksums-not-text = { $file } is not text
+release-checksum-missing = { $file } does not list { $name }
+release-checksum-mismatch = Checksum mismatch for { $name }: expected { $expected }, got { $actual }
+failed-parse-release = Failed to parse the release from { $url }
+failed-download = Failed to download { $url }
+failed-ctrl-c = Failed to install the Ctrl-C handler
+failed-move = Failed to move { $from } to { $to }
+cannot-archive = Cannot archive { $path }
+failed-resolve-search = Failed to resolve the directory to search
+failed-search = Error while searching for .git/config files
+export-dest-obsidian-only = --dest is only used by --format obsidian
+forge-url-unknown = Cannot tell the forge's URL; pass --forge-url
+failed-find-exe = Failed to find the running executable
+no-subcommand = No subcommand named { $name }
+config-no-roots = The configuration defines no roots
+failed-current-dir = Failed to get current directory
+failed-run-git = Failed to run git { $args }
+failed-read = Failed to read { $what }
+failed-write = Failed to write { $what }
+failed-create = Failed to create { $what }
+failed-resolve = Failed to resolve { $what }
+failed-parse = Failed to parse { $what }
+failed-remove = Failed to remove { $what }
+failed-delete = Failed to delete { $what }
+failed-open = Failed to open { $what }
+failed-run = Failed to run { $what }
+failed-run-plugin = Failed to run plugin { $what }
+failed-read-dir = Failed to read directory { $what }
+failed-create-temp-file = Failed to create a temporary file in { $what }
+failed-create-tmux-session = Failed to create tmux session { $what }
+failed-attach-tmux-session = Failed to attach to tmux session { $what }
+failed-listen = Failed to listen on { $what }
+failed-watch = Failed to watch { $what }
+failed-make-executable = Failed to make { $what } executable
+failed-move-aside = Failed to move { $what } aside
+failed-replace = Failed to replace { $what }
+failed-write-man = Failed to write man pages to { $what }
+
+# Problems lg config check finds
+check-unknown = unknown { $what } { $key }
+check-unknown-suggest = unknown { $what } { $key }; did you mean { $close }?
+check-scan-key = [scan] key
+check-root-key = [roots.{ $root }] key
+check-section = section
+check-option = lg { $command } option
+check-key = key
+check-bad-glob = bad glob { $glob }: { $error }
+check-not-dir = { $path } is not a directory
+check-max-depth = max_depth of root { $root } has no effect without tree = true
+check-alias-unused = alias { $alias } is never used: a subcommand has that name
+check-alias = alias { $alias }: { $error }
+check-conflict = --{ $first } and --{ $second } of lg { $command } cannot be used together
+
+# Progress
+listening = listening on { $address }
+clone-exists = exists: { $path }
+clone-dry-run = would clone { $url } into { $path }
+clone-cloning = cloning { $url } into { $path }
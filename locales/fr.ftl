@@ -0,0 +1,310 @@
+# Messages shown by lg, in French.
+error = Erreur : { $error }
+no-match = Aucun dépôt indexé ne correspond
+no-web-page = { $path } n'a aucun dépôt distant avec une page web
+repositories-failed = { $failed } dépôts sur { $total } en échec
+export-wrote = { $count } notes écrites dans { $dest }
+notify-baseline = état de référence enregistré pour { $root }
+snapshot-saved = { $count } dépôts sous { $root } enregistrés sous le nom { $name }
+report-wrote = { $path } écrit ({ $count } dépôts, { $added } ajoutés, { $removed } supprimés, { $changed } modifiés)
+stashes-summary = { $count } entrées de remisage dans { $repos } dépôts
+summary-branches = branches : { $counts }
+summary-repositories = dépôts : { $counts }
+outcome-pushed = poussés
+outcome-switched = basculés
+outcome-ok = intacts
+outcome-corrupt = corrompus
+outcome-skipped = ignorés
+outcome-failed = en échec
+push-failed = { $count } envois en échec
+switch-failed = { $count } changements de branche en échec
+verify-failed = { $corrupt } dépôts corrompus, { $failed } en échec
+self-update-current = lg { $version } est à jour
+self-update-available = lg { $latest } est disponible ; version actuelle : { $version }
+self-update-done = lg mis à jour de { $version } vers { $latest }
+credentials-summary = URL : { $fixed } corrigées, { $failed } en échec
+credentials-failed = { $count } URL n'ont pas pu être corrigées
+audit-summary = { $count } dépôts dans { $org } : { $missing } manquants, { $removed } supprimés, { $archived } archivés
+audit-differ = les clones diffèrent de { $org }
+organize-summary = dépôts : { $moved } déplacés, { $in_place } en place, { $skipped } ignorés, { $failed } en échec
+organize-summary-dry-run = dépôts : { $moved } à déplacer, { $in_place } en place, { $skipped } ignorés, { $failed } en échec
+organize-undo-hint = pour annuler : lg organize --undo { $manifest }
+organize-failed = { $count } déplacements en échec
+organize-undo-failed = { $count } déplacements n'ont pas pu être annulés
+backup-summary = { $sent } dépôts envoyés, { $unchanged } inchangés
+zoxide-added = { $count } dépôts ajoutés à zoxide
+auth-failed = { $failed } hôtes sur { $total } en échec
+config-none = aucun fichier de configuration
+config-ok = { $path } : ok
+config-errors = { $count } erreurs dans { $path }
+action-fetch = Récupérer
+action-pull = Tirer
+action-archive = Archiver
+action-delete = Supprimer
+apply-done = { $action } { $path } : terminé
+pick-status = { $matches }/{ $total } dépôts, { $marked } marqués  { $keys }
+pick-loading = Chargement...
+pick-confirm-hint = y pour confirmer, n pour annuler
+pick-confirm-one = { $action } { $count } dépôt ?
+pick-confirm-other = { $action } { $count } dépôts ?
+pick-key-mark = marquer
+pick-key-filter = filtrer
+pick-key-fetch = récupérer
+pick-key-pull = tirer
+pick-key-archive = archiver
+pick-key-delete = supprimer
+pick-key-preview = aperçu
+pick-key-choose = choisir
+pick-key-quit = quitter
+
+# The fields of the plain listing
+field-path = chemin
+field-vcs = VCS
+field-git-dir = répertoire Git
+field-annex = annex
+field-uuid = UUID
+field-remotes = dépôts distants
+field-size = taille
+size-bytes-one = { $count } octet
+size-bytes-other = { $count } octets
+field-sparse-checkout = extraction partielle
+field-cone = cône
+field-patterns = motifs
+field-ci = CI
+field-license = licence
+field-unknown = inconnue
+field-push = push
+field-default = par défaut
+push-nothing = { $remote } (rien)
+field-tracking = suivi
+field-gone = disparue
+field-last-fetch = dernier fetch
+field-remote-checks = vérifications des dépôts distants
+field-ok = ok
+field-first-commit = premier commit
+field-in-progress = en cours
+field-partial-clone = clone partiel
+field-primary-remote = dépôt distant principal
+field-fetch = fetch
+field-children = sous-répertoires
+
+# Working tree status
+ahead = { $count } en avance
+behind = { $count } en retard
+status-detached = HEAD (détachée)
+status-staged = { $count } indexés
+status-modified = { $count } modifiés
+status-conflicted = { $count } en conflit
+status-untracked = { $count } non suivis
+status-clean = propre
+status-in-progress = { $operation } en cours
+no-commits = Aucun commit
+
+# How long ago something happened
+ago-future = dans le futur
+ago-second-one = il y a { $count } seconde
+ago-second-other = il y a { $count } secondes
+ago-minute-one = il y a { $count } minute
+ago-minute-other = il y a { $count } minutes
+ago-hour-one = il y a { $count } heure
+ago-hour-other = il y a { $count } heures
+ago-day-one = il y a { $count } jour
+ago-day-other = il y a { $count } jours
+ago-week-one = il y a { $count } semaine
+ago-week-other = il y a { $count } semaines
+ago-month-one = il y a { $count } mois
+ago-month-other = il y a { $count } mois
+ago-year-one = il y a { $count } an
+ago-year-other = il y a { $count } ans
+
+# Outcomes of bulk commands
+outcome-failed-with = échec : { $message }
+skipped-detached = ignoré : HEAD détachée
+pushed-commits-one = { $count } commit poussé
+pushed-commits-other = { $count } commits poussés
+pushed-up-to-date = à jour
+pushed-behind = ignoré : { $count } en retard
+pushed-diverged = ignoré : divergent ({ $ahead } en avance, { $behind } en retard)
+pushed-no-upstream = ignoré : pas d'upstream
+pushed-gone = ignoré : upstream disparu
+switched-to = passé sur { $branch }
+switched-to-stashed = passé sur { $branch }, modifications remisées
+switched-already = déjà sur { $branch }
+switched-dirty = ignoré : modifications non commitées
+switched-in-progress = ignoré : { $operation } en cours
+switched-no-default = ignoré : branche par défaut inconnue
+mirrored-created = dépôt créé
+mirrored-added = dépôt distant ajouté
+mirrored-no-url = ignoré : pas d'URL pour le dépôt distant
+mirrored-conflict = ignoré : le dépôt distant existe avec l'URL { $url }
+planned-move = déplacer vers { $path }
+planned-in-place = en place
+planned-no-url = ignoré : pas d'URL pour la disposition
+planned-collision = ignoré : { $path } est pris
+planned-linked = ignoré : liens de worktree ou de sous-module
+verified-corrupt-one = corrompu : { $count } problème
+verified-corrupt-other = corrompu : { $count } problèmes
+credentials-fixed = corrigé
+organize-moved-to = déplacé vers { $path }
+organize-moved-back = remis en place
+backup-sent = { $name } envoyé
+auth-ok-one = ok    { $base } ({ $count } dépôt)
+auth-ok-other = ok    { $base } ({ $count } dépôts)
+auth-fail-one = ÉCHEC  { $base } ({ $count } dépôt) : { $reason }
+auth-fail-other = ÉCHEC  { $base } ({ $count } dépôts) : { $reason }
+
+# Clone audits
+audit-missing = manquant
+audit-removed = supprimé
+audit-archived = archivé
+
+# Differences between snapshots
+diff-none = (aucune)
+diff-head = HEAD { $before } -> { $after }
+diff-remote-removed = { $name } { $url } supprimé
+diff-remote-added = { $name } { $url } ajouté
+
+# Configuration diagnostics
+diagnostic-error = erreur : { $message }
+diagnostic-warning = avertissement : { $message }
+
+# Errors
+no-command = Aucune commande à exécuter
+failed-run-program = Impossible d'exécuter { $program }
+exited-with = { $command } s'est terminé avec { $status }
+command-failed = échec de { $command } : { $reason }
+failed-read-scan = Impossible de lire la sortie du scan
+failed-parse-scan = Impossible d'analyser la sortie du scan
+scan-schema-version = La sortie du scan a la version de schéma { $version }, mais au plus { $supported } est prise en charge
+unknown-key = Touche inconnue { $key }
+in-config-section = Dans la section de configuration [{ $section }]
+pick-needs-terminal = lg pick a besoin d'un terminal
+unterminated-quote = Guillemet non fermé dans { $text }
+no-cache-dir = Impossible de déterminer le répertoire de cache ; définissez LG_CACHE_DIR
+no-data-dir = Impossible de déterminer le répertoire de données ; définissez LG_DATA_DIR
+daemon-unsupported = lg daemon a besoin de sockets de domaine Unix, que cette plateforme n'a pas
+daemon-listening = lg daemon écoute déjà sur { $path }
+failed-read-index = Impossible de lire { $what } ; reconstruisez-le avec `lg index`
+index-unsupported-version = { $path } a la version non prise en charge { $version } ; reconstruisez-le avec `lg index`
+index-not-lg = Ce n'est pas un index lg
+index-unsupported-format = Version d'index non prise en charge { $version }
+index-truncated = L'index est tronqué : { $expected } octets attendus, { $found } trouvés
+index-checksum = L'index est corrompu : sa somme de contrôle ne correspond pas
+index-left-over = L'index est corrompu : { $count } octets en trop
+index-ends-early = L'index est corrompu : il se termine au milieu d'un enregistrement
+index-not-utf8 = L'index est corrompu : une chaîne n'est pas en UTF-8
+index-unexpected-tag = L'index est corrompu : marqueur inattendu { $tag }
+index-bad-path = L'index contient un chemin qui n'est pas valide ici
+no-ghq-root = Impossible de déterminer la racine ghq ; définissez GHQ_ROOT
+failed-read-dir-entry = Impossible de lire une entrée du répertoire
+failed-read-import-list = Impossible de lire la liste d'import
+clone-failed = { $count } dépôts n'ont pas pu être clonés
+forge-no-token = Aucun jeton pour { $url } ; définissez { $var } ou enregistrez-en un avec un assistant d'identifiants Git
+forge-no-owner = { $path } n'a pas de propriétaire
+gitlab-no-namespace = Aucun espace de noms GitLab { $owner }
+gitlab-no-namespace-id = L'espace de noms GitLab { $owner } n'a pas d'identifiant
+forge-no-user = { $api }/user introuvable
+forge-no-login = { $api }/user n'a pas d'identifiant de connexion
+failed-parse-response = Impossible d'analyser la réponse de { $url }
+failed-get = Échec de GET { $url }
+failed-post = Échec de POST vers { $url }
+pijul-unsupported = lg a été compilé sans la prise en charge de Pijul ; recompilez avec `--features pijul`
+status-unsupported = La lecture de l'état des dépôts { $vcs } n'est pas prise en charge
+failed-parse-line = Impossible d'analyser la ligne { $line } de { $path }
+backup-expected-transport = rclone:DEST, rsync:DEST ou exec:COMMANDE attendu
+backup-unknown-transport = Transport inconnu { $transport } ; rclone, rsync ou exec attendu
+failed-create-temp-dir = Impossible de créer un répertoire temporaire
+git-failed = échec de git { $command } : { $error }
+git-failed-in = échec de git { $command } dans { $path } : { $error }
+snapshot-invalid-name = Nom d'instantané invalide { $name }
+snapshot-missing-save = Aucun instantané nommé { $name } ; enregistrez-en un avec `lg snapshot save`
+snapshot-unsupported-version = { $path } a la version non prise en charge { $version } ; enregistrez-le de nouveau avec `lg snapshot save`
+snapshot-missing = Aucun instantané nommé { $name }
+plugin-missing = Aucun greffon nommé { $name } : { $program } n'est pas dans le PATH
+plugin-invalid-json = Le greffon { $name } a affiché du JSON invalide
+plugin-value-count = Le greffon { $name } a renvoyé { $values } valeurs pour { $repositories } dépôts
+audit-not-api = { $url } n'est pas une URL d'API
+audit-no-org = Aucune organisation GitHub { $org }
+already-exists = { $path } existe déjà
+credential-unstorable = l'identifiant contient un saut de ligne ou un NUL encodé, que Git ne peut pas enregistrer
+credential-no-helper = aucun assistant d'identifiants n'est configuré ; définissez d'abord credential.helper
+export-obsidian-dest = Le format obsidian écrit un répertoire ; indiquez-le avec --dest
+failed-run-zoxide = Impossible d'exécuter zoxide ; est-il installé ?
+unsupported-version = { $path } a la version non prise en charge { $version }
+config-no-roots-named = Aucune racine nommée { $name } : la configuration n'en définit aucune
+config-no-root-named = Aucune racine nommée { $name } ; les racines configurées sont : { $roots }
+alias-recursive = L'alias { $name } se développe en lui-même
+in-alias = Dans l'alias { $name }
+in-var = Dans ${ $var }
+expected-root = NOM=CHEMIN attendu, pas { $root }
+expected-bool = true ou false attendu, pas { $value }
+unsupported-value = Valeur non prise en charge pour { $key } : { $value }
+git2-unsupported = lg a été compilé sans la prise en charge de libgit2 ; recompilez avec `--features git2`
+gix-unsupported = lg a été compilé sans la prise en charge de gitoxide ; recompilez avec `--features gix`
+vcstool-outside-root = Refus de cloner { $path }, qui est hors de la racine du manifeste
+invalid-exclude = Motif d'exclusion invalide { $pattern }
+failed-start-watching = Impossible de démarrer la surveillance
+watcher-stopped = La surveillance s'est arrêtée
+invalid-date = Date invalide { $date } ; attendu par ex. 2018 ou 2018-06-30
+invalid-duration = Durée invalide { $duration } ; attendu par ex. 30d
+invalid-duration-unit = Durée invalide { $duration } ; attendu un nombre suivi de s, m, h, d ou w
+release-no-binary = La version { $release } n'a pas de binaire pour { $name }
+release-no-checksums = La version { $release } n'a pas de { $file } ; un binaire non vérifié ne sera pas installé
+release-checksums-not-text = { $file } n'est pas du texte
+release-checksum-missing = { $file } ne mentionne pas { $name }
+release-checksum-mismatch = Somme de contrôle incorrecte pour { $name } : { $expected } attendu, { $actual } obtenu
+failed-parse-release = Impossible d'analyser la version depuis { $url }
+failed-download = Impossible de télécharger { $url }
+failed-ctrl-c = Impossible d'installer le gestionnaire de Ctrl-C
+failed-move = Impossible de déplacer { $from } vers { $to }
+cannot-archive = Impossible d'archiver { $path }
+failed-resolve-search = Impossible de résoudre le répertoire à parcourir
+failed-search = Erreur lors de la recherche des fichiers .git/config
+export-dest-obsidian-only = --dest n'est utilisé que par --format obsidian
+forge-url-unknown = Impossible de déterminer l'URL de la forge ; passez --forge-url
+failed-find-exe = Impossible de trouver l'exécutable en cours
+no-subcommand = Aucune sous-commande nommée { $name }
+config-no-roots = La configuration ne définit aucune racine
+failed-current-dir = Impossible d'obtenir le répertoire courant
+failed-run-git = Impossible d'exécuter git { $args }
+failed-read = Impossible de lire { $what }
+failed-write = Impossible d'écrire { $what }
+failed-create = Impossible de créer { $what }
+failed-resolve = Impossible de résoudre { $what }
+failed-parse = Impossible d'analyser { $what }
+failed-remove = Impossible de supprimer { $what }
+failed-delete = Impossible d'effacer { $what }
+failed-open = Impossible d'ouvrir { $what }
+failed-run = Impossible d'exécuter { $what }
+failed-run-plugin = Impossible d'exécuter le greffon { $what }
+failed-read-dir = Impossible de lire le répertoire { $what }
+failed-create-temp-file = Impossible de créer un fichier temporaire dans { $what }
+failed-create-tmux-session = Impossible de créer la session tmux { $what }
+failed-attach-tmux-session = Impossible de s'attacher à la session tmux { $what }
+failed-listen = Impossible d'écouter sur { $what }
+failed-watch = Impossible de surveiller { $what }
+failed-make-executable = Impossible de rendre { $what } exécutable
+failed-move-aside = Impossible de mettre { $what } de côté
+failed-replace = Impossible de remplacer { $what }
+failed-write-man = Impossible d'écrire les pages de manuel dans { $what }
+
+# Problems lg config check finds
+check-unknown = inconnu : { $what } { $key }
+check-unknown-suggest = inconnu : { $what } { $key } ; vouliez-vous dire { $close } ?
+check-scan-key = clé [scan]
+check-root-key = clé [roots.{ $root }]
+check-section = section
+check-option = option de lg { $command }
+check-key = clé
+check-bad-glob = motif glob invalide { $glob } : { $error }
+check-not-dir = { $path } n'est pas un répertoire
+check-max-depth = max_depth de la racine { $root } n'a aucun effet sans tree = true
+check-alias-unused = l'alias { $alias } n'est jamais utilisé : une sous-commande porte ce nom
+check-alias = alias { $alias } : { $error }
+check-conflict = --{ $first } et --{ $second } de lg { $command } ne peuvent pas être utilisés ensemble
+
+# Progress
+listening = écoute sur { $address }
+clone-exists = existe : { $path }
+clone-dry-run = clonerait { $url } dans { $path }
+clone-cloning = clonage de { $url } dans { $path }
@@ -0,0 +1,142 @@
+//! [git-annex](https://git-annex.branchable.com/) repositories, whose large files are kept in
+//! an annex outside Git and copied between annex remotes.
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{gitconfig, refs};
+
+/// What a repository's annex holds and where its content can be copied.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annex {
+    /// The repository's `annex.uuid`; absent until `git annex init` has run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// The remotes, including special remotes, that git-annex copies content to, by name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remotes: Vec<String>,
+    /// The bytes of annexed content present in this repository; only measured when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+/// Read the annex of the Git repository at `path`, if it uses git-annex: it has an
+/// `annex.uuid` or an `annex` directory in its Git directory.
+/// * `size` - Measure the annexed content present, which lists every annexed object.
+pub fn read(path: &Path, size: bool) -> Result<Option<Annex>> {
+    let Some(common_dir) = refs::common_dir(path) else {
+        return Ok(None);
+    };
+    let config = common_dir.join("config");
+    let entries = if config.is_file() {
+        gitconfig::read(&config)?
+    } else {
+        Vec::new()
+    };
+    let uuid = entries
+        .iter()
+        .rev()
+        .find(|entry| entry.is("annex", "uuid") && entry.subsection.is_none())
+        .and_then(|entry| entry.value.clone());
+    let objects = common_dir.join("annex").join("objects");
+    if uuid.is_none() && !common_dir.join("annex").is_dir() {
+        return Ok(None);
+    }
+    let mut remotes = Vec::new();
+    for entry in &entries {
+        if let (true, Some(name)) = (entry.is("remote", "annex-uuid"), &entry.subsection) {
+            let ignored = entries.iter().any(|other| {
+                other.is("remote", "annex-ignore")
+                    && other.subsection.as_ref() == Some(name)
+                    && other.is_true()
+            });
+            if !ignored && !remotes.contains(name) {
+                remotes.push(name.clone());
+            }
+        }
+    }
+    remotes.sort();
+    Ok(Some(Annex {
+        uuid,
+        remotes,
+        size: size.then(|| dir_size(&objects)),
+    }))
+}
+
+/// The total size of the regular files beneath `dir`; what cannot be read counts as empty.
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(file_type) if file_type.is_file() => entry.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        fs::create_dir_all(repo.join(".git"))?;
+        fs::write(
+            repo.join(".git/config"),
+            "[remote \"origin\"]\n\turl = https://example.com/media.git\n",
+        )?;
+        assert_eq!(read(repo, true)?, None);
+
+        fs::write(
+            repo.join(".git/config"),
+            "[annex]\n\tuuid = 5c4a7d3e-0000-4000-8000-000000000001\n\
+             [remote \"origin\"]\n\turl = https://example.com/media.git\n\
+             \tannex-uuid = 5c4a7d3e-0000-4000-8000-000000000002\n\
+             [remote \"s3\"]\n\tannex-s3 = true\n\
+             \tannex-uuid = 5c4a7d3e-0000-4000-8000-000000000003\n\
+             [remote \"laptop\"]\n\turl = ../laptop\n\
+             \tannex-uuid = 5c4a7d3e-0000-4000-8000-000000000004\n\
+             \tannex-ignore = true\n",
+        )?;
+        let object = repo.join(".git/annex/objects/Xk/9v/SHA256E-s5--abc.jpg");
+        fs::create_dir_all(&object)?;
+        fs::write(object.join("SHA256E-s5--abc.jpg"), "12345")?;
+        let annex = read(repo, true)?.unwrap();
+        assert_eq!(
+            annex.uuid.as_deref(),
+            Some("5c4a7d3e-0000-4000-8000-000000000001")
+        );
+        assert_eq!(annex.remotes, ["origin", "s3"]);
+        assert_eq!(annex.size, Some(5));
+        assert_eq!(read(repo, false)?.unwrap().size, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_annex_dir() -> Result<()> {
+        // An annex directory is enough, even without an `annex.uuid`.
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join(".git/annex"))?;
+        fs::write(
+            temp_dir.path().join(".git/config"),
+            "[core]\n\tbare = false\n",
+        )?;
+        assert_eq!(
+            read(temp_dir.path(), true)?,
+            Some(Annex {
+                size: Some(0),
+                ..Annex::default()
+            })
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,283 @@
+//! Auditing local clones against a GitHub organization's repositories, as `lg audit` does:
+//! which of the organization's repositories are not cloned, which clones are of repositories no
+//! longer in it, and which are of repositories it has archived.
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::locale;
+use crate::url::RemoteUrl;
+use crate::{auth, forge, GitDirectory};
+
+/// The GitHub API, for organizations on github.com.
+pub const GITHUB_API: &str = "https://api.github.com";
+
+/// How many repositories to ask for in each page of the listing, GitHub's most.
+const PER_PAGE: usize = 100;
+
+/// A repository of an organization, as the GitHub API describes it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrgRepo {
+    pub name: String,
+    /// The repository's web page.
+    pub html_url: String,
+    #[serde(default)]
+    pub archived: bool,
+}
+
+/// A GitHub API, such as [`GITHUB_API`] or a GitHub Enterprise server's `/api/v3`.
+pub struct GitHub {
+    api: String,
+    token: Option<String>,
+}
+
+impl GitHub {
+    /// The API at `api`, authenticated with `token` if there is one.
+    pub fn new(api: &str, token: Option<String>) -> Self {
+        GitHub {
+            api: api.trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+
+    /// The token for the API at `api`: `$LG_FORGE_TOKEN`, `$GITHUB_TOKEN`, or else the password
+    /// Git's credential helpers have for its host; none if there is none, when only public
+    /// repositories can be listed.
+    pub fn token(api: &str) -> Option<String> {
+        [forge::TOKEN_VAR, "GITHUB_TOKEN"]
+            .iter()
+            .find_map(|var| std::env::var(var).ok().filter(|token| !token.is_empty()))
+            .or_else(|| {
+                let host = web_host(api)?;
+                auth::fill_password(&format!("https://{}", host))
+                    .ok()
+                    .flatten()
+            })
+    }
+
+    /// The host the API's repositories are cloned from, e.g. `github.com` for
+    /// `https://api.github.com`.
+    pub fn host(&self) -> Result<String> {
+        web_host(&self.api).ok_or_else(|| {
+            anyhow!(locale::text(
+                "audit-not-api",
+                &[("url", &format_args!("{:?}", self.api))]
+            ))
+        })
+    }
+
+    /// Every repository of the organization `org`.
+    pub fn org_repos(&self, org: &str) -> Result<Vec<OrgRepo>> {
+        let mut repos = Vec::new();
+        for page in 1.. {
+            let url = format!(
+                "{}/orgs/{}/repos?type=all&per_page={}&page={}",
+                self.api, org, PER_PAGE, page
+            );
+            let mut request = ureq::get(&url)
+                .header("Accept", "application/vnd.github+json")
+                .header("User-Agent", concat!("lg/", env!("CARGO_PKG_VERSION")));
+            if let Some(token) = &self.token {
+                request = request.header("Authorization", &format!("Bearer {}", token));
+            }
+            let batch: Vec<OrgRepo> = match request.call() {
+                Ok(mut response) => response
+                    .body_mut()
+                    .read_json()
+                    .with_context(|| locale::text("failed-parse-response", &[("url", &url)]))?,
+                Err(ureq::Error::StatusCode(404)) => {
+                    return Err(anyhow!(locale::text(
+                        "audit-no-org",
+                        &[("org", &format_args!("{:?}", org))]
+                    )))
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| locale::text("failed-get", &[("url", &url)]))
+                }
+            };
+            let last = batch.len() < PER_PAGE;
+            repos.extend(batch);
+            if last {
+                break;
+            }
+        }
+        repos.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(repos)
+    }
+}
+
+/// The host of the web interface of the API at `api`: its host, without an `api.` prefix.
+fn web_host(api: &str) -> Option<String> {
+    let (_, rest) = api.split_once("://")?;
+    let authority = rest.split('/').next()?;
+    let host = authority.strip_prefix("api.").unwrap_or(authority);
+    Some(host.split(':').next()?.to_lowercase()).filter(|host| !host.is_empty())
+}
+
+/// A local clone of one of an organization's repositories.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct LocalClone {
+    pub path: PathBuf,
+    /// The repository's name in the organization.
+    pub name: String,
+}
+
+/// What `lg audit` found.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Audit {
+    /// The organization's repositories that are not cloned.
+    pub missing: Vec<OrgRepo>,
+    /// Clones of repositories of the organization that it no longer has.
+    pub removed: Vec<LocalClone>,
+    /// Clones of the organization's archived repositories.
+    pub archived: Vec<LocalClone>,
+}
+
+impl Audit {
+    /// Compare the repositories `repos` of the organization `org` on `host` with the clones in
+    /// `dir`. A clone is of a repository if any of its remotes is, with names compared without
+    /// regard to case, as GitHub does.
+    pub fn new(org: &str, host: &str, repos: &[OrgRepo], dir: &GitDirectory) -> Self {
+        let by_name: BTreeMap<_, _> = repos
+            .iter()
+            .map(|repo| (repo.name.to_lowercase(), repo))
+            .collect();
+        let mut cloned = BTreeSet::new();
+        let mut audit = Audit::default();
+        for (path, repo) in dir.repos() {
+            let mut names: Vec<_> = repo
+                .remotes
+                .values()
+                .filter_map(|url| org_repo_name(url, org, host))
+                .collect();
+            names.sort();
+            names.dedup();
+            for name in names {
+                let clone = LocalClone {
+                    path: path.clone(),
+                    name,
+                };
+                match by_name.get(&clone.name.to_lowercase()) {
+                    Some(org_repo) => {
+                        cloned.insert(clone.name.to_lowercase());
+                        if org_repo.archived {
+                            audit.archived.push(clone);
+                        }
+                    }
+                    None => audit.removed.push(clone),
+                }
+            }
+        }
+        audit.missing = repos
+            .iter()
+            .filter(|repo| !cloned.contains(&repo.name.to_lowercase()))
+            .cloned()
+            .collect();
+        audit.removed.sort();
+        audit.archived.sort();
+        audit
+    }
+
+    /// Whether every repository is cloned and every clone is of a current repository.
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.removed.is_empty() && self.archived.is_empty()
+    }
+}
+
+/// The name of the repository of `org` on `host` that `url` is a remote of, if it is one.
+fn org_repo_name(url: &str, org: &str, host: &str) -> Option<String> {
+    let remote = RemoteUrl::parse(url)?;
+    let web_host = remote.host.strip_prefix("ssh.").unwrap_or(&remote.host);
+    if web_host != host {
+        return None;
+    }
+    let (owner, name) = remote.path.split_once('/')?;
+    (owner.eq_ignore_ascii_case(org) && !name.contains('/')).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::thread;
+
+    fn org_repo(name: &str, archived: bool) -> OrgRepo {
+        OrgRepo {
+            name: name.to_string(),
+            html_url: format!("https://github.com/acme/{}", name),
+            archived,
+        }
+    }
+
+    #[test]
+    fn test_audit() {
+        let clone = |path: &str, url: &str| GitDirectory {
+            remotes: HashMap::from([("origin".to_string(), url.to_string())]),
+            ..GitDirectory::directory(PathBuf::from(path))
+        };
+        let dir = GitDirectory {
+            children: vec![
+                clone("api", "git@github.com:Acme/API.git"),
+                clone("old", "https://github.com/acme/old"),
+                clone("gone", "ssh://git@ssh.github.com:443/acme/gone.git"),
+                clone("fork", "https://github.com/me/web"),
+            ],
+            ..GitDirectory::directory(PathBuf::from("/src"))
+        };
+        let repos = [
+            org_repo("api", false),
+            org_repo("old", true),
+            org_repo("web", false),
+        ];
+        let audit = Audit::new("acme", "github.com", &repos, &dir);
+        assert_eq!(audit.missing, [org_repo("web", false)]);
+        let clone = |path: &str, name: &str| LocalClone {
+            path: PathBuf::from(path),
+            name: name.to_string(),
+        };
+        assert_eq!(audit.removed, [clone("/src/gone", "gone")]);
+        assert_eq!(audit.archived, [clone("/src/old", "old")]);
+        assert!(!audit.is_clean());
+    }
+
+    #[test]
+    fn test_org_repos() -> Result<()> {
+        let server = tiny_http::Server::http("127.0.0.1:0").map_err(|e| anyhow!(e))?;
+        let addr = server.server_addr().to_ip().unwrap();
+        let handle = thread::spawn(move || {
+            let mut seen = Vec::new();
+            for _ in 0..2 {
+                let request = server.recv().unwrap();
+                let page = request.url().rsplit_once("page=").unwrap().1.to_string();
+                let count = if page == "1" { PER_PAGE } else { 1 };
+                let repos: Vec<_> = (0..count)
+                    .map(|i| org_repo(&format!("repo-{}-{:03}", page, i), false))
+                    .collect();
+                seen.push(request.url().to_string());
+                request
+                    .respond(tiny_http::Response::from_data(
+                        serde_json::to_vec(&repos).unwrap(),
+                    ))
+                    .unwrap();
+            }
+            seen
+        });
+
+        let github = GitHub::new(&format!("http://{}/", addr), None);
+        assert_eq!(github.host()?, "127.0.0.1");
+        let repos = github.org_repos("acme")?;
+        assert_eq!(repos.len(), PER_PAGE + 1);
+        assert_eq!(repos[0].name, "repo-1-000");
+        assert_eq!(
+            handle.join().unwrap(),
+            [
+                "/orgs/acme/repos?type=all&per_page=100&page=1",
+                "/orgs/acme/repos?type=all&per_page=100&page=2",
+            ]
+        );
+        assert_eq!(web_host(GITHUB_API).as_deref(), Some("github.com"));
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+//! Backends used to read the remotes of a Git repository.
+//!
+//! Only remotes are read through the chosen backend. Heads are read from the Git directory by
+//! [`refs`] whichever backend is chosen, and status, branches and other refs by running `git`,
+//! so the library backends do not remove the need for a `git` binary when those are asked for.
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+
+use crate::locale;
+use crate::{parse_git_config, refs};
+
+/// The implementation used to read a repository's remotes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// The built-in parser, reading `.git/config`.
+    #[default]
+    Native,
+    /// libgit2, via the `git2` crate (requires the `git2` feature).
+    Git2,
+    /// gitoxide, via the pure-Rust `gix` crate (requires the `gix` feature). Status and refs
+    /// are still read with `git`.
+    Gix,
+}
+
+impl Backend {
+    /// Check that this backend was compiled into the binary.
+    pub fn ensure_available(self) -> Result<()> {
+        match self {
+            Backend::Native => Ok(()),
+            Backend::Git2 if cfg!(feature = "git2") => Ok(()),
+            Backend::Git2 => Err(anyhow!(locale::text("git2-unsupported", &[]))),
+            Backend::Gix if cfg!(feature = "gix") => Ok(()),
+            Backend::Gix => Err(anyhow!(locale::text("gix-unsupported", &[]))),
+        }
+    }
+
+    /// Read the remotes of the repository at `path`, or `None` if `path` is not a repository.
+    /// * `path` - The working directory of the candidate repository.
+    pub fn remotes(self, path: &Path) -> Result<Option<HashMap<String, String>>> {
+        match self {
+            Backend::Native => native_remotes(path),
+            Backend::Git2 => git2_remotes(path),
+            Backend::Gix => gix_remotes(path),
+        }
+    }
+}
+
+fn native_remotes(path: &Path) -> Result<Option<HashMap<String, String>>> {
+    let Some(git_config) = refs::common_dir(path)
+        .map(|dir| dir.join("config"))
+        .filter(|config| config.is_file())
+    else {
+        return Ok(None);
+    };
+    parse_git_config(&git_config)
+        .with_context(|| format!("Error parsing {:?}", git_config))
+        .map(Some)
+}
+
+#[cfg(feature = "git2")]
+fn git2_remotes(path: &Path) -> Result<Option<HashMap<String, String>>> {
+    let Some(git_dir) = refs::git_dir(path) else {
+        return Ok(None);
+    };
+    let repo = match git2::Repository::open(&git_dir) {
+        Ok(repo) => repo,
+        Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+        Err(e) => return Err(anyhow!("Error opening repository {:?}: {}", path, e)),
+    };
+    let mut remotes = HashMap::new();
+    for name in repo.remotes()?.iter().flatten() {
+        let remote = repo.find_remote(name)?;
+        if let Some(url) = remote.url() {
+            remotes.insert(name.to_string(), url.to_string());
+        }
+    }
+    Ok(Some(remotes))
+}
+
+#[cfg(not(feature = "git2"))]
+fn git2_remotes(_path: &Path) -> Result<Option<HashMap<String, String>>> {
+    Backend::Git2.ensure_available().map(|_| None)
+}
+
+#[cfg(feature = "gix")]
+fn gix_remotes(path: &Path) -> Result<Option<HashMap<String, String>>> {
+    let Some(git_dir) = refs::git_dir(path) else {
+        return Ok(None);
+    };
+    let repo = match gix::open(&git_dir) {
+        Ok(repo) => repo,
+        Err(gix::open::Error::NotARepository { .. }) => return Ok(None),
+        Err(e) => return Err(anyhow!("Error opening repository {:?}: {}", path, e)),
+    };
+    let mut remotes = HashMap::new();
+    for name in repo.remote_names() {
+        let remote = repo.find_remote(name.as_ref())?;
+        if let Some(url) = remote.url(gix::remote::Direction::Fetch) {
+            remotes.insert(name.to_string(), url.to_bstring().to_string());
+        }
+    }
+    Ok(Some(remotes))
+}
+
+#[cfg(not(feature = "gix"))]
+fn gix_remotes(_path: &Path) -> Result<Option<HashMap<String, String>>> {
+    Backend::Gix.ensure_available().map(|_| None)
+}
+
+#[cfg(all(test, any(feature = "git2", feature = "gix")))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn init_with_include(dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir.join(".git/objects"))?;
+        std::fs::create_dir_all(dir.join(".git/refs"))?;
+        std::fs::write(dir.join(".git/HEAD"), "ref: refs/heads/main\n")?;
+        std::fs::write(
+            dir.join(".git/config"),
+            "[core]\n\trepositoryformatversion = 0\n\tbare = false\n[include]\n\
+             \tpath = remotes.inc\n",
+        )?;
+        std::fs::write(
+            dir.join(".git/remotes.inc"),
+            "[remote \"origin\"]\n\turl = https://github.com/user/repo.git\n",
+        )?;
+        Ok(())
+    }
+
+    #[cfg(feature = "gix")]
+    #[test]
+    fn test_gix_follows_include() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        init_with_include(temp_dir.path())?;
+
+        let remotes = Backend::Gix.remotes(temp_dir.path())?.unwrap();
+        assert_eq!(
+            remotes.get("origin"),
+            Some(&"https://github.com/user/repo.git".to_string())
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "git2")]
+    #[test]
+    fn test_git2_follows_include() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        init_with_include(temp_dir.path())?;
+
+        let remotes = Backend::Git2.remotes(temp_dir.path())?.unwrap();
+        assert_eq!(
+            remotes.get("origin"),
+            Some(&"https://github.com/user/repo.git".to_string())
+        );
+
+        // The native parser does not follow includes.
+        let remotes = Backend::Native.remotes(temp_dir.path())?.unwrap();
+        assert!(remotes.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+//! Backing up repositories offsite, as `lg backup` does.
+//!
+//! Each Git repository is written to a `git bundle` of all its refs, which `git clone` can
+//! restore from, and handed to a transport: `rclone`, `rsync` or a command of your own. The
+//! commit each repository's `HEAD` was at when it was last sent through a transport is kept in
+//! `backup.json` in the cache directory, and repositories still at that commit are skipped.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use tempfile::TempDir;
+use tracing::warn;
+
+use crate::dirs::cache_dir;
+use crate::export::relative_repos;
+use crate::locale;
+use crate::refs;
+use crate::shell;
+use crate::GitDirectory;
+
+/// Where bundles are sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Transport {
+    /// `rclone copyto` into an rclone destination, such as `remote:bucket/git`.
+    Rclone(String),
+    /// `rsync` into an rsync destination, such as `host:/backups/git`.
+    Rsync(String),
+    /// A shell command, in which `{bundle}` is replaced by the bundle's path and `{name}` by
+    /// the repository's path relative to the directory scanned.
+    Exec(String),
+}
+
+impl FromStr for Transport {
+    type Err = anyhow::Error;
+
+    /// Parse `rclone:DEST`, `rsync:DEST` or `exec:COMMAND`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, rest) = s
+            .split_once(':')
+            .filter(|(_, rest)| !rest.is_empty())
+            .ok_or_else(|| anyhow!(locale::text("backup-expected-transport", &[])))?;
+        match kind {
+            "rclone" => Ok(Transport::Rclone(rest.trim_end_matches('/').to_string())),
+            "rsync" => Ok(Transport::Rsync(rest.trim_end_matches('/').to_string())),
+            "exec" => Ok(Transport::Exec(rest.to_string())),
+            _ => bail!(locale::text(
+                "backup-unknown-transport",
+                &[("transport", &format_args!("{:?}", kind))]
+            )),
+        }
+    }
+}
+
+impl Transport {
+    /// Send the bundle at `bundle` as the repository called `name`, a relative path using `/`.
+    pub fn send(&self, bundle: &Path, name: &str) -> Result<()> {
+        let mut command = match self {
+            Transport::Rclone(dest) => {
+                let mut command = Command::new("rclone");
+                command
+                    .arg("copyto")
+                    .arg(bundle)
+                    .arg(format!("{}/{}.bundle", dest, name));
+                command
+            }
+            Transport::Rsync(dest) => {
+                let mut command = Command::new("rsync");
+                command
+                    .arg("--mkpath")
+                    .arg(bundle)
+                    .arg(format!("{}/{}.bundle", dest, name));
+                command
+            }
+            Transport::Exec(template) => shell::command(
+                &template
+                    .replace("{bundle}", &shell::quote(&bundle.to_string_lossy()))
+                    .replace("{name}", &shell::quote(name)),
+            ),
+        };
+        let status = command
+            .stdin(Stdio::null())
+            .status()
+            .with_context(|| locale::failed("run", &command.get_program()))?;
+        if !status.success() {
+            bail!(locale::text(
+                "exited-with",
+                &[
+                    ("command", &format_args!("{:?}", command.get_program())),
+                    ("status", &status)
+                ]
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// What happened to a repository.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Sent,
+    /// Skipped because `HEAD` has not moved since the repository was last sent.
+    Unchanged,
+    /// Skipped because it is not a Git repository or has no commits.
+    Skipped,
+}
+
+/// The commits each repository was at when last sent, by transport and then absolute path.
+type State = BTreeMap<String, BTreeMap<PathBuf, String>>;
+
+/// Backs up the repositories of a scan through one transport.
+pub struct Backup {
+    transport: Transport,
+    /// The `--via` argument, which keys the state.
+    via: String,
+    /// Send every repository, even those that have not changed.
+    pub force: bool,
+    state: State,
+}
+
+impl Backup {
+    /// Prepare to send through `via`, as given to `--via`, loading what was sent before.
+    pub fn new(via: &str) -> Result<Self> {
+        Ok(Backup {
+            transport: via.parse()?,
+            via: via.to_string(),
+            force: false,
+            state: load_state(&state_file()?)?,
+        })
+    }
+
+    /// The repositories of `dir` that would be sent, by path relative to `dir`.
+    /// * `dir` - A scan result whose root path is absolute.
+    pub fn pending(&self, dir: &GitDirectory) -> Vec<(String, PathBuf)> {
+        repos(dir)
+            .into_iter()
+            .filter(|(_, path)| {
+                head_commit(path).is_some_and(|head| self.force || self.sent(path) != Some(&head))
+            })
+            .collect()
+    }
+
+    /// Bundle and send each repository of `dir`, calling `report` with its name and what
+    /// happened, and remember what was sent. Returns the number of repositories that failed,
+    /// each of which is logged.
+    /// * `dir` - A scan result whose root path is absolute.
+    pub fn run(
+        &mut self,
+        dir: &GitDirectory,
+        mut report: impl FnMut(&str, Outcome),
+    ) -> Result<usize> {
+        let temp = TempDir::new().with_context(|| locale::text("failed-create-temp-dir", &[]))?;
+        let mut failed = 0;
+        for (name, path) in repos(dir) {
+            let Some(head) = head_commit(&path) else {
+                report(&name, Outcome::Skipped);
+                continue;
+            };
+            if !self.force && self.sent(&path) == Some(&head) {
+                report(&name, Outcome::Unchanged);
+                continue;
+            }
+            let bundle = temp.path().join("repo.bundle");
+            match create_bundle(&path, &bundle).and_then(|()| self.transport.send(&bundle, &name)) {
+                Ok(()) => {
+                    self.state
+                        .entry(self.via.clone())
+                        .or_default()
+                        .insert(path, head);
+                    report(&name, Outcome::Sent);
+                }
+                Err(e) => {
+                    warn!(path = %path.display(), "{:#}", e);
+                    failed += 1;
+                }
+            }
+        }
+        save_state(&state_file()?, &self.state)?;
+        Ok(failed)
+    }
+
+    /// The commit `path` was at when last sent through this transport.
+    fn sent(&self, path: &Path) -> Option<&String> {
+        self.state.get(&self.via)?.get(path)
+    }
+}
+
+/// The Git repositories of `dir`, by their path relative to `dir` using `/`, and their
+/// absolute paths. A repository at the root is named after its directory.
+fn repos(dir: &GitDirectory) -> Vec<(String, PathBuf)> {
+    relative_repos(dir)
+        .into_iter()
+        .filter(|(relative, repo)| {
+            if !repo.vcs.is_git() {
+                warn!(
+                    path = %dir.path.join(relative).display(),
+                    "only Git repositories are backed up"
+                );
+            }
+            repo.vcs.is_git()
+        })
+        .map(|(relative, _)| {
+            let path = dir.path.join(&relative);
+            let name = if relative == Path::new(".") {
+                dir.path
+                    .file_name()
+                    .map_or_else(|| "root".to_string(), |n| n.to_string_lossy().into_owned())
+            } else {
+                relative.to_string_lossy().replace('\\', "/")
+            };
+            (name, path)
+        })
+        .collect()
+}
+
+/// The commit `HEAD` is at in the working tree at `path`; none if there are no commits.
+fn head_commit(path: &Path) -> Option<String> {
+    let output = refs::git_command(path)
+        .args(["rev-parse", "--verify", "-q", "HEAD"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let commit = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !commit.is_empty()).then_some(commit)
+}
+
+/// Write a bundle of every ref of the repository at `path` to `bundle`.
+fn create_bundle(path: &Path, bundle: &Path) -> Result<()> {
+    let output = refs::git_command(path)
+        .args(["bundle", "create", "-q"])
+        .arg(bundle)
+        .arg("--all")
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| locale::text("failed-run-git", &[("args", &"bundle")]))?;
+    if !output.status.success() {
+        bail!(locale::text(
+            "git-failed",
+            &[
+                ("command", &"bundle"),
+                ("error", &String::from_utf8_lossy(&output.stderr).trim()),
+            ]
+        ));
+    }
+    Ok(())
+}
+
+/// The location of the backup state file.
+fn state_file() -> Result<PathBuf> {
+    cache_dir()
+        .map(|dir| dir.join("backup.json"))
+        .ok_or_else(|| anyhow!(locale::text("no-cache-dir", &[])))
+}
+
+fn load_state(path: &Path) -> Result<State> {
+    match fs::read(path) {
+        Ok(contents) => {
+            serde_json::from_slice(&contents).with_context(|| locale::failed("parse", &path))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(State::new()),
+        Err(e) => Err(e).with_context(|| locale::failed("read", &path)),
+    }
+}
+
+fn save_state(path: &Path, state: &State) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| locale::failed("create", &dir))?;
+    }
+    fs::write(path, serde_json::to_vec_pretty(state)?)
+        .with_context(|| locale::failed("write", &path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transport() -> Result<()> {
+        assert_eq!(
+            "rclone:b2:bucket/git/".parse::<Transport>()?,
+            Transport::Rclone("b2:bucket/git".to_string())
+        );
+        assert_eq!(
+            "rsync:nas:/backups".parse::<Transport>()?,
+            Transport::Rsync("nas:/backups".to_string())
+        );
+        assert_eq!(
+            "exec:cp {bundle} /mnt/{name}".parse::<Transport>()?,
+            Transport::Exec("cp {bundle} /mnt/{name}".to_string())
+        );
+        assert!("rclone:".parse::<Transport>().is_err());
+        assert!("scp:host:/backups".parse::<Transport>().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,507 @@
+//! Actions applied to many repositories at once: those `lg pick` applies to the repositories
+//! marked in the picker, pushing for `lg push` and switching branches for `lg switch`, run on
+//! several repositories in parallel.
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use rayon::prelude::*;
+
+use crate::locale;
+use crate::operation::Operation;
+use crate::refs::{self, Head};
+use crate::status::{self, Status};
+
+/// How many repositories actions run on at once unless told otherwise.
+pub const DEFAULT_JOBS: usize = 4;
+
+/// Run `f` on each of `items`, such as repository paths, on up to `jobs` at once, returning
+/// the results in the order of `items`.
+pub fn run_parallel<I: Sync, T: Send>(
+    items: &[I],
+    jobs: usize,
+    f: impl Fn(&I) -> T + Send + Sync,
+) -> Result<Vec<T>> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.max(1))
+        .build()?;
+    Ok(pool.install(|| items.par_iter().map(f).collect()))
+}
+
+/// What an action did to one repository or branch, as counted in a [`Tally`].
+pub trait Outcome {
+    /// What the outcome counts as in a summary, such as `pushed`, `skipped` or `failed`; none
+    /// if it is not counted, as when there was nothing to do.
+    fn label(&self) -> Option<&'static str>;
+}
+
+/// The number of outcomes of an action counted as each of a set of labels, for a summary such
+/// as `2 pushed, 1 skipped, 0 failed`; each label is shown as the message `outcome-<label>` in
+/// the current locale.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tally {
+    counts: Vec<(&'static str, usize)>,
+}
+
+impl Tally {
+    /// A tally of the outcomes counted as each of `labels`, summarized in that order.
+    pub fn new(labels: &[&'static str]) -> Self {
+        Tally {
+            counts: labels.iter().map(|&label| (label, 0)).collect(),
+        }
+    }
+
+    /// Count `outcome`, if it has one of the tally's labels.
+    pub fn add(&mut self, outcome: &impl Outcome) {
+        let Some(label) = outcome.label() else {
+            return;
+        };
+        if let Some((_, count)) = self.counts.iter_mut().find(|(l, _)| *l == label) {
+            *count += 1;
+        }
+    }
+
+    /// The number of outcomes counted as `label`.
+    pub fn count(&self, label: &str) -> usize {
+        self.counts
+            .iter()
+            .find(|(l, _)| *l == label)
+            .map_or(0, |(_, count)| *count)
+    }
+}
+
+impl fmt::Display for Tally {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (label, count)) in self.counts.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(
+                f,
+                "{} {}",
+                count,
+                locale::text(&format!("outcome-{}", label), &[])
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Something to do to each of a set of repositories.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// `git fetch` every remote.
+    Fetch,
+    /// `git pull --ff-only`.
+    Pull,
+    /// Move the repository into the archive directory.
+    Archive,
+    /// Delete the repository's working tree, and with it any unpushed work.
+    Delete,
+}
+
+impl Action {
+    /// The verb to show when asking for confirmation, e.g. "Fetch", in the current locale.
+    pub fn verb(self) -> &'static str {
+        let id = match self {
+            Action::Fetch => "action-fetch",
+            Action::Pull => "action-pull",
+            Action::Archive => "action-archive",
+            Action::Delete => "action-delete",
+        };
+        locale::lookup(id).unwrap_or(id)
+    }
+
+    /// Whether the action removes the repository from where it was.
+    pub fn removes(self) -> bool {
+        matches!(self, Action::Archive | Action::Delete)
+    }
+
+    /// Apply the action to the repository at `path`.
+    /// * `archive_dir` - Where `Archive` moves repositories to.
+    pub fn apply(self, path: &Path, archive_dir: &Path) -> Result<()> {
+        match self {
+            Action::Fetch => git(path, &["fetch", "--all", "--prune"]),
+            Action::Pull => git(path, &["pull", "--ff-only"]),
+            Action::Archive => {
+                let target = archive_target(path, archive_dir)?;
+                fs::create_dir_all(archive_dir)
+                    .with_context(|| locale::failed("create", &archive_dir))?;
+                fs::rename(path, &target).with_context(|| {
+                    locale::text(
+                        "failed-move",
+                        &[
+                            ("from", &format_args!("{:?}", path)),
+                            ("to", &format_args!("{:?}", target)),
+                        ],
+                    )
+                })
+            }
+            Action::Delete => {
+                fs::remove_dir_all(path).with_context(|| locale::failed("delete", &path))
+            }
+        }
+    }
+}
+
+/// Where archiving `path` moves it to: its directory name inside `archive_dir`, which must not
+/// already exist.
+fn archive_target(path: &Path, archive_dir: &Path) -> Result<PathBuf> {
+    let Some(name) = path.file_name() else {
+        bail!(locale::text(
+            "cannot-archive",
+            &[("path", &format_args!("{:?}", path))]
+        ));
+    };
+    let target = archive_dir.join(name);
+    if target.exists() {
+        bail!(locale::text(
+            "already-exists",
+            &[("path", &format_args!("{:?}", target))]
+        ));
+    }
+    Ok(target)
+}
+
+/// What pushing a branch did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Pushed {
+    /// Pushed this many commits to the upstream.
+    Pushed(u32),
+    UpToDate,
+    /// Not pushed, having nothing the upstream lacks, which has this many commits more.
+    Behind(u32),
+    /// Not pushed, since the branch and its upstream each have commits the other lacks.
+    Diverged {
+        ahead: u32,
+        behind: u32,
+    },
+    /// Not pushed, having no upstream on a remote.
+    NoUpstream,
+    /// Not pushed, since the upstream's remote-tracking branch no longer exists.
+    Gone,
+    /// Not pushed, `HEAD` not being on a branch.
+    Detached,
+    /// `git push` failed, with this message.
+    Failed(String),
+}
+
+impl Outcome for Pushed {
+    fn label(&self) -> Option<&'static str> {
+        match self {
+            Pushed::Pushed(_) => Some("pushed"),
+            Pushed::UpToDate => None,
+            Pushed::Failed(_) => Some("failed"),
+            _ => Some("skipped"),
+        }
+    }
+}
+
+impl fmt::Display for Pushed {
+    /// What happened, in the current locale.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Pushed::Pushed(count) => locale::plural("pushed-commits", *count as usize, &[]),
+            Pushed::UpToDate => locale::text("pushed-up-to-date", &[]),
+            Pushed::Behind(count) => locale::text("pushed-behind", &[("count", count)]),
+            Pushed::Diverged { ahead, behind } => {
+                locale::text("pushed-diverged", &[("ahead", ahead), ("behind", behind)])
+            }
+            Pushed::NoUpstream => locale::text("pushed-no-upstream", &[]),
+            Pushed::Gone => locale::text("pushed-gone", &[]),
+            Pushed::Detached => locale::text("skipped-detached", &[]),
+            Pushed::Failed(message) => locale::text("outcome-failed-with", &[("message", message)]),
+        };
+        f.write_str(&text)
+    }
+}
+
+/// Push the current branch of the Git working tree at `path`, or every local branch, to its
+/// upstream, skipping branches whose upstream has commits they lack. Returns what happened to
+/// each branch, by name. Only what has been fetched of the upstreams is known, so a push can
+/// still be refused.
+pub fn push(path: &Path, all_branches: bool) -> Result<Vec<(String, Pushed)>> {
+    let tracking = status::read_tracking(path)?;
+    let branches = if all_branches {
+        refs::list(path, "refs/heads/")
+    } else {
+        match refs::head(path) {
+            Some(Head::Branch(branch)) => vec![branch],
+            _ => return Ok(vec![("HEAD".to_string(), Pushed::Detached)]),
+        }
+    };
+    Ok(branches
+        .into_iter()
+        .map(|branch| {
+            let pushed = match tracking.get(&branch) {
+                None => Pushed::NoUpstream,
+                Some(upstream) if upstream.remote == "." => Pushed::NoUpstream,
+                Some(upstream) if upstream.gone => Pushed::Gone,
+                Some(upstream) => match (upstream.ahead, upstream.behind) {
+                    (0, 0) => Pushed::UpToDate,
+                    (0, behind) => Pushed::Behind(behind),
+                    (ahead, 0) => {
+                        let refspec = format!("refs/heads/{}:{}", branch, upstream.merge);
+                        match git(path, &["push", "--quiet", &upstream.remote, &refspec]) {
+                            Ok(()) => Pushed::Pushed(ahead),
+                            Err(e) => Pushed::Failed(format!("{:#}", e)),
+                        }
+                    }
+                    (ahead, behind) => Pushed::Diverged { ahead, behind },
+                },
+            };
+            (branch, pushed)
+        })
+        .collect())
+}
+
+/// What switching to the default branch did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Switched {
+    /// Checked out the default branch, having first stashed changes if `stashed`.
+    Switched { branch: String, stashed: bool },
+    /// The default branch was already checked out.
+    AlreadyOn(String),
+    /// Not switched, having uncommitted or untracked changes.
+    Dirty,
+    /// Not switched, `HEAD` not being on a branch.
+    Detached,
+    /// Not switched, a merge, rebase or the like having been left in progress.
+    InProgress(Operation),
+    /// Not switched, the primary remote's default branch not being recorded.
+    NoDefault,
+    /// Switching failed, with this message; any changes stashed were restored.
+    Failed(String),
+}
+
+impl Outcome for Switched {
+    fn label(&self) -> Option<&'static str> {
+        match self {
+            Switched::Switched { .. } => Some("switched"),
+            Switched::AlreadyOn(_) => None,
+            Switched::Failed(_) => Some("failed"),
+            _ => Some("skipped"),
+        }
+    }
+}
+
+impl fmt::Display for Switched {
+    /// What happened, in the current locale.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Switched::Switched {
+                branch,
+                stashed: false,
+            } => locale::text("switched-to", &[("branch", branch)]),
+            Switched::Switched {
+                branch,
+                stashed: true,
+            } => locale::text("switched-to-stashed", &[("branch", branch)]),
+            Switched::AlreadyOn(branch) => locale::text("switched-already", &[("branch", branch)]),
+            Switched::Dirty => locale::text("switched-dirty", &[]),
+            Switched::Detached => locale::text("skipped-detached", &[]),
+            Switched::InProgress(operation) => {
+                locale::text("switched-in-progress", &[("operation", operation)])
+            }
+            Switched::NoDefault => locale::text("switched-no-default", &[]),
+            Switched::Failed(message) => {
+                locale::text("outcome-failed-with", &[("message", message)])
+            }
+        };
+        f.write_str(&text)
+    }
+}
+
+/// Check out the default branch of the primary remote in the Git working tree at `path`, as
+/// recorded by `git clone` or `git remote set-head`, creating the local branch from the remote
+/// one if need be. Repositories whose `HEAD` is detached or that have an operation in progress
+/// are left alone, as are those with uncommitted or untracked changes unless `stash` is set,
+/// when the changes are stashed first.
+pub fn switch_default(path: &Path, stash: bool) -> Result<Switched> {
+    let status = Status::read(path)?;
+    if let Some(operation) = status.in_progress {
+        return Ok(Switched::InProgress(operation));
+    }
+    let Some(current) = status.branch.as_deref() else {
+        return Ok(Switched::Detached);
+    };
+    let Some(branch) = refs::default_branch(path) else {
+        return Ok(Switched::NoDefault);
+    };
+    if current == branch {
+        return Ok(Switched::AlreadyOn(branch));
+    }
+    let stashed = status.is_dirty();
+    if stashed {
+        if !stash {
+            return Ok(Switched::Dirty);
+        }
+        let message = format!("lg switch from {}", current);
+        git(
+            path,
+            &["stash", "push", "--include-untracked", "-m", &message],
+        )?;
+    }
+    if let Err(e) = git(path, &["switch", "--quiet", &branch]) {
+        if stashed {
+            git(path, &["stash", "pop", "--quiet"])?;
+        }
+        return Ok(Switched::Failed(format!("{:#}", e)));
+    }
+    Ok(Switched::Switched { branch, stashed })
+}
+
+/// Run `git` with `args` in the repository at `path`, failing with its error output if it does.
+pub(crate) fn git(path: &Path, args: &[&str]) -> Result<()> {
+    let output = refs::git_command(path)
+        .args(args)
+        // Actions run unattended, several at once, so must not wait for a password.
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .with_context(|| locale::text("failed-run-git", &[("args", &args[0])]))?;
+    if !output.status.success() {
+        bail!(locale::text(
+            "git-failed",
+            &[
+                ("command", &args[0]),
+                ("error", &String::from_utf8_lossy(&output.stderr).trim())
+            ]
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testutil;
+
+    #[test]
+    fn test_tally() {
+        let mut tally = Tally::new(&["pushed", "skipped", "failed"]);
+        for outcome in [
+            Pushed::Pushed(2),
+            Pushed::UpToDate,
+            Pushed::NoUpstream,
+            Pushed::Failed("rejected".to_string()),
+            Pushed::Pushed(1),
+        ] {
+            tally.add(&outcome);
+        }
+        assert_eq!(tally.count("pushed"), 2);
+        assert_eq!(tally.count("up to date"), 0);
+        assert_eq!(tally.to_string(), "2 pushed, 1 skipped, 1 failed");
+    }
+
+    #[test]
+    fn test_archive_and_delete() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let repo = temp_dir.path().join("src/lg");
+        let archive = temp_dir.path().join("archive");
+        fs::create_dir_all(repo.join(".git"))?;
+
+        Action::Archive.apply(&repo, &archive)?;
+        assert!(!repo.exists());
+        assert!(archive.join("lg/.git").is_dir());
+
+        fs::create_dir_all(repo.join(".git"))?;
+        assert!(Action::Archive.apply(&repo, &archive).is_err());
+        assert!(repo.exists());
+
+        Action::Delete.apply(&repo, &archive)?;
+        assert!(!repo.exists());
+        assert!(Action::Delete.apply(&repo, &archive).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_push() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let git = |dir: &str, args: &[&str]| testutil::git(&temp_dir.path().join(dir), args);
+        let commit = |dir: &str| git(dir, &["commit", "-q", "--allow-empty", "-m", dir]);
+        git(".", &["init", "-q", "--bare", "-b", "main", "origin.git"]);
+        git(".", &["init", "-q", "-b", "main", "work"]);
+        git("work", &["remote", "add", "origin", "../origin.git"]);
+        commit("work");
+        git("work", &["push", "-q", "-u", "origin", "main"]);
+        let work = temp_dir.path().join("work");
+
+        assert_eq!(
+            push(&work, false)?,
+            [("main".to_string(), Pushed::UpToDate)]
+        );
+        commit("work");
+        commit("work");
+        git("work", &["branch", "local"]);
+        assert_eq!(
+            push(&work, false)?,
+            [("main".to_string(), Pushed::Pushed(2))]
+        );
+        assert_eq!(
+            push(&work, true)?,
+            [
+                ("local".to_string(), Pushed::NoUpstream),
+                ("main".to_string(), Pushed::UpToDate),
+            ]
+        );
+
+        // Someone else pushes first.
+        git(".", &["clone", "-q", "-b", "main", "origin.git", "other"]);
+        commit("other");
+        git("other", &["push", "-q"]);
+        commit("work");
+        git("work", &["fetch", "-q"]);
+        assert_eq!(
+            push(&work, false)?,
+            [(
+                "main".to_string(),
+                Pushed::Diverged {
+                    ahead: 1,
+                    behind: 1
+                }
+            )]
+        );
+        git("work", &["checkout", "-q", "--detach"]);
+        assert_eq!(
+            push(&work, false)?,
+            [("HEAD".to_string(), Pushed::Detached)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_switch_default() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let git = |dir: &str, args: &[&str]| testutil::git(&temp_dir.path().join(dir), args);
+        git(".", &["init", "-q", "-b", "trunk", "origin"]);
+        git("origin", &["commit", "-q", "--allow-empty", "-m", "root"]);
+        git(".", &["clone", "-q", "origin", "work"]);
+        git("work", &["switch", "-q", "-c", "feature"]);
+        let work = temp_dir.path().join("work");
+
+        fs::write(work.join("notes.txt"), "wip")?;
+        assert_eq!(switch_default(&work, false)?, Switched::Dirty);
+        assert_eq!(
+            switch_default(&work, true)?,
+            Switched::Switched {
+                branch: "trunk".to_string(),
+                stashed: true
+            }
+        );
+        assert!(!work.join("notes.txt").exists());
+        assert_eq!(
+            switch_default(&work, false)?,
+            Switched::AlreadyOn("trunk".to_string())
+        );
+
+        git("work", &["checkout", "-q", "--detach"]);
+        assert_eq!(switch_default(&work, false)?, Switched::Detached);
+        git("work", &["remote", "set-head", "origin", "-d"]);
+        git("work", &["switch", "-q", "feature"]);
+        assert_eq!(switch_default(&work, false)?, Switched::NoDefault);
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+//! Recognising the CI systems a repository is configured for by their configuration files.
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// A CI system configured by files in the repository.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CiSystem {
+    Github,
+    Gitlab,
+    Forgejo,
+    Gitea,
+    Jenkins,
+    Circleci,
+    Travis,
+    Azure,
+    Bitbucket,
+    Buildkite,
+    Drone,
+    Woodpecker,
+    Appveyor,
+    Sourcehut,
+}
+
+/// The files, or directories as `dir/`, that configure each CI system, relative to the top
+/// of the working tree.
+const CONFIGS: &[(CiSystem, &[&str])] = &[
+    (CiSystem::Github, &[".github/workflows/"]),
+    (CiSystem::Gitlab, &[".gitlab-ci.yml"]),
+    (CiSystem::Forgejo, &[".forgejo/workflows/"]),
+    (CiSystem::Gitea, &[".gitea/workflows/"]),
+    (CiSystem::Jenkins, &["Jenkinsfile"]),
+    (CiSystem::Circleci, &[".circleci/config.yml"]),
+    (CiSystem::Travis, &[".travis.yml"]),
+    (CiSystem::Azure, &["azure-pipelines.yml"]),
+    (CiSystem::Bitbucket, &["bitbucket-pipelines.yml"]),
+    (CiSystem::Buildkite, &[".buildkite/"]),
+    (CiSystem::Drone, &[".drone.yml"]),
+    (CiSystem::Woodpecker, &[".woodpecker.yml", ".woodpecker/"]),
+    (CiSystem::Appveyor, &["appveyor.yml", ".appveyor.yml"]),
+    (CiSystem::Sourcehut, &[".build.yml", ".builds/"]),
+];
+
+impl CiSystem {
+    /// The name used in output.
+    pub fn name(self) -> &'static str {
+        match self {
+            CiSystem::Github => "github",
+            CiSystem::Gitlab => "gitlab",
+            CiSystem::Forgejo => "forgejo",
+            CiSystem::Gitea => "gitea",
+            CiSystem::Jenkins => "jenkins",
+            CiSystem::Circleci => "circleci",
+            CiSystem::Travis => "travis",
+            CiSystem::Azure => "azure",
+            CiSystem::Bitbucket => "bitbucket",
+            CiSystem::Buildkite => "buildkite",
+            CiSystem::Drone => "drone",
+            CiSystem::Woodpecker => "woodpecker",
+            CiSystem::Appveyor => "appveyor",
+            CiSystem::Sourcehut => "sourcehut",
+        }
+    }
+}
+
+/// Whether the working tree at `path` has `config`: a file, or for `dir/` a directory that
+/// is not empty.
+fn has_config(path: &Path, config: &str) -> bool {
+    match config.strip_suffix('/') {
+        Some(dir) => path
+            .join(dir)
+            .read_dir()
+            .is_ok_and(|mut entries| entries.next().is_some()),
+        None => path.join(config).is_file(),
+    }
+}
+
+/// The CI systems the working tree at `path` is configured for, in the order of
+/// [`CiSystem`].
+pub fn detect(path: &Path) -> Vec<CiSystem> {
+    CONFIGS
+        .iter()
+        .filter(|(_, configs)| configs.iter().any(|config| has_config(path, config)))
+        .map(|(system, _)| *system)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect() -> std::io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        assert_eq!(detect(repo), []);
+
+        // An empty workflows directory configures nothing.
+        fs::create_dir_all(repo.join(".github/workflows"))?;
+        fs::create_dir_all(repo.join(".circleci"))?;
+        fs::write(repo.join(".gitlab-ci.yml"), "")?;
+        fs::write(repo.join("Jenkinsfile"), "")?;
+        assert_eq!(detect(repo), [CiSystem::Gitlab, CiSystem::Jenkins]);
+
+        fs::write(repo.join(".github/workflows/ci.yml"), "")?;
+        fs::write(repo.join(".circleci/config.yml"), "")?;
+        assert_eq!(
+            detect(repo),
+            [
+                CiSystem::Github,
+                CiSystem::Gitlab,
+                CiSystem::Jenkins,
+                CiSystem::Circleci
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+//! Cloning repositories listed by imports and manifests.
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use tracing::warn;
+
+use crate::locale;
+use crate::refs;
+
+/// What happened to a single repository passed to [`clone_repo`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloneOutcome {
+    /// The target already existed and was left alone.
+    Exists,
+    /// The clone was only printed because of `--dry-run`.
+    DryRun,
+    /// The repository was cloned.
+    Cloned,
+    /// `git` reported an error, which has already been printed.
+    Failed,
+}
+
+/// Clone `url` into `target` with `git clone`, then check out `version` if given.
+/// * `url` - The remote URL.
+/// * `target` - The directory to clone into; skipped if it already exists.
+/// * `version` - A branch, tag or commit to check out after cloning.
+/// * `dry_run` - Print what would be cloned without cloning.
+pub fn clone_repo(
+    url: &str,
+    target: &Path,
+    version: Option<&str>,
+    dry_run: bool,
+) -> Result<CloneOutcome> {
+    if target.exists() {
+        println!(
+            "{}",
+            locale::text("clone-exists", &[("path", &target.display())])
+        );
+        return Ok(CloneOutcome::Exists);
+    }
+    if dry_run {
+        println!(
+            "{}",
+            locale::text(
+                "clone-dry-run",
+                &[("url", &url), ("path", &target.display())]
+            )
+        );
+        return Ok(CloneOutcome::DryRun);
+    }
+    println!(
+        "{}",
+        locale::text(
+            "clone-cloning",
+            &[("url", &url), ("path", &target.display())]
+        )
+    );
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).with_context(|| locale::failed("create", &parent))?;
+    }
+    let status = Command::new("git")
+        .arg("clone")
+        .arg(url)
+        .arg(target)
+        .status()
+        .with_context(|| locale::text("failed-run-git", &[("args", &"clone")]))?;
+    if !status.success() {
+        warn!("git clone {} failed", url);
+        return Ok(CloneOutcome::Failed);
+    }
+    if let Some(version) = version {
+        let status = refs::git_command(target)
+            .args(["checkout", "--quiet", version])
+            .status()
+            .with_context(|| locale::text("failed-run-git", &[("args", &"checkout")]))?;
+        if !status.success() {
+            warn!("git checkout {} failed in {}", version, target.display());
+            return Ok(CloneOutcome::Failed);
+        }
+    }
+    Ok(CloneOutcome::Cloned)
+}
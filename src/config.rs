@@ -0,0 +1,1037 @@
+//! The user configuration file, `~/.config/lg/config.toml`.
+//!
+//! ```toml
+//! format = "json"
+//!
+//! [scan]
+//! root = "~/src"
+//! tree = true
+//! exclude = ["archive/*"]
+//! prune = ["node_modules", "target"]
+//! history = true
+//!
+//! [roots.work]
+//! path = "~/work"
+//! exclude = ["vendor"]
+//!
+//! [serve]
+//! listen = "0.0.0.0:7777"
+//!
+//! [alias]
+//! work = "--root work -f json"
+//!
+//! [keys]
+//! o = "code {path}"
+//! ```
+//!
+//! `format` and `[scan]` set defaults for listing and for every subcommand that scans.
+//! `[roots.NAME]` tables define named directories, selected with `--root NAME`, whose settings
+//! add to or override `[scan]`. Any other table sets default options for the subcommand of the
+//! same name: each key becomes a `--key` option inserted before those on the command line, so
+//! the command line wins. `[alias]` defines words that expand to command-line arguments, and
+//! `[keys]` binds keys in `lg pick` (see [`crate::pick::Keymap`]).
+//!
+//! Options can also be set with environment variables, for when templating a command line is
+//! awkward, as in CI: `LG_` and the long option's name in capitals, with `_` for `-`, such as
+//! `LG_FORMAT`, `LG_EXCLUDE` or `LG_MAX_DEPTH`. [`Config::resolve`] layers the sources, each
+//! overriding the ones before it:
+//!
+//! 1. built-in defaults;
+//! 2. the configuration file;
+//! 3. the environment;
+//! 4. the command line.
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::dirs;
+use crate::locale;
+use crate::output::OutputFormat;
+use crate::shell;
+
+/// The parsed configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The default output format when listing.
+    pub format: Option<OutputFormat>,
+    pub scan: ScanConfig,
+    /// Command aliases: each name expands to the arguments in its value.
+    pub alias: BTreeMap<String, String>,
+    /// Named directories to scan, by name.
+    pub roots: BTreeMap<String, RootConfig>,
+    /// Key bindings for `lg pick`: each key maps to a built-in binding's name or a command.
+    pub keys: BTreeMap<String, String>,
+    /// Default options for other subcommands, by subcommand name.
+    #[serde(flatten)]
+    pub commands: BTreeMap<String, toml::Table>,
+}
+
+/// Defaults for the options controlling where and how repositories are discovered.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScanConfig {
+    /// The directory to search when none is given.
+    pub root: Option<PathBuf>,
+    /// Search recursively.
+    pub tree: bool,
+    /// Glob patterns for directories to skip, like `--exclude`.
+    pub exclude: Vec<String>,
+    /// Directory names that are never descended into.
+    pub prune: Vec<String>,
+    /// Append a summary of each listing to the history file; see [`crate::history`].
+    pub history: bool,
+}
+
+/// A named directory to scan and the settings to scan it with.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RootConfig {
+    pub path: PathBuf,
+    /// Search recursively; defaults to `[scan]`'s setting.
+    pub tree: Option<bool>,
+    pub max_depth: Option<usize>,
+    /// Glob patterns for directories to skip, in addition to `[scan]`'s.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Directory names never descended into, in addition to `[scan]`'s.
+    #[serde(default)]
+    pub prune: Vec<String>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// The default configuration file: `config.toml` in [`dirs::config_dir`].
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("config.toml"))
+}
+
+impl Config {
+    /// Read the configuration from `path`.
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).with_context(|| locale::failed("read", &path))?;
+        let mut config: Config =
+            toml::from_str(&contents).with_context(|| locale::failed("parse", &path))?;
+        config.scan.root = config.scan.root.as_deref().map(dirs::expand_tilde);
+        for root in config.roots.values_mut() {
+            root.path = dirs::expand_tilde(&root.path);
+        }
+        Ok(config)
+    }
+
+    /// Load the configuration selected by the command line in `args`, as [`Config::path`]
+    /// chooses it.
+    pub fn load(args: &[OsString]) -> Result<Self> {
+        match Config::path(args) {
+            Some(path) => Config::read(&path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    /// The configuration file selected by the command line in `args`: none with
+    /// `--no-config`, `PATH` with `--config PATH` or else `$LG_CONFIG`, and otherwise the
+    /// default file if it exists.
+    pub fn path(args: &[OsString]) -> Option<PathBuf> {
+        let mut explicit = None;
+        let mut args = args.iter().skip(1);
+        while let Some(arg) = args.next() {
+            let Some(arg) = arg.to_str() else {
+                continue;
+            };
+            if arg == "--" {
+                break;
+            } else if arg == "--no-config" {
+                return None;
+            } else if arg == "--config" {
+                explicit = args.next().map(PathBuf::from);
+            } else if let Some(path) = arg.strip_prefix("--config=") {
+                explicit = Some(PathBuf::from(path));
+            }
+        }
+        Config::select(explicit)
+    }
+
+    /// The configuration file to read, given the path passed with `--config` if there was one:
+    /// that path, or else `$LG_CONFIG`, and otherwise the default file if it exists.
+    pub fn select(explicit: Option<PathBuf>) -> Option<PathBuf> {
+        explicit
+            .or_else(|| {
+                std::env::var_os(CONFIG_VAR)
+                    .filter(|path| !path.is_empty())
+                    .map(PathBuf::from)
+            })
+            .or_else(|| default_path().filter(|path| path.is_file()))
+    }
+
+    /// The root called `name`.
+    pub fn root(&self, name: &str) -> Result<&RootConfig> {
+        match self.roots.get(name) {
+            Some(root) => Ok(root),
+            None if self.roots.is_empty() => {
+                bail!(locale::text(
+                    "config-no-roots-named",
+                    &[("name", &format_args!("{:?}", name))]
+                ))
+            }
+            None => bail!(locale::text(
+                "config-no-root-named",
+                &[
+                    ("name", &format_args!("{:?}", name)),
+                    (
+                        "roots",
+                        &self.roots.keys().cloned().collect::<Vec<_>>().join(", ")
+                    )
+                ]
+            )),
+        }
+    }
+
+    /// Expand an alias used as the command in `args`, the way git does.
+    /// * `args` - The command line, including the program name.
+    /// * `subcommands` - The names of the known subcommands, which aliases cannot replace.
+    ///
+    /// The command is the first argument that is not an option, skipping the value of
+    /// `--config`. Aliases may refer to other aliases, but not recursively.
+    pub fn expand_alias(&self, args: Vec<OsString>, subcommands: &[&str]) -> Result<Vec<OsString>> {
+        let mut args = args;
+        let mut seen = Vec::new();
+        loop {
+            let mut position = None;
+            let mut i = 1;
+            while i < args.len() {
+                match args[i].to_str() {
+                    Some("--") => break,
+                    Some("--config") => i += 1,
+                    Some(arg) if arg.starts_with('-') => {}
+                    _ => {
+                        position = Some(i);
+                        break;
+                    }
+                }
+                i += 1;
+            }
+            let Some(position) = position else {
+                return Ok(args);
+            };
+            let name = args[position].to_string_lossy().into_owned();
+            if subcommands.contains(&name.as_str()) {
+                return Ok(args);
+            }
+            let Some(expansion) = self.alias.get(&name) else {
+                return Ok(args);
+            };
+            if seen.contains(&name) {
+                bail!(locale::text(
+                    "alias-recursive",
+                    &[("name", &format_args!("{:?}", name))]
+                ));
+            }
+            let words = shell::split(expansion).with_context(|| {
+                locale::text("in-alias", &[("name", &format_args!("{:?}", name))])
+            })?;
+            args.splice(position..=position, words.into_iter().map(OsString::from));
+            seen.push(name);
+        }
+    }
+
+    /// Resolve the command line `args` into the one to parse: expand any alias, then insert
+    /// the options set in the environment `vars`, then the configured defaults, so that each
+    /// is overridden by those after it. `LG_ROOTS` adds named roots to the configuration.
+    /// * `subcommands` - The names of the known subcommands.
+    /// * `options` - The options of the subcommand named, or of `lg` itself for `None`, that
+    ///   the environment may set.
+    pub fn resolve(
+        &mut self,
+        args: Vec<OsString>,
+        subcommands: &[&str],
+        options: impl Fn(Option<&str>) -> Vec<EnvOption>,
+        vars: &BTreeMap<String, String>,
+    ) -> Result<Vec<OsString>> {
+        if let Some(roots) = vars.get(ROOTS_VAR) {
+            self.add_roots(roots)
+                .with_context(|| locale::text("in-var", &[("var", &ROOTS_VAR)]))?;
+        }
+        let args = self.expand_alias(args, subcommands)?;
+        let position = command_position(&args, subcommands);
+        let name = position.map(|i| args[i].to_string_lossy().into_owned());
+        let set = env_args(&args, &options(name.as_deref()), vars)?;
+        let mut args = args;
+        let at = position.map_or(1.min(args.len()), |i| i + 1);
+        args.splice(at..at, set);
+        self.expand_args(args, subcommands)
+    }
+
+    /// Add the roots in `roots`, comma-separated `NAME=PATH` pairs, replacing any configured
+    /// with the same names.
+    fn add_roots(&mut self, roots: &str) -> Result<()> {
+        for root in roots
+            .split(',')
+            .map(str::trim)
+            .filter(|root| !root.is_empty())
+        {
+            let Some((name, path)) = root.split_once('=') else {
+                bail!(locale::text(
+                    "expected-root",
+                    &[("root", &format_args!("{:?}", root))]
+                ));
+            };
+            self.roots.insert(
+                name.trim().to_string(),
+                RootConfig {
+                    path: dirs::expand_tilde(Path::new(path.trim())),
+                    tree: None,
+                    max_depth: None,
+                    exclude: Vec::new(),
+                    prune: Vec::new(),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Insert the configured defaults for the subcommand in `args` right after its name.
+    /// * `args` - The command line, including the program name.
+    /// * `subcommands` - The names of the known subcommands.
+    pub fn expand_args(&self, args: Vec<OsString>, subcommands: &[&str]) -> Result<Vec<OsString>> {
+        let Some(position) = command_position(&args, subcommands) else {
+            return Ok(args);
+        };
+        let name = args[position].to_string_lossy().into_owned();
+        let Some(table) = self.commands.get(&name) else {
+            return Ok(args);
+        };
+        let defaults = table_args(table)
+            .with_context(|| locale::text("in-config-section", &[("section", &name)]))?;
+        let mut expanded = args;
+        expanded.splice(position + 1..position + 1, defaults);
+        Ok(expanded)
+    }
+}
+
+/// The index in `args` of the subcommand's name, if one is given.
+fn command_position(args: &[OsString], subcommands: &[&str]) -> Option<usize> {
+    args.iter()
+        .skip(1)
+        .take_while(|arg| *arg != "--")
+        .position(|arg| arg.to_str().is_some_and(|a| subcommands.contains(&a)))
+        .map(|i| i + 1)
+}
+
+/// The environment variable naming the configuration file, like `--config`.
+const CONFIG_VAR: &str = "LG_CONFIG";
+
+/// The environment variable defining named roots, as `NAME=PATH,...`.
+const ROOTS_VAR: &str = "LG_ROOTS";
+
+/// Environment variables for options that are not named after them.
+const ENV_ALIASES: &[(&str, &str)] = &[("LG_THREADS", "jobs")];
+
+/// An option that can be set from the environment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnvOption {
+    /// The option's long name, without the `--`.
+    pub long: String,
+    pub short: Option<char>,
+    pub kind: EnvKind,
+}
+
+/// How an option set from the environment takes its value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvKind {
+    /// A switch, set by a boolean such as `1` or `true`.
+    Flag,
+    /// A switch that can be repeated, set by a count or a boolean.
+    Count,
+    /// An option taking one value.
+    Value,
+    /// A repeatable option, taking comma-separated values.
+    List,
+}
+
+impl EnvOption {
+    /// The environment variables setting this option.
+    fn vars(&self) -> impl Iterator<Item = String> + '_ {
+        let own = format!("LG_{}", self.long.to_uppercase().replace('-', "_"));
+        let aliases = ENV_ALIASES
+            .iter()
+            .filter(|(_, long)| *long == self.long)
+            .map(|(var, _)| var.to_string());
+        std::iter::once(own).chain(aliases)
+    }
+
+    /// Whether the command line `args` gives this option.
+    fn given(&self, args: &[OsString]) -> bool {
+        let long = format!("--{}", self.long);
+        args.iter()
+            .skip(1)
+            .filter_map(|arg| arg.to_str())
+            .take_while(|arg| *arg != "--")
+            .any(|arg| {
+                arg == long
+                    || arg
+                        .strip_prefix(&long)
+                        .is_some_and(|rest| rest.starts_with('='))
+                    || self.short.is_some_and(|short| {
+                        arg.len() > 1
+                            && arg.starts_with('-')
+                            && !arg.starts_with("--")
+                            && arg[1..].contains(short)
+                    })
+            })
+    }
+}
+
+/// The command-line options set by the environment `vars` of `options` not given in `args`.
+fn env_args(
+    args: &[OsString],
+    options: &[EnvOption],
+    vars: &BTreeMap<String, String>,
+) -> Result<Vec<OsString>> {
+    let mut set = Vec::new();
+    for option in options {
+        let Some((var, value)) = option
+            .vars()
+            .find_map(|var| vars.get(&var).map(|value| (var, value.trim())))
+        else {
+            continue;
+        };
+        if value.is_empty() || option.given(args) {
+            continue;
+        }
+        let flag = format!("--{}", option.long);
+        let times = match option.kind {
+            EnvKind::Flag => usize::from(
+                parse_bool(value).with_context(|| locale::text("in-var", &[("var", &var)]))?,
+            ),
+            EnvKind::Count => match value.parse() {
+                Ok(count) => count,
+                Err(_) => usize::from(
+                    parse_bool(value).with_context(|| locale::text("in-var", &[("var", &var)]))?,
+                ),
+            },
+            EnvKind::Value => {
+                set.push(format!("{}={}", flag, value).into());
+                continue;
+            }
+            EnvKind::List => {
+                set.extend(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|value| !value.is_empty())
+                        .map(|value| OsString::from(format!("{}={}", flag, value))),
+                );
+                continue;
+            }
+        };
+        set.extend(std::iter::repeat_n(OsString::from(&flag), times));
+    }
+    Ok(set)
+}
+
+fn parse_bool(value: &str) -> Result<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => bail!(locale::text(
+            "expected-bool",
+            &[("value", &format_args!("{:?}", value))]
+        )),
+    }
+}
+
+/// The command-line options equivalent to a table of defaults.
+fn table_args(table: &toml::Table) -> Result<Vec<OsString>> {
+    let mut args = Vec::new();
+    for (key, value) in table {
+        let flag = format!("--{}", key.replace('_', "-"));
+        let values = match value {
+            toml::Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        };
+        for value in values {
+            match value {
+                toml::Value::Boolean(true) => args.push(flag.clone().into()),
+                toml::Value::Boolean(false) => {}
+                toml::Value::String(s) => args.extend([flag.clone().into(), s.into()]),
+                toml::Value::Integer(_) | toml::Value::Float(_) => {
+                    args.extend([flag.clone().into(), value.to_string().into()])
+                }
+                _ => bail!(locale::text(
+                    "unsupported-value",
+                    &[("key", &key), ("value", &value)]
+                )),
+            }
+        }
+    }
+    Ok(args)
+}
+
+/// The keys of `[scan]`.
+const SCAN_KEYS: &[&str] = &["root", "tree", "exclude", "prune", "history"];
+
+/// The keys of a `[roots.NAME]` table.
+const ROOT_KEYS: &[&str] = &["path", "tree", "max_depth", "exclude", "prune"];
+
+/// The top-level keys that are not subcommand defaults.
+const TOP_KEYS: &[&str] = &["format", "scan", "alias", "roots", "keys"];
+
+/// What [`check`] knows of a subcommand: its long options, without the `--`, and the pairs of
+/// them that cannot be given together.
+#[derive(Clone, Debug, Default)]
+pub struct CommandOptions {
+    pub options: Vec<String>,
+    pub conflicts: Vec<(String, String)>,
+}
+
+/// How bad a problem [`check`] found is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// The file will not load, or a setting is ignored or rejected.
+    Error,
+    /// The file loads but probably does not do what was meant.
+    Warning,
+}
+
+/// A problem found in a configuration file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The line the problem is on, counting from 1, if it has one.
+    pub line: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// The problem, without its line, in the current locale.
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let id = match self.severity {
+            Severity::Error => "diagnostic-error",
+            Severity::Warning => "diagnostic-warning",
+        };
+        f.write_str(&locale::text(id, &[("message", &self.message)]))
+    }
+}
+
+/// Check the configuration file `contents` for what would make it fail to load or silently
+/// change behaviour: unknown keys and sections, bad globs, roots that do not exist, options
+/// that conflict and aliases that are never used.
+/// * `commands` - The subcommands, by name, whose sections hold their default options.
+pub fn check(contents: &str, commands: &BTreeMap<String, CommandOptions>) -> Vec<Diagnostic> {
+    use toml::de::{DeTable, DeValue};
+
+    let line = |offset: usize| Some(contents[..offset].matches('\n').count() + 1);
+    let mut found = Vec::new();
+    let table = match DeTable::parse(contents) {
+        Ok(table) => table.into_inner(),
+        Err(e) => {
+            return vec![Diagnostic {
+                line: e.span().and_then(|span| line(span.start)),
+                severity: Severity::Error,
+                message: e.message().to_string(),
+            }]
+        }
+    };
+    let mut report = |offset: usize, severity: Severity, message: String| {
+        found.push(Diagnostic {
+            line: line(offset),
+            severity,
+            message,
+        })
+    };
+    let unknown = |what: String, key: &str, known: &[&str]| {
+        let quoted = format!("{:?}", key);
+        match suggest(key, known) {
+            Some(close) => locale::text(
+                "check-unknown-suggest",
+                &[
+                    ("what", &what),
+                    ("key", &quoted),
+                    ("close", &format_args!("{:?}", close)),
+                ],
+            ),
+            None => locale::text("check-unknown", &[("what", &what), ("key", &quoted)]),
+        }
+    };
+    // Check that each glob of an `exclude` array compiles.
+    let check_globs = |value: &toml::Spanned<DeValue>,
+                       report: &mut dyn FnMut(usize, Severity, String)| {
+        let DeValue::Array(patterns) = value.get_ref() else {
+            return;
+        };
+        for pattern in patterns {
+            if let DeValue::String(s) = pattern.get_ref() {
+                if let Err(e) = globset::Glob::new(s) {
+                    report(
+                        pattern.span().start,
+                        Severity::Error,
+                        locale::text(
+                            "check-bad-glob",
+                            &[("glob", &format_args!("{:?}", s)), ("error", &e.kind())],
+                        ),
+                    );
+                }
+            }
+        }
+    };
+    let check_dir = |value: &toml::Spanned<DeValue>,
+                     report: &mut dyn FnMut(usize, Severity, String)| {
+        if let DeValue::String(path) = value.get_ref() {
+            let expanded = dirs::expand_tilde(Path::new(path.as_ref()));
+            if !expanded.is_dir() {
+                report(
+                    value.span().start,
+                    Severity::Warning,
+                    locale::text("check-not-dir", &[("path", &format_args!("{:?}", path))]),
+                );
+            }
+        }
+    };
+    let scan_tree = table
+        .get("scan")
+        .and_then(|scan| match scan.get_ref() {
+            DeValue::Table(scan) => scan.get("tree"),
+            _ => None,
+        })
+        .is_some_and(|tree| matches!(tree.get_ref(), DeValue::Boolean(true)));
+
+    for (key, value) in &table {
+        let name = key.get_ref().as_ref();
+        let at = key.span().start;
+        match (name, value.get_ref()) {
+            ("scan", DeValue::Table(scan)) => {
+                for (key, value) in scan {
+                    match key.get_ref().as_ref() {
+                        "exclude" => check_globs(value, &mut report),
+                        "root" => check_dir(value, &mut report),
+                        k if !SCAN_KEYS.contains(&k) => report(
+                            key.span().start,
+                            Severity::Error,
+                            unknown(locale::text("check-scan-key", &[]), k, SCAN_KEYS),
+                        ),
+                        _ => {}
+                    }
+                }
+            }
+            ("roots", DeValue::Table(roots)) => {
+                for (root, value) in roots {
+                    let DeValue::Table(settings) = value.get_ref() else {
+                        continue;
+                    };
+                    let root = root.get_ref().as_ref();
+                    for (key, value) in settings {
+                        match key.get_ref().as_ref() {
+                            "exclude" => check_globs(value, &mut report),
+                            "path" => check_dir(value, &mut report),
+                            "max_depth" => {
+                                let tree = settings.get("tree").map_or(scan_tree, |tree| {
+                                    matches!(tree.get_ref(), DeValue::Boolean(true))
+                                });
+                                if !tree {
+                                    let message = locale::text(
+                                        "check-max-depth",
+                                        &[("root", &format_args!("{:?}", root))],
+                                    );
+                                    report(key.span().start, Severity::Warning, message);
+                                }
+                            }
+                            k if !ROOT_KEYS.contains(&k) => report(
+                                key.span().start,
+                                Severity::Error,
+                                unknown(
+                                    locale::text("check-root-key", &[("root", &root)]),
+                                    k,
+                                    ROOT_KEYS,
+                                ),
+                            ),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            ("alias", DeValue::Table(aliases)) => {
+                for (alias, value) in aliases {
+                    let alias = alias.get_ref().as_ref();
+                    if commands.contains_key(alias) {
+                        report(
+                            value.span().start,
+                            Severity::Warning,
+                            locale::text(
+                                "check-alias-unused",
+                                &[("alias", &format_args!("{:?}", alias))],
+                            ),
+                        );
+                    }
+                    if let DeValue::String(expansion) = value.get_ref() {
+                        if let Err(e) = shell::split(expansion) {
+                            report(
+                                value.span().start,
+                                Severity::Error,
+                                locale::text(
+                                    "check-alias",
+                                    &[
+                                        ("alias", &format_args!("{:?}", alias)),
+                                        ("error", &format_args!("{:#}", e)),
+                                    ],
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+            (name, _) if TOP_KEYS.contains(&name) => {}
+            (name, DeValue::Table(defaults)) => {
+                let Some(command) = commands.get(name) else {
+                    let names: Vec<_> = commands
+                        .keys()
+                        .map(String::as_str)
+                        .chain(TOP_KEYS.iter().copied())
+                        .collect();
+                    let section = locale::text("check-section", &[]);
+                    report(at, Severity::Error, unknown(section, name, &names));
+                    continue;
+                };
+                let options: Vec<_> = command.options.iter().map(String::as_str).collect();
+                let mut given = Vec::new();
+                for (key, value) in defaults {
+                    let option = key.get_ref().replace('_', "-");
+                    if !options.contains(&option.as_str()) {
+                        report(
+                            key.span().start,
+                            Severity::Error,
+                            unknown(
+                                locale::text("check-option", &[("command", &name)]),
+                                &option,
+                                &options,
+                            ),
+                        );
+                        continue;
+                    }
+                    if option == "exclude" {
+                        check_globs(value, &mut report);
+                    }
+                    // `false` leaves a flag out, so cannot conflict.
+                    if !matches!(value.get_ref(), DeValue::Boolean(false)) {
+                        given.push((option, key.span().start));
+                    }
+                }
+                // Each conflict may be listed from both sides.
+                let mut conflicts: Vec<_> = command
+                    .conflicts
+                    .iter()
+                    .map(|(a, b)| if a < b { (a, b) } else { (b, a) })
+                    .collect();
+                conflicts.sort();
+                conflicts.dedup();
+                for (a, b) in conflicts {
+                    let (Some(first), Some(second)) = (
+                        given.iter().find(|(o, _)| o == a),
+                        given.iter().find(|(o, _)| o == b),
+                    ) else {
+                        continue;
+                    };
+                    report(
+                        first.1.max(second.1),
+                        Severity::Error,
+                        locale::text(
+                            "check-conflict",
+                            &[("first", a), ("second", b), ("command", &name)],
+                        ),
+                    );
+                }
+            }
+            (name, _) => {
+                let key = locale::text("check-key", &[]);
+                report(at, Severity::Error, unknown(key, name, TOP_KEYS))
+            }
+        }
+    }
+    // Anything the checks above missed, such as a value of the wrong type, fails to load.
+    if !found.iter().any(|d| d.severity == Severity::Error) {
+        if let Err(e) = toml::from_str::<Config>(contents) {
+            found.push(Diagnostic {
+                line: e.span().and_then(|span| line(span.start)),
+                severity: Severity::Error,
+                message: e.message().to_string(),
+            });
+        }
+    }
+    found.sort_by_key(|d| d.line);
+    found
+}
+
+/// The one of `candidates` closest to `key`, if any is within two edits of it.
+fn suggest<'a>(key: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(key, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// The Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substituted = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Make `config` the one returned by [`get`]. Only the first call has an effect.
+pub fn init(config: Config) {
+    let _ = CONFIG.set(config);
+}
+
+/// The configuration passed to [`init`], or the empty configuration.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn os(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_expand_alias() -> Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+[alias]
+stale = "-t --exclude 'old stuff'"
+work = "stale --root work"
+index = "--oops"
+loop = "loop"
+"#,
+        )?;
+        let subcommands = ["index"];
+        assert_eq!(
+            config.expand_alias(
+                os(&["lg", "--config", "work", "work", "-f", "json"]),
+                &subcommands
+            )?,
+            os(&[
+                "lg",
+                "--config",
+                "work",
+                "-t",
+                "--exclude",
+                "old stuff",
+                "--root",
+                "work",
+                "-f",
+                "json"
+            ])
+        );
+        assert_eq!(
+            config.expand_alias(os(&["lg", "index"]), &subcommands)?,
+            os(&["lg", "index"])
+        );
+        assert_eq!(
+            config.expand_alias(os(&["lg", "/src"]), &subcommands)?,
+            os(&["lg", "/src"])
+        );
+        assert!(config
+            .expand_alias(os(&["lg", "loop"]), &subcommands)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check() {
+        let commands = BTreeMap::from([(
+            "serve".to_string(),
+            CommandOptions {
+                options: vec!["listen".to_string(), "tree".to_string(), "ui".to_string()],
+                conflicts: vec![
+                    ("listen".to_string(), "ui".to_string()),
+                    ("ui".to_string(), "listen".to_string()),
+                ],
+            },
+        )]);
+        let check = |contents: &str| -> Vec<String> {
+            check(contents, &commands)
+                .iter()
+                .map(|d| format!("{}: {}", d.line.unwrap_or(0), d))
+                .collect()
+        };
+        assert_eq!(
+            check(
+                r#"
+[scan]
+tre = true
+exclude = ["ok", "a["]
+
+[roots.work]
+path = "/no/such/dir"
+max_depth = 2
+
+[alias]
+serve = "-t"
+
+[serv]
+listen = ":80"
+
+[serve]
+listen = ":80"
+ui = true
+tree = false
+"#
+            ),
+            [
+                r#"3: error: unknown [scan] key "tre"; did you mean "tree"?"#,
+                r#"4: error: bad glob "a[": unclosed character class; missing ']'"#,
+                r#"7: warning: "/no/such/dir" is not a directory"#,
+                r#"8: warning: max_depth of root "work" has no effect without tree = true"#,
+                r#"11: warning: alias "serve" is never used: a subcommand has that name"#,
+                r#"13: error: unknown section "serv"; did you mean "serve"?"#,
+                "18: error: --listen and --ui of lg serve cannot be used together",
+            ]
+        );
+        // Values of the wrong type fail to load.
+        assert_eq!(check("format = 3\n"), ["1: error: wanted string or table"]);
+        assert_eq!(check("[scan\n").len(), 1);
+        assert_eq!(check("format = \"json\"\n"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_resolve() -> Result<()> {
+        let mut config: Config = toml::from_str("[serve]\ninterval = 60\n")?;
+        let options = |name: Option<&str>| {
+            let option = |long: &str, short, kind| EnvOption {
+                long: long.to_string(),
+                short,
+                kind,
+            };
+            match name {
+                Some("serve") => vec![
+                    option("interval", None, EnvKind::Value),
+                    option("exclude", None, EnvKind::List),
+                    option("tree", Some('t'), EnvKind::Flag),
+                    option("jobs", Some('j'), EnvKind::Value),
+                    option("verbose", Some('v'), EnvKind::Count),
+                ],
+                _ => vec![option("format", Some('f'), EnvKind::Value)],
+            }
+        };
+        let vars: BTreeMap<String, String> = [
+            ("LG_INTERVAL", "30"),
+            ("LG_EXCLUDE", "a, b"),
+            ("LG_TREE", "true"),
+            ("LG_THREADS", "4"),
+            ("LG_VERBOSE", "2"),
+            ("LG_FORMAT", "json"),
+            ("LG_ROOTS", "work=/work,oss=/oss"),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        let subcommands = ["serve"];
+
+        // Defaults from the file come first, then the environment, then the command line; the
+        // environment does not repeat options the command line gives.
+        assert_eq!(
+            config.resolve(
+                os(&["lg", "serve", "-tj", "2"]),
+                &subcommands,
+                options,
+                &vars
+            )?,
+            os(&[
+                "lg",
+                "serve",
+                "--interval",
+                "60",
+                "--interval=30",
+                "--exclude=a",
+                "--exclude=b",
+                "--verbose",
+                "--verbose",
+                "-tj",
+                "2"
+            ])
+        );
+        assert_eq!(config.roots["oss"].path, PathBuf::from("/oss"));
+        assert_eq!(
+            config.resolve(os(&["lg", "-t"]), &subcommands, options, &vars)?,
+            os(&["lg", "--format=json", "-t"])
+        );
+
+        let vars = BTreeMap::from([("LG_TREE".to_string(), "maybe".to_string())]);
+        assert!(config
+            .resolve(os(&["lg", "serve"]), &subcommands, options, &vars)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_args() -> Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+format = "json"
+
+[scan]
+prune = ["node_modules"]
+
+[serve]
+listen = "0.0.0.0:80"
+interval = 60
+tree = true
+exclude = ["a", "b"]
+"#,
+        )?;
+        assert_eq!(config.format, Some(OutputFormat::Json));
+        assert_eq!(config.scan.prune, vec!["node_modules"]);
+        assert!(config.root("work").is_err());
+
+        let subcommands = ["serve", "index"];
+        assert_eq!(
+            config.expand_args(
+                os(&["lg", "-b", "native", "serve", "--interval", "5"]),
+                &subcommands
+            )?,
+            os(&[
+                "lg",
+                "-b",
+                "native",
+                "serve",
+                "--exclude",
+                "a",
+                "--exclude",
+                "b",
+                "--interval",
+                "60",
+                "--listen",
+                "0.0.0.0:80",
+                "--tree",
+                "--interval",
+                "5"
+            ])
+        );
+        assert_eq!(
+            config.expand_args(os(&["lg", "index"]), &subcommands)?,
+            os(&["lg", "index"])
+        );
+        assert_eq!(
+            config.expand_args(os(&["lg", "--", "serve"]), &subcommands)?,
+            os(&["lg", "--", "serve"])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+//! The background indexer behind `lg daemon`, and the client that lets index queries use it.
+//!
+//! The daemon keeps the index entries for a directory current by watching it (see
+//! [`crate::watch`]) and answers [`Request`]s over a Unix socket in the cache directory, one
+//! line of JSON each way. [`ask`] sends a request to the daemon if one is listening and
+//! otherwise answers it from the index file, so callers need not care whether it is running.
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::dirs::cache_dir;
+use crate::fuzzy;
+use crate::index::{Index, IndexEntry};
+use crate::locale;
+
+/// A question about the indexed repositories.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Request {
+    /// Every indexed repository.
+    Entries,
+    /// The repositories matching `query`, best first; see [`fuzzy::rank`].
+    Rank { query: Vec<String> },
+    /// The innermost repository containing `path`; see [`Index::find_containing`].
+    Containing { path: PathBuf },
+    /// The repositories whose name starts with `prefix`; see [`Index::with_name_prefix`].
+    Prefix { prefix: String },
+}
+
+impl Request {
+    /// Answer the request from `index`.
+    pub fn answer(&self, index: &Index) -> Vec<IndexEntry> {
+        match self {
+            Request::Entries => index.entries.clone(),
+            Request::Rank { query } => fuzzy::rank(query, &index.entries)
+                .into_iter()
+                .map(|(_, entry)| entry.clone())
+                .collect(),
+            Request::Containing { path } => {
+                index.find_containing(path).into_iter().cloned().collect()
+            }
+            Request::Prefix { prefix } => index
+                .with_name_prefix(prefix)
+                .into_iter()
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// The daemon's reply to a [`Request`].
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Response {
+    #[serde(default)]
+    entries: Vec<IndexEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// The location of the daemon's socket.
+pub fn socket() -> Result<PathBuf> {
+    cache_dir()
+        .map(|dir| dir.join("daemon.sock"))
+        .ok_or_else(|| anyhow!(locale::text("no-cache-dir", &[])))
+}
+
+/// Answer `request` through the daemon if one is listening, otherwise from the index file.
+pub fn ask(request: &Request) -> Result<Vec<IndexEntry>> {
+    #[cfg(unix)]
+    if let Some(entries) = unix::ask(request) {
+        return Ok(entries);
+    }
+    Ok(request.answer(&Index::load()?))
+}
+
+#[cfg(unix)]
+pub use unix::run;
+
+/// Run the daemon; only supported where Unix domain sockets are.
+#[cfg(not(unix))]
+pub fn run(
+    _scanner: crate::scan::Scanner,
+    _root: &std::path::Path,
+    _reconcile: std::time::Duration,
+) -> Result<()> {
+    anyhow::bail!(locale::text("daemon-unsupported", &[]))
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, RwLock};
+    use std::thread;
+    use std::time::Duration;
+
+    use anyhow::{bail, Context, Result};
+    use tracing::{debug, info, warn};
+
+    use super::{socket, Request, Response};
+    use crate::index::{Index, IndexEntry};
+    use crate::locale;
+    use crate::scan::Scanner;
+    use crate::watch::Watch;
+
+    /// How long either side waits for the other before giving up on a request.
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// How long to wait for related events before rescanning.
+    const DELAY: Duration = Duration::from_millis(500);
+
+    /// Ask the daemon, or `None` if it is not running or cannot answer.
+    pub fn ask(request: &Request) -> Option<Vec<IndexEntry>> {
+        let stream = UnixStream::connect(socket().ok()?).ok()?;
+        match exchange(stream, request) {
+            Ok(entries) => Some(entries),
+            Err(e) => {
+                debug!("the daemon did not answer, reading the index: {:#}", e);
+                None
+            }
+        }
+    }
+
+    /// Send `request` over `stream` and read the reply.
+    fn exchange(mut stream: UnixStream, request: &Request) -> Result<Vec<IndexEntry>> {
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let mut line = serde_json::to_vec(request)?;
+        line.push(b'\n');
+        stream.write_all(&line)?;
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply)?;
+        let response: Response =
+            serde_json::from_str(&reply).context("Failed to parse the daemon's reply")?;
+        match response.error {
+            Some(error) => bail!("{}", error),
+            None => Ok(response.entries),
+        }
+    }
+
+    /// Answer one request on `stream` from the index.
+    fn serve(mut stream: UnixStream, index: &RwLock<Index>) -> Result<()> {
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => Response {
+                entries: request.answer(&index.read().unwrap()),
+                error: None,
+            },
+            Err(e) => Response {
+                entries: Vec::new(),
+                error: Some(format!("Invalid request: {}", e)),
+            },
+        };
+        let mut reply = serde_json::to_vec(&response)?;
+        reply.push(b'\n');
+        stream.write_all(&reply)?;
+        Ok(())
+    }
+
+    /// The socket file, removed when the daemon exits.
+    struct Socket(PathBuf);
+
+    impl Socket {
+        /// Listen on `path`, replacing a socket left behind by a daemon that is no longer running.
+        fn bind(path: &Path) -> Result<(Self, UnixListener)> {
+            if path.exists() {
+                if UnixStream::connect(path).is_ok() {
+                    bail!(locale::text(
+                        "daemon-listening",
+                        &[("path", &path.display())]
+                    ));
+                }
+                fs::remove_file(path).with_context(|| locale::failed("remove", &path))?;
+            }
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).with_context(|| locale::failed("create", &dir))?;
+            }
+            let listener =
+                UnixListener::bind(path).with_context(|| locale::failed("listen", &path))?;
+            Ok((Socket(path.to_path_buf()), listener))
+        }
+    }
+
+    impl Drop for Socket {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    /// Keep the index entries under `root` current and answer requests until an error occurs.
+    /// * `scanner` - The scanner for rescans.
+    /// * `root` - The absolute path of the directory to index.
+    /// * `reconcile` - How often to rescan all of `root`, to catch changes that notifications
+    ///   missed.
+    pub fn run(scanner: Scanner, root: &Path, reconcile: Duration) -> Result<()> {
+        let mut index = Index::load()?;
+        index.add_root(root);
+        let watch = Watch::new(scanner, root, index.entries_under(root));
+        let index = Arc::new(RwLock::new(index));
+
+        let (bound, listener) = Socket::bind(&socket()?)?;
+        eprintln!(
+            "{}",
+            locale::text("listening", &[("address", &bound.0.display())])
+        );
+        let serving = Arc::clone(&index);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let result = stream
+                    .map_err(Into::into)
+                    .and_then(|stream| serve(stream, &serving));
+                if let Err(e) = result {
+                    warn!("failed to answer a request: {:#}", e);
+                }
+            }
+        });
+
+        watch.run(DELAY, Some(reconcile), |changes| {
+            // Reload, so that entries written by `lg index` for other directories are kept.
+            let mut updated = Index::load()?;
+            updated.add_root(root);
+            updated.apply(changes);
+            updated.save()?;
+            info!(
+                added = changes.added.len(),
+                removed = changes.removed.len(),
+                changed = changes.changed.len(),
+                "index updated"
+            );
+            *index.write().unwrap() = updated;
+            Ok(())
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::collections::HashMap;
+
+        #[test]
+        fn test_exchange() -> Result<()> {
+            let mut index = Index::default();
+            index.entries = ["/src/lg", "/src/lgtm", "/src/tools"]
+                .into_iter()
+                .map(|path| IndexEntry {
+                    path: PathBuf::from(path),
+                    remotes: HashMap::new(),
+                    primary_remote: None,
+                    head: None,
+                })
+                .collect();
+            let index = RwLock::new(index);
+
+            let ask = |request: Request| -> Result<Vec<PathBuf>> {
+                let (client, server) = UnixStream::pair()?;
+                let entries = thread::scope(|scope| {
+                    scope.spawn(|| serve(server, &index));
+                    exchange(client, &request)
+                })?;
+                Ok(entries.into_iter().map(|entry| entry.path).collect())
+            };
+            assert_eq!(ask(Request::Entries)?.len(), 3);
+            assert_eq!(
+                ask(Request::Rank {
+                    query: vec!["too".to_string()]
+                })?,
+                vec![PathBuf::from("/src/tools")]
+            );
+            assert_eq!(
+                ask(Request::Containing {
+                    path: PathBuf::from("/src/lgtm/src")
+                })?,
+                vec![PathBuf::from("/src/lgtm")]
+            );
+            assert_eq!(
+                ask(Request::Prefix {
+                    prefix: "LG".to_string()
+                })?,
+                vec![PathBuf::from("/src/lg"), PathBuf::from("/src/lgtm")]
+            );
+
+            let (mut client, server) = UnixStream::pair()?;
+            client.write_all(b"{\"method\": \"unknown\"}\n")?;
+            serve(server, &index)?;
+            let mut reply = String::new();
+            BufReader::new(client).read_line(&mut reply)?;
+            assert!(reply.contains("Invalid request"), "{}", reply);
+            Ok(())
+        }
+    }
+}
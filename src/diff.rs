@@ -0,0 +1,173 @@
+//! Differences between two sets of repositories.
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::IndexEntry;
+use crate::locale;
+
+/// A repository present in both sets whose remotes or checked-out branch differ.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Changed {
+    pub path: PathBuf,
+    pub before: HashMap<String, String>,
+    pub after: HashMap<String, String>,
+    /// The head before, if it was read and differs from the head after.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head_before: Option<String>,
+    /// The head after, if it was read and differs from the head before.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head_after: Option<String>,
+}
+
+/// The repositories added, removed and changed between two scans.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Changes {
+    pub added: Vec<IndexEntry>,
+    pub removed: Vec<IndexEntry>,
+    pub changed: Vec<Changed>,
+}
+
+impl Changes {
+    /// Compare `before` with `after`, matching repositories by path.
+    pub fn between(before: &[IndexEntry], after: &[IndexEntry]) -> Self {
+        let before: BTreeMap<_, _> = before.iter().map(|e| (&e.path, e)).collect();
+        let after: BTreeMap<_, _> = after.iter().map(|e| (&e.path, e)).collect();
+        let mut changes = Changes::default();
+        for (path, entry) in &after {
+            match before.get(path) {
+                None => changes.added.push((*entry).clone()),
+                Some(old) if old.remotes != entry.remotes || old.head != entry.head => {
+                    let head_changed = old.head != entry.head;
+                    changes.changed.push(Changed {
+                        path: (*path).clone(),
+                        before: old.remotes.clone(),
+                        after: entry.remotes.clone(),
+                        head_before: old.head.clone().filter(|_| head_changed),
+                        head_after: entry.head.clone().filter(|_| head_changed),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        for (path, entry) in &before {
+            if !after.contains_key(path) {
+                changes.removed.push((*entry).clone());
+            }
+        }
+        changes
+    }
+
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl fmt::Display for Changes {
+    /// One line per change: `+` added, `-` removed, `~` remote or head changed, in the current
+    /// locale.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.added {
+            writeln!(f, "+ {}", entry.path.display())?;
+        }
+        for entry in &self.removed {
+            writeln!(f, "- {}", entry.path.display())?;
+        }
+        for changed in &self.changed {
+            let path = changed.path.display();
+            if changed.head_before.is_some() || changed.head_after.is_some() {
+                let none = locale::text("diff-none", &[]);
+                let head = |head: &Option<String>| head.clone().unwrap_or_else(|| none.clone());
+                let line = locale::text(
+                    "diff-head",
+                    &[
+                        ("before", &head(&changed.head_before)),
+                        ("after", &head(&changed.head_after)),
+                    ],
+                );
+                writeln!(f, "~ {}: {}", path, line)?;
+            }
+            let mut names: Vec<_> = changed.before.keys().chain(changed.after.keys()).collect();
+            names.sort();
+            names.dedup();
+            for name in names {
+                let line = match (changed.before.get(name), changed.after.get(name)) {
+                    (Some(old), Some(new)) if old != new => format!("{} {} -> {}", name, old, new),
+                    (Some(old), None) => {
+                        locale::text("diff-remote-removed", &[("name", name), ("url", old)])
+                    }
+                    (None, Some(new)) => {
+                        locale::text("diff-remote-added", &[("name", name), ("url", new)])
+                    }
+                    _ => continue,
+                };
+                writeln!(f, "~ {}: {}", path, line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, remotes: &[(&str, &str)]) -> IndexEntry {
+        IndexEntry {
+            path: PathBuf::from(path),
+            remotes: remotes
+                .iter()
+                .map(|(n, u)| (n.to_string(), u.to_string()))
+                .collect(),
+            primary_remote: None,
+            head: None,
+        }
+    }
+
+    #[test]
+    fn test_changes_between() {
+        let before = vec![
+            entry("/src/a", &[("origin", "https://example.com/a")]),
+            entry("/src/b", &[("origin", "https://example.com/b")]),
+            entry("/src/c", &[("origin", "https://example.com/c")]),
+        ];
+        let after = vec![
+            entry("/src/a", &[("origin", "https://example.com/a")]),
+            entry(
+                "/src/c",
+                &[
+                    ("origin", "https://example.com/c2"),
+                    ("fork", "https://example.com/me/c"),
+                ],
+            ),
+            entry("/src/d", &[("origin", "https://example.com/d")]),
+        ];
+        let changes = Changes::between(&before, &after);
+        assert_eq!(changes.added, vec![after[2].clone()]);
+        assert_eq!(changes.removed, vec![before[1].clone()]);
+        assert_eq!(changes.changed.len(), 1);
+        assert_eq!(
+            changes.to_string(),
+            "+ /src/d\n\
+             - /src/b\n\
+             ~ /src/c: fork https://example.com/me/c added\n\
+             ~ /src/c: origin https://example.com/c -> https://example.com/c2\n"
+        );
+        assert!(Changes::between(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn test_head_changes() {
+        let with_head = |head: &str| IndexEntry {
+            head: Some(head.to_string()),
+            ..entry("/src/a", &[("origin", "https://example.com/a")])
+        };
+        let changes = Changes::between(&[with_head("main")], &[with_head("release")]);
+        assert_eq!(changes.changed.len(), 1);
+        assert_eq!(changes.to_string(), "~ /src/a: head main -> release\n");
+        assert!(Changes::between(&[with_head("main")], &[with_head("main")]).is_empty());
+    }
+}
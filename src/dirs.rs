@@ -0,0 +1,53 @@
+//! Well-known per-user directories.
+use std::path::{Path, PathBuf};
+
+/// The current user's home directory, from `HOME` (or `USERPROFILE` on Windows).
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The directory `lg` keeps its cache in: `$LG_CACHE_DIR`, else `$XDG_CACHE_HOME/lg`, else
+/// `~/.cache/lg`.
+pub fn cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("LG_CACHE_DIR").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    std::env::var_os("XDG_CACHE_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".cache")))
+        .map(|cache| cache.join("lg"))
+}
+
+/// The directory `lg` keeps data it cannot regenerate in, such as snapshots: `$LG_DATA_DIR`,
+/// else `$XDG_DATA_HOME/lg`, else `~/.local/share/lg`.
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("LG_DATA_DIR").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    std::env::var_os("XDG_DATA_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".local/share")))
+        .map(|data| data.join("lg"))
+}
+
+/// The directory `lg` reads its configuration from: `$XDG_CONFIG_HOME/lg`, else `~/.config/lg`.
+pub fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".config")))
+        .map(|config| config.join("lg"))
+}
+
+/// Replace a leading `~` in `path` with the home directory.
+pub fn expand_tilde(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => home_dir().map_or_else(|| path.to_path_buf(), |home| home.join(rest)),
+        Err(_) => path.to_path_buf(),
+    }
+}
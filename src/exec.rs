@@ -0,0 +1,152 @@
+//! Running a command in each repository, as `lg exec` does, optionally in the environment the
+//! project sets up for itself with direnv's `.envrc` or a `.env` file.
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use anyhow::{bail, Context, Result};
+use tracing::warn;
+
+use crate::locale;
+
+/// Run `command`, a program and its arguments, in the repository at `path`, passing its output
+/// through.
+/// * `with_env` - Load the repository's environment first: through `direnv exec` if it has an
+///   `.envrc` and direnv is installed, otherwise from its `.env` file, if it has one.
+pub fn run(path: &Path, command: &[String], with_env: bool) -> Result<()> {
+    let Some((program, args)) = command.split_first() else {
+        bail!(locale::text("no-command", &[]));
+    };
+    let mut process = if with_env && path.join(".envrc").is_file() && direnv_installed() {
+        let mut process = Command::new("direnv");
+        process.arg("exec").arg(path).arg(program).args(args);
+        process
+    } else {
+        let mut process = Command::new(program);
+        process.args(args);
+        if with_env {
+            if path.join(".envrc").is_file() {
+                warn!(path = %path.display(), "direnv is not installed; ignoring .envrc");
+            }
+            process.envs(read_dotenv(path)?);
+        }
+        process
+    };
+    let status = process
+        .current_dir(path)
+        .stdin(Stdio::null())
+        .status()
+        .with_context(|| locale::text("failed-run-program", &[("program", program)]))?;
+    if !status.success() {
+        bail!(locale::text(
+            "exited-with",
+            &[("command", program), ("status", &status)]
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `direnv` can be run, checked once.
+fn direnv_installed() -> bool {
+    static INSTALLED: OnceLock<bool> = OnceLock::new();
+    *INSTALLED.get_or_init(|| {
+        Command::new("direnv")
+            .arg("version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+/// The variables set by the `.env` file in the directory `path`; none if there is no such file.
+fn read_dotenv(path: &Path) -> Result<Vec<(String, String)>> {
+    let file = path.join(".env");
+    if !file.is_file() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(&file).with_context(|| locale::failed("read", &file))?;
+    Ok(parse_dotenv(&contents))
+}
+
+/// Parse `.env` contents: `KEY=VALUE` lines, optionally starting with `export`, with `#`
+/// comments. Values may be in single quotes, taken literally, or double quotes, which
+/// understand `\n`, `\"` and `\\`; lines that set nothing are ignored.
+pub fn parse_dotenv(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() || key.starts_with('#') || key.contains(char::is_whitespace) {
+                return None;
+            }
+            Some((key.to_string(), parse_value(value.trim())))
+        })
+        .collect()
+}
+
+/// A `.env` value as written after the `=`.
+fn parse_value(value: &str) -> String {
+    if let Some(quoted) = value.strip_prefix('\'') {
+        return quoted.split('\'').next().unwrap_or_default().to_string();
+    }
+    if let Some(quoted) = value.strip_prefix('"') {
+        let mut parsed = String::new();
+        let mut chars = quoted.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => parsed.push('\n'),
+                    Some(escaped) => parsed.push(escaped),
+                    None => parsed.push('\\'),
+                },
+                c => parsed.push(c),
+            }
+        }
+        return parsed;
+    }
+    // An unquoted value ends at a comment.
+    match value.find(" #") {
+        Some(comment) => value[..comment].trim_end().to_string(),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dotenv() {
+        let pairs = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        assert_eq!(
+            parse_dotenv(
+                "# settings\n\
+                 PLAIN=value # note\n\
+                 export EXPORTED=1\n\
+                 SINGLE='$literal # kept'\n\
+                 DOUBLE=\"two\\nlines \\\"quoted\\\"\"\n\
+                 EMPTY=\n\
+                 not a variable\n\
+                 #COMMENTED=1\n"
+            ),
+            pairs(&[
+                ("PLAIN", "value"),
+                ("EXPORTED", "1"),
+                ("SINGLE", "$literal # kept"),
+                ("DOUBLE", "two\nlines \"quoted\""),
+                ("EMPTY", ""),
+            ])
+        );
+    }
+}
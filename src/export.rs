@@ -0,0 +1,292 @@
+//! Export of discovered repositories in formats understood by other tools.
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use serde_json::json;
+use tracing::warn;
+
+use crate::locale;
+use crate::paths;
+use crate::refs;
+use crate::shell::quote;
+use crate::url::{split_base, RemoteUrl};
+use crate::vcstool::Manifest;
+use crate::GitDirectory;
+
+/// The format to export repositories in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// One remote URL per line, suitable for piping into `ghq get`.
+    Ghq,
+    /// A myrepos `.mrconfig` with a checkout command per repository.
+    Mrconfig,
+    /// A vcstool `.repos` YAML file.
+    Vcstool,
+    /// An Android `repo` tool XML manifest.
+    RepoManifest,
+    /// A VS Code `.code-workspace` file with each repository as a folder.
+    CodeWorkspace,
+    /// A directory of Markdown notes, one per repository, for an Obsidian vault; needs a
+    /// destination directory, so is written by [`crate::obsidian::write_vault`].
+    Obsidian,
+}
+
+/// Write every repository with a remote in `dir` to `out` in the given format.
+/// * `dir` - The scanned directory structure.
+/// * `format` - The export format.
+/// * `out` - Where to write the export.
+pub fn export(dir: &GitDirectory, format: ExportFormat, out: &mut impl Write) -> Result<()> {
+    match format {
+        ExportFormat::Ghq => export_ghq(dir, out),
+        ExportFormat::Mrconfig => export_mrconfig(dir, out),
+        ExportFormat::Vcstool => {
+            serde_yaml::to_writer(out, &Manifest::from_tree(dir))?;
+            Ok(())
+        }
+        ExportFormat::RepoManifest => export_repo_manifest(dir, out),
+        ExportFormat::CodeWorkspace => export_code_workspace(dir, out),
+        ExportFormat::Obsidian => {
+            bail!(locale::text("export-obsidian-dest", &[]))
+        }
+    }
+}
+
+fn export_ghq(dir: &GitDirectory, out: &mut impl Write) -> Result<()> {
+    for (_, repo) in dir.repos() {
+        if let Some((_, url)) = repo.primary_remote() {
+            writeln!(out, "{}", url)?;
+        }
+    }
+    Ok(())
+}
+
+/// Repositories with remotes, keyed by their path relative to the scanned directory.
+pub fn relative_repos(dir: &GitDirectory) -> Vec<(PathBuf, &GitDirectory)> {
+    let mut repos: Vec<_> = dir
+        .repos()
+        .into_iter()
+        .map(|(path, repo)| {
+            let relative = path.strip_prefix(&dir.path).unwrap_or(&path);
+            if relative == Path::new("") {
+                (PathBuf::from("."), repo)
+            } else {
+                (relative.to_path_buf(), repo)
+            }
+        })
+        .collect();
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+    repos
+}
+
+fn export_mrconfig(dir: &GitDirectory, out: &mut impl Write) -> Result<()> {
+    for (path, repo) in relative_repos(dir) {
+        let Some((primary, url)) = repo.primary_remote() else {
+            continue;
+        };
+        let name = path
+            .file_name()
+            .map_or_else(|| ".".into(), |n| n.to_string_lossy());
+        let mut checkout = "git clone ".to_string();
+        if primary != "origin" {
+            checkout.push_str(&format!("-o {} ", quote(primary)));
+        }
+        checkout.push_str(&format!("{} {}", quote(url), quote(&name)));
+        let mut others: Vec<_> = repo.remotes.iter().filter(|(n, _)| *n != primary).collect();
+        others.sort();
+        if !others.is_empty() {
+            checkout.push_str(&format!(" && cd {}", quote(&name)));
+            for (name, url) in others {
+                checkout.push_str(&format!(
+                    " && git remote add {} {}",
+                    quote(name),
+                    quote(url)
+                ));
+            }
+        }
+        writeln!(out, "[{}]", path.display())?;
+        writeln!(out, "checkout = {}", checkout)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Escape `s` for use in an XML attribute value.
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn export_repo_manifest(dir: &GitDirectory, out: &mut impl Write) -> Result<()> {
+    // Each distinct fetch base becomes a <remote>, named after its host.
+    let mut remotes: BTreeMap<String, String> = BTreeMap::new();
+    let mut projects = Vec::new();
+    for (path, repo) in relative_repos(dir) {
+        let Some((_, url)) = repo.primary_remote() else {
+            continue;
+        };
+        let Some((base, name)) = split_base(url) else {
+            warn!("skipping {}: {} has no host", path.display(), url);
+            continue;
+        };
+        if !remotes.contains_key(&base) {
+            let host = RemoteUrl::parse(url).map(|u| u.host).unwrap_or_default();
+            let mut remote_name = host.clone();
+            let mut n = 1;
+            while remotes.values().any(|v| *v == remote_name) {
+                n += 1;
+                remote_name = format!("{}-{}", host, n);
+            }
+            remotes.insert(base.clone(), remote_name);
+        }
+        let revision = refs::head(&dir.path.join(&path)).map(|h| h.name().to_string());
+        projects.push((path, name, base, revision));
+    }
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, "<manifest>")?;
+    let mut by_name: Vec<_> = remotes.iter().collect();
+    by_name.sort_by_key(|(_, name)| *name);
+    for (base, name) in by_name {
+        writeln!(
+            out,
+            r#"  <remote name="{}" fetch="{}" />"#,
+            xml_escape(name),
+            xml_escape(base)
+        )?;
+    }
+    for (path, name, base, revision) in projects {
+        write!(
+            out,
+            r#"  <project path="{}" name="{}" remote="{}""#,
+            xml_escape(&path.to_string_lossy().replace('\\', "/")),
+            xml_escape(&name),
+            xml_escape(&remotes[&base])
+        )?;
+        if let Some(revision) = revision {
+            write!(out, r#" revision="{}""#, xml_escape(&revision))?;
+        }
+        writeln!(out, " />")?;
+    }
+    writeln!(out, "</manifest>")?;
+    Ok(())
+}
+
+/// Write a multi-root workspace with a folder for each repository. Folders are absolute, so
+/// the file works wherever it is saved, and named by their path relative to `dir`.
+fn export_code_workspace(dir: &GitDirectory, out: &mut impl Write) -> Result<()> {
+    let root = paths::canonicalize(&dir.path).unwrap_or_else(|_| dir.path.clone());
+    let folders: Vec<_> = relative_repos(dir)
+        .into_iter()
+        .map(|(path, _)| {
+            let name = if path == Path::new(".") {
+                root.file_name()
+                    .map_or_else(|| ".".to_string(), |n| n.to_string_lossy().into_owned())
+            } else {
+                path.to_string_lossy().replace('\\', "/")
+            };
+            json!({
+                "name": name,
+                "path": root.join(&path).to_string_lossy(),
+            })
+        })
+        .collect();
+    let workspace = json!({ "folders": folders, "settings": {} });
+    writeln!(out, "{}", serde_json::to_string_pretty(&workspace)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(path: &str, remotes: &[(&str, &str)]) -> GitDirectory {
+        GitDirectory {
+            remotes: remotes
+                .iter()
+                .map(|(n, u)| (n.to_string(), u.to_string()))
+                .collect(),
+            ..GitDirectory::directory(PathBuf::from(path))
+        }
+    }
+
+    fn tree() -> GitDirectory {
+        GitDirectory {
+            children: vec![
+                repo(
+                    "lg",
+                    &[
+                        ("origin", "git@github.com:me/lg.git"),
+                        ("upstream", "https://github.com/kthwaite/lg.git"),
+                    ],
+                ),
+                repo("it's", &[("gitlab", "https://gitlab.com/me/its.git")]),
+            ],
+            ..GitDirectory::directory(PathBuf::from("/src"))
+        }
+    }
+
+    #[test]
+    fn test_export_mrconfig() -> Result<()> {
+        let mut out = Vec::new();
+        export(&tree(), ExportFormat::Mrconfig, &mut out)?;
+        assert_eq!(
+            String::from_utf8(out)?,
+            "[it's]\n\
+             checkout = git clone -o 'gitlab' 'https://gitlab.com/me/its.git' 'it'\\''s'\n\
+             \n\
+             [lg]\n\
+             checkout = git clone 'git@github.com:me/lg.git' 'lg' && cd 'lg' \
+             && git remote add 'upstream' 'https://github.com/kthwaite/lg.git'\n\
+             \n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_repo_manifest() -> Result<()> {
+        let mut tree = tree();
+        tree.children.push(repo(
+            "fork",
+            &[("origin", "https://github.com/me/fork.git")],
+        ));
+        let mut out = Vec::new();
+        export(&tree, ExportFormat::RepoManifest, &mut out)?;
+        assert_eq!(
+            String::from_utf8(out)?,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest>
+  <remote name="github.com" fetch="https://github.com/" />
+  <remote name="github.com-2" fetch="ssh://git@github.com/" />
+  <remote name="gitlab.com" fetch="https://gitlab.com/" />
+  <project path="fork" name="me/fork.git" remote="github.com" />
+  <project path="it's" name="me/its.git" remote="gitlab.com" />
+  <project path="lg" name="me/lg.git" remote="github.com-2" />
+</manifest>
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_code_workspace() -> Result<()> {
+        let mut out = Vec::new();
+        export(&tree(), ExportFormat::CodeWorkspace, &mut out)?;
+        let workspace: serde_json::Value = serde_json::from_slice(&out)?;
+        assert_eq!(
+            workspace,
+            json!({
+                "folders": [
+                    {"name": "it's", "path": Path::new("/src").join("it's").to_string_lossy()},
+                    {"name": "lg", "path": Path::new("/src").join("lg").to_string_lossy()},
+                ],
+                "settings": {},
+            })
+        );
+        Ok(())
+    }
+}
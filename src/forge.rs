@@ -0,0 +1,244 @@
+//! Creating repositories on a forge through its API, for `lg mirror --create-missing`, so that
+//! a mirror can be pushed to a host that does not have the repository yet.
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+use crate::auth;
+use crate::locale;
+
+/// The environment variable holding the API token, before Git's credential helpers are asked.
+pub const TOKEN_VAR: &str = "LG_FORGE_TOKEN";
+
+/// The kinds of forge whose API `lg` can create repositories through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ForgeKind {
+    /// Gitea or Forgejo.
+    Gitea,
+    /// GitLab.
+    Gitlab,
+}
+
+/// A forge's API, with a token allowed to create repositories.
+pub struct Forge {
+    kind: ForgeKind,
+    /// The API's base URL, e.g. `https://gitea.example.com/api/v1`.
+    api: String,
+    token: String,
+    /// The token owner's login, read once, for Gitea.
+    login: OnceLock<String>,
+}
+
+impl Forge {
+    /// The API of the forge of `kind` at `url`, the base URL of its web interface.
+    pub fn new(kind: ForgeKind, url: &str, token: String) -> Self {
+        let url = url.trim_end_matches('/');
+        let api = match kind {
+            ForgeKind::Gitea => format!("{}/api/v1", url),
+            ForgeKind::Gitlab => format!("{}/api/v4", url),
+        };
+        Forge {
+            kind,
+            api,
+            token,
+            login: OnceLock::new(),
+        }
+    }
+
+    /// The token for the forge at `url`: `$LG_FORGE_TOKEN`, or else the password Git's
+    /// credential helpers have for it.
+    pub fn token(url: &str) -> Result<String> {
+        if let Some(token) = std::env::var(TOKEN_VAR).ok().filter(|t| !t.is_empty()) {
+            return Ok(token);
+        }
+        auth::fill_password(url.trim_end_matches('/'))
+            .map_err(|e| anyhow!(e))?
+            .ok_or_else(|| {
+                anyhow!(locale::text(
+                    "forge-no-token",
+                    &[("url", &url), ("var", &TOKEN_VAR)]
+                ))
+            })
+    }
+
+    /// Create the private repository at `path`, such as `owner/name`, unless it exists.
+    /// Returns whether it was created.
+    pub fn ensure_repo(&self, path: &str) -> Result<bool> {
+        let (owner, name) = path.rsplit_once('/').ok_or_else(|| {
+            anyhow!(locale::text(
+                "forge-no-owner",
+                &[("path", &format_args!("{:?}", path))]
+            ))
+        })?;
+        match self.kind {
+            ForgeKind::Gitea => {
+                if self.get(&format!("/repos/{}", path))?.is_some() {
+                    return Ok(false);
+                }
+                // Repositories of the token's owner are created as theirs, others in the
+                // organization named.
+                let endpoint = if owner == self.login()? {
+                    "/user/repos".to_string()
+                } else {
+                    format!("/orgs/{}/repos", owner)
+                };
+                self.post(&endpoint, json!({ "name": name, "private": true }))?;
+            }
+            ForgeKind::Gitlab => {
+                if self.get(&format!("/projects/{}", encode(path)))?.is_some() {
+                    return Ok(false);
+                }
+                let namespace = self
+                    .get(&format!("/namespaces/{}", encode(owner)))?
+                    .ok_or_else(|| {
+                        anyhow!(locale::text(
+                            "gitlab-no-namespace",
+                            &[("owner", &format_args!("{:?}", owner))]
+                        ))
+                    })?;
+                let namespace_id = namespace["id"].as_u64().ok_or_else(|| {
+                    anyhow!(locale::text(
+                        "gitlab-no-namespace-id",
+                        &[("owner", &format_args!("{:?}", owner))]
+                    ))
+                })?;
+                self.post(
+                    "/projects",
+                    json!({
+                        "name": name,
+                        "path": name,
+                        "namespace_id": namespace_id,
+                        "visibility": "private",
+                    }),
+                )?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// The login of the token's owner.
+    fn login(&self) -> Result<&str> {
+        if let Some(login) = self.login.get() {
+            return Ok(login);
+        }
+        let user = self
+            .get("/user")?
+            .ok_or_else(|| anyhow!(locale::text("forge-no-user", &[("api", &self.api)])))?;
+        let login = user["login"]
+            .as_str()
+            .ok_or_else(|| anyhow!(locale::text("forge-no-login", &[("api", &self.api)])))?;
+        Ok(self.login.get_or_init(|| login.to_string()))
+    }
+
+    fn auth_header(&self) -> (&'static str, String) {
+        match self.kind {
+            ForgeKind::Gitea => ("Authorization", format!("token {}", self.token)),
+            ForgeKind::Gitlab => ("PRIVATE-TOKEN", self.token.clone()),
+        }
+    }
+
+    /// GET `endpoint` of the API; `None` if it is not found.
+    fn get(&self, endpoint: &str) -> Result<Option<Value>> {
+        let url = format!("{}{}", self.api, endpoint);
+        let (name, value) = self.auth_header();
+        match ureq::get(&url).header(name, &value).call() {
+            Ok(mut response) => {
+                Ok(Some(response.body_mut().read_json().with_context(
+                    || locale::text("failed-parse-response", &[("url", &url)]),
+                )?))
+            }
+            Err(ureq::Error::StatusCode(404)) => Ok(None),
+            Err(e) => Err(e).with_context(|| locale::text("failed-get", &[("url", &url)])),
+        }
+    }
+
+    /// POST `body` to `endpoint` of the API.
+    fn post(&self, endpoint: &str, body: Value) -> Result<()> {
+        let url = format!("{}{}", self.api, endpoint);
+        let (name, value) = self.auth_header();
+        ureq::post(&url)
+            .header(name, &value)
+            .send_json(body)
+            .with_context(|| locale::text("failed-post", &[("url", &url)]))?;
+        Ok(())
+    }
+}
+
+/// `s` percent-encoded as one segment of a URL path, as GitLab wants project paths.
+fn encode(s: &str) -> String {
+    let mut out = String::new();
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode("group/sub/my repo"), "group%2Fsub%2Fmy%20repo");
+    }
+
+    #[test]
+    fn test_ensure_repo_gitea() -> Result<()> {
+        let server = tiny_http::Server::http("127.0.0.1:0").map_err(|e| anyhow!(e))?;
+        let addr = server.server_addr().to_ip().unwrap();
+        let handle = thread::spawn(move || {
+            let mut seen = Vec::new();
+            for _ in 0..4 {
+                let mut request = server.recv().unwrap();
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                let authorized = request
+                    .headers()
+                    .iter()
+                    .any(|h| h.field.equiv("Authorization") && h.value == "token secret");
+                let line = format!("{} {}", request.method(), request.url());
+                let (code, response) = match line.as_str() {
+                    "GET /api/v1/repos/me/lg" => (404, "{}"),
+                    "GET /api/v1/user" => (200, r#"{"login": "me"}"#),
+                    "POST /api/v1/user/repos" => (201, "{}"),
+                    _ => (200, "{}"),
+                };
+                seen.push((line, authorized, body));
+                request
+                    .respond(tiny_http::Response::from_string(response).with_status_code(code))
+                    .unwrap();
+            }
+            seen
+        });
+
+        let forge = Forge::new(
+            ForgeKind::Gitea,
+            &format!("http://{}/", addr),
+            "secret".to_string(),
+        );
+        assert!(forge.ensure_repo("me/lg")?);
+        assert!(!forge.ensure_repo("org/tools")?);
+        let seen = handle.join().unwrap();
+        let lines: Vec<_> = seen.iter().map(|(line, _, _)| line.as_str()).collect();
+        assert_eq!(
+            lines,
+            [
+                "GET /api/v1/repos/me/lg",
+                "GET /api/v1/user",
+                "POST /api/v1/user/repos",
+                "GET /api/v1/repos/org/tools",
+            ]
+        );
+        assert!(seen.iter().all(|(_, authorized, _)| *authorized));
+        let created: Value = serde_json::from_str(&seen[2].2)?;
+        assert_eq!(created, json!({ "name": "lg", "private": true }));
+        Ok(())
+    }
+}
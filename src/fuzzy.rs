@@ -0,0 +1,163 @@
+//! Ranking indexed repositories against a short, possibly abbreviated, name.
+use std::path::{Path, PathBuf};
+
+use crate::index::{Index, IndexEntry};
+use crate::url::RemoteUrl;
+
+/// Score how well a single query `word` matches `entry`; `None` if it does not match at all.
+///
+/// In order of preference: the directory name equals the word, starts with it, or contains it;
+/// the path below `base` or primary remote contains it; the letters of the word appear in order
+/// in the directory name; or they appear in order in the path below `base`.
+fn score_word(word: &str, entry: &IndexEntry, base: &Path) -> Option<u32> {
+    let name = entry.name().to_lowercase();
+    let path = entry
+        .path
+        .strip_prefix(base)
+        .unwrap_or(&entry.path)
+        .to_string_lossy()
+        .to_lowercase();
+    let remote = entry
+        .primary_remote()
+        .and_then(|(_, url)| RemoteUrl::parse(url))
+        .map(|url| url.to_string().to_lowercase())
+        .unwrap_or_default();
+    let closeness = || 100u32.saturating_sub((name.len() - word.len().min(name.len())) as u32);
+    if name == word {
+        Some(1000)
+    } else if name.starts_with(word) {
+        Some(800 + closeness())
+    } else if name.contains(word) {
+        Some(600 + closeness())
+    } else if path.contains(word) || remote.contains(word) {
+        Some(400)
+    } else if is_subsequence(word, &name) {
+        Some(200 + closeness())
+    } else if is_subsequence(word, &path) {
+        Some(100)
+    } else {
+        None
+    }
+}
+
+/// The innermost directory holding every entry. The part of their paths it makes up, such as
+/// the home directory, is the same for all of them, so matching it says nothing.
+fn common_base(entries: &[IndexEntry]) -> PathBuf {
+    let mut parents = entries.iter().filter_map(|entry| entry.path.parent());
+    let Some(first) = parents.next() else {
+        return PathBuf::new();
+    };
+    parents.fold(first.to_path_buf(), |base, parent| {
+        base.components()
+            .zip(parent.components())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a)
+            .collect()
+    })
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|h| h == c))
+}
+
+/// Rank `entries` against `query`, best match first.
+///
+/// The query is split on whitespace and every word must match; matching is case-insensitive.
+/// Paths are matched below the directory all the entries are in. Ties go to the shorter path.
+pub fn rank<'a>(query: &[String], entries: &'a [IndexEntry]) -> Vec<(u32, &'a IndexEntry)> {
+    rank_indices(query, entries)
+        .into_iter()
+        .map(|(score, i)| (score, &entries[i]))
+        .collect()
+}
+
+/// Like [`rank`], but with the positions of the matches in `entries`.
+pub fn rank_indices(query: &[String], entries: &[IndexEntry]) -> Vec<(u32, usize)> {
+    let words: Vec<_> = query
+        .iter()
+        .flat_map(|q| q.split_whitespace())
+        .map(str::to_lowercase)
+        .collect();
+    let base = common_base(entries);
+    let mut ranked: Vec<_> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(i, entry)| {
+            words
+                .iter()
+                .map(|word| score_word(word, entry, &base))
+                .sum::<Option<u32>>()
+                .map(|score| (score, i))
+        })
+        .collect();
+    ranked.sort_by(|&(a_score, a), &(b_score, b)| {
+        let (a, b) = (&entries[a].path, &entries[b].path);
+        b_score
+            .cmp(&a_score)
+            .then_with(|| a.as_os_str().len().cmp(&b.as_os_str().len()))
+            .then_with(|| a.cmp(b))
+    });
+    ranked
+}
+
+/// The indexed repository a command should act on.
+/// * `query` - Words to rank repositories by; if empty, the repository containing `cwd` is used.
+/// * `cwd` - The current directory.
+pub fn resolve<'a>(index: &'a Index, query: &[String], cwd: &Path) -> Option<&'a IndexEntry> {
+    if query.is_empty() {
+        index.find_containing(cwd)
+    } else {
+        rank(query, &index.entries).first().map(|(_, entry)| *entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn entry(path: &str, url: &str) -> IndexEntry {
+        IndexEntry {
+            path: PathBuf::from(path),
+            remotes: HashMap::from([("origin".to_string(), url.to_string())]),
+            primary_remote: None,
+            head: None,
+        }
+    }
+
+    fn best(query: &str, entries: &[IndexEntry]) -> Option<String> {
+        rank(&[query.to_string()], entries)
+            .first()
+            .map(|(_, e)| e.path.display().to_string())
+    }
+
+    #[test]
+    fn test_rank() {
+        let entries = vec![
+            entry("/src/lg", "git@github.com:kthwaite/lg.git"),
+            entry("/src/lgtm", "https://github.com/other/lgtm"),
+            entry("/src/work/lg", "https://gitlab.com/work/lg"),
+            entry("/src/linux-glue", "https://example.com/linux-glue"),
+            entry("/src/tools", "https://github.com/kthwaite/tools"),
+        ];
+        assert_eq!(best("lg", &entries).as_deref(), Some("/src/lg"));
+        assert_eq!(best("LGT", &entries).as_deref(), Some("/src/lgtm"));
+        assert_eq!(best("work lg", &entries).as_deref(), Some("/src/work/lg"));
+        assert_eq!(
+            best("kthwaite/too", &entries).as_deref(),
+            Some("/src/tools")
+        );
+        assert_eq!(best("lnxg", &entries).as_deref(), Some("/src/linux-glue"));
+        assert_eq!(best("nothing", &entries), None);
+
+        // The directory every entry is in matches nothing, even if it contains the word.
+        let entries = vec![
+            entry("/home/lg/src/tools", "https://github.com/kthwaite/tools"),
+            entry("/home/lg/src/lgtm", "https://github.com/other/lgtm"),
+        ];
+        let ranked = rank(&["lg".to_string()], &entries);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].1.path, PathBuf::from("/home/lg/src/lgtm"));
+    }
+}
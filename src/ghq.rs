@@ -0,0 +1,224 @@
+//! Interoperability with [ghq](https://github.com/x-motemen/ghq) and its `host/owner/repo` layout.
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use tracing::warn;
+
+use crate::backend::Backend;
+use crate::clone::{clone_repo, CloneOutcome};
+use crate::dirs::home_dir;
+use crate::locale;
+use crate::model::ScanError;
+use crate::paths;
+use crate::url::RemoteUrl;
+use crate::GitDirectory;
+
+/// Locate the ghq root the same way ghq does: `$GHQ_ROOT`, then `ghq.root`, then `~/ghq`.
+pub fn root() -> Result<PathBuf> {
+    if let Some(roots) = std::env::var_os("GHQ_ROOT").filter(|r| !r.is_empty()) {
+        if let Some(first) = std::env::split_paths(&roots).next() {
+            return Ok(first);
+        }
+    }
+    if let Ok(output) = Command::new("git")
+        .args(["config", "--path", "--get", "ghq.root"])
+        .output()
+    {
+        let configured = String::from_utf8_lossy(&output.stdout);
+        let configured = configured.lines().next().unwrap_or("").trim();
+        if output.status.success() && !configured.is_empty() {
+            return Ok(PathBuf::from(configured));
+        }
+    }
+    home_dir()
+        .map(|home| home.join("ghq"))
+        .with_context(|| locale::text("no-ghq-root", &[]))
+}
+
+/// Scan a directory laid out as `host/owner/repo`.
+///
+/// Repositories are reported as direct children of `root` with their full relative path, and
+/// the walk does not descend into repositories. A warning is printed for each repository whose
+/// location disagrees with its primary remote.
+/// * `root` - The root of the layout.
+/// * `backend` - The backend used to read repository metadata.
+/// * `strict` - Fail on the first unreadable path instead of recording it in the result's
+///   errors.
+pub fn scan(root: &Path, backend: Backend, strict: bool) -> Result<GitDirectory> {
+    let mut children = Vec::new();
+    let mut errors = Vec::new();
+    let walk_root = paths::verbatim(root);
+    walk(
+        &walk_root,
+        &walk_root,
+        backend,
+        strict,
+        &mut children,
+        &mut errors,
+    )?;
+    children.sort_by(|a, b| a.path.cmp(&b.path));
+    for child in &children {
+        if let Some(expected) = expected_path(child) {
+            if child.path != expected {
+                warn!(
+                    code = "layout_mismatch",
+                    path = %child.path.display(),
+                    "{} does not match its remote; expected {}",
+                    child.path.display(),
+                    expected.display()
+                );
+            }
+        }
+    }
+    Ok(GitDirectory {
+        children,
+        errors,
+        ..GitDirectory::directory(root.to_path_buf())
+    })
+}
+
+fn walk(
+    root: &Path,
+    dir: &Path,
+    backend: Backend,
+    strict: bool,
+    out: &mut Vec<GitDirectory>,
+    errors: &mut Vec<ScanError>,
+) -> Result<()> {
+    let entries = fs::read_dir(dir).with_context(|| locale::failed("read-dir", &dir))?;
+    for entry in entries {
+        let entry = entry.with_context(|| locale::text("failed-read-dir-entry", &[]))?;
+        let path = entry.path();
+        if !path.is_dir() || entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let result = match backend.remotes(&path) {
+            Ok(Some(remotes)) => {
+                out.push(GitDirectory {
+                    remotes,
+                    ..GitDirectory::directory(path.strip_prefix(root)?.to_path_buf())
+                });
+                Ok(())
+            }
+            Ok(None) => walk(root, &path, backend, strict, out, errors),
+            Err(e) => Err(e),
+        };
+        // Without `strict`, a nested walk only fails if `path` itself cannot be listed.
+        match result {
+            Err(e) if !strict => {
+                let error = ScanError::new(&paths::simplify(&path), &e);
+                error.warn();
+                errors.push(error);
+            }
+            result => result?,
+        }
+    }
+    Ok(())
+}
+
+/// The location of a repository under a ghq root, derived from its primary remote.
+pub fn expected_path(dir: &GitDirectory) -> Option<PathBuf> {
+    let (_, url) = dir.primary_remote()?;
+    let url = RemoteUrl::parse(url)?;
+    Some(url.to_string().split('/').collect())
+}
+
+/// Clone every repository listed in `list` into `root`, skipping those already present.
+///
+/// Each line is either a ghq name (`github.com/owner/repo`, as printed by `ghq list`) or a
+/// remote URL. Blank lines and lines starting with `#` are ignored.
+/// * `list` - The file to read, or standard input if `None`.
+/// * `root` - The root to clone into.
+/// * `dry_run` - Print what would be cloned without cloning.
+pub fn import(list: Option<&Path>, root: &Path, dry_run: bool) -> Result<()> {
+    let reader: Box<dyn BufRead> = match list {
+        Some(path) => Box::new(BufReader::new(
+            fs::File::open(path).with_context(|| locale::failed("open", &path))?,
+        )),
+        None => Box::new(BufReader::new(io::stdin())),
+    };
+    let mut failed = 0;
+    for line in reader.lines() {
+        let line = line.with_context(|| locale::text("failed-read-import-list", &[]))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let url = if RemoteUrl::parse(line).is_some() {
+            line.to_string()
+        } else {
+            format!("https://{}", line)
+        };
+        let Some(parsed) = RemoteUrl::parse(&url) else {
+            warn!("skipping unrecognised entry {:?}", line);
+            continue;
+        };
+        let Some(target) = target(root, &parsed) else {
+            warn!("skipping {:?}, which is outside the root", line);
+            continue;
+        };
+        if clone_repo(&url, &target, None, dry_run)? == CloneOutcome::Failed {
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        bail!(locale::text("clone-failed", &[("count", &failed)]));
+    }
+    Ok(())
+}
+
+/// Where the repository at `url` belongs under `root`; `None` if its path would lead outside
+/// `root`, as a `..` in it would.
+fn target(root: &Path, url: &RemoteUrl) -> Option<PathBuf> {
+    let relative = PathBuf::from(url.to_string());
+    paths::is_contained(&relative).then(|| root.join(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    use crate::testutil::create_repo;
+
+    #[test]
+    fn test_scan_ghq_layout() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path().join("github.com/user/repo");
+        create_repo(&repo, "git@github.com:user/repo.git")?;
+        // Nested directories inside a repository are not scanned.
+        create_repo(&repo.join("vendor/dep"), "https://github.com/other/dep")?;
+        let moved = temp_dir.path().join("github.com/user/old-name");
+        create_repo(&moved, "https://github.com/user/new-name")?;
+
+        let result = scan(temp_dir.path(), Backend::Native, false)?;
+        let paths: Vec<_> = result.children.iter().map(|c| c.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("github.com/user/old-name"),
+                PathBuf::from("github.com/user/repo"),
+            ]
+        );
+        assert_eq!(
+            expected_path(&result.children[0]),
+            Some(PathBuf::from("github.com/user/new-name"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_target() {
+        let root = Path::new("/ghq");
+        let url = |url| RemoteUrl::parse(url).unwrap();
+        assert_eq!(
+            target(root, &url("https://github.com/user/repo")),
+            Some(PathBuf::from("/ghq/github.com/user/repo"))
+        );
+        assert_eq!(target(root, &url("https://github.com/../../etc")), None);
+        assert_eq!(target(root, &url("git@github.com:user/..")), None);
+    }
+}
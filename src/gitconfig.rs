@@ -0,0 +1,725 @@
+//! Reading remotes from Git config files.
+//!
+//! [`parse`] implements the syntax described in git-config(1): `#` and `;` comments, quoted
+//! and escaped values, backslash line continuations, `[section "subsection"]` headers with
+//! escapes as well as the deprecated `[section.subsection]` form, keys without values, and
+//! case-insensitive section and key names. Includes are not followed.
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::model::{preferred_remote, RemoteUrls};
+
+/// A variable set in a Git config file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// The section name, lowercased.
+    pub section: String,
+    /// The subsection name, which is case-sensitive; lowercased for `[section.subsection]`.
+    pub subsection: Option<String>,
+    /// The variable name, lowercased.
+    pub key: String,
+    /// The value, or `None` for a key given without `=`, which means true.
+    pub value: Option<String>,
+}
+
+impl Entry {
+    /// Whether this variable is `section.key`, or `section.<subsection>.key` for any
+    /// subsection, ignoring case as Git does.
+    pub fn is(&self, section: &str, key: &str) -> bool {
+        self.section.eq_ignore_ascii_case(section) && self.key.eq_ignore_ascii_case(key)
+    }
+
+    /// Whether the value is one of Git's spellings of true; a key without a value is true.
+    pub fn is_true(&self) -> bool {
+        self.value.as_deref().is_none_or(|value| {
+            ["true", "yes", "on", "1"]
+                .iter()
+                .any(|t| value.eq_ignore_ascii_case(t))
+        })
+    }
+}
+
+/// Parse a Git config file.
+/// * `config_path` - The path to the Git config file.
+///
+/// A remote with several `url` values is given the first, which is the one Git fetches from;
+/// see [`read_remote_urls`] for the rest.
+pub fn parse_git_config(config_path: &Path) -> Result<HashMap<String, String>> {
+    let mut remotes = HashMap::new();
+    for entry in read(config_path)? {
+        if let (true, Some(name), Some(url)) =
+            (entry.is("remote", "url"), entry.subsection, entry.value)
+        {
+            remotes.entry(name).or_insert(url);
+        }
+    }
+    Ok(remotes)
+}
+
+/// The remote that identifies the repository whose Git config file this is: the remote named
+/// by `checkout.defaultRemoteName` if there is one, otherwise as [`primary_remote`] chooses.
+/// * `config_path` - The path to the Git config file.
+///
+/// [`primary_remote`]: crate::model::primary_remote
+pub fn read_primary_remote(config_path: &Path) -> Result<Option<String>> {
+    let mut remotes = HashMap::new();
+    let mut default_name = None;
+    for entry in read(config_path)? {
+        if entry.is("checkout", "defaultremotename") && entry.subsection.is_none() {
+            default_name = entry.value;
+        } else if let (true, Some(name), Some(url)) =
+            (entry.is("remote", "url"), entry.subsection, entry.value)
+        {
+            remotes.entry(name).or_insert(url);
+        }
+    }
+    Ok(preferred_remote(&remotes, default_name.as_deref()).map(|(name, _)| name.to_string()))
+}
+
+/// The promisor remote of a partial clone, which fetches the objects left out of the clone
+/// on demand.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartialClone {
+    /// The remote's name.
+    pub remote: String,
+    /// The filter the clone was made with, such as `blob:none`, if recorded.
+    pub filter: Option<String>,
+}
+
+/// Read the promisor remote of a partial clone from its Git config file, if the repository is
+/// one: the remote named by `extensions.partialClone`, else the first with `promisor` set.
+/// * `config_path` - The path to the Git config file.
+pub fn read_partial_clone(config_path: &Path) -> Result<Option<PartialClone>> {
+    let entries = read(config_path)?;
+    let extension = entries
+        .iter()
+        .rev()
+        .find(|entry| entry.is("extensions", "partialclone") && entry.subsection.is_none())
+        .and_then(|entry| entry.value.clone());
+    let remote = extension.or_else(|| {
+        entries
+            .iter()
+            .filter(|entry| entry.is("remote", "promisor"))
+            .find(|entry| {
+                // The last value of `promisor` for a remote is the one that holds.
+                let name = entry.subsection.as_ref();
+                entries
+                    .iter()
+                    .rev()
+                    .find(|other| {
+                        other.is("remote", "promisor") && other.subsection.as_ref() == name
+                    })
+                    .is_some_and(Entry::is_true)
+            })
+            .and_then(|entry| entry.subsection.clone())
+    });
+    Ok(remote.map(|remote| {
+        let filter = entries
+            .iter()
+            .rev()
+            .find(|entry| {
+                entry.is("remote", "partialclonefilter")
+                    && entry.subsection.as_ref() == Some(&remote)
+            })
+            .and_then(|entry| entry.value.clone());
+        PartialClone { remote, filter }
+    }))
+}
+
+/// Read every `url` and `pushurl` of the remotes in a Git config file, by remote name.
+/// * `config_path` - The path to the Git config file.
+pub fn read_remote_urls(config_path: &Path) -> Result<BTreeMap<String, RemoteUrls>> {
+    let mut remotes: BTreeMap<String, RemoteUrls> = BTreeMap::new();
+    for entry in read(config_path)? {
+        let (Some(name), Some(url)) = (entry.subsection.clone(), entry.value.clone()) else {
+            continue;
+        };
+        if entry.is("remote", "url") {
+            remotes.entry(name).or_default().fetch.push(url);
+        } else if entry.is("remote", "pushurl") {
+            remotes.entry(name).or_default().push.push(url);
+        }
+    }
+    Ok(remotes)
+}
+
+/// Read and parse a Git config file.
+pub(crate) fn read(config_path: &Path) -> Result<Vec<Entry>> {
+    let contents = fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to open Git config file: {:?}", config_path))?;
+    // Reported as invalid data, like a file that is not UTF-8.
+    parse(&contents)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+        .with_context(|| format!("Failed to parse Git config file: {:?}", config_path))
+}
+
+/// Parse the contents of a Git config file into its variables, in order.
+pub fn parse(contents: &str) -> Result<Vec<Entry>> {
+    let mut parser = Parser {
+        chars: contents
+            .strip_prefix('\u{feff}')
+            .unwrap_or(contents)
+            .chars()
+            .collect(),
+        pos: 0,
+        line: 1,
+    };
+    let mut entries = Vec::new();
+    let mut section: Option<(String, Option<String>)> = None;
+    loop {
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => break,
+            Some('\n') => parser.bump(),
+            Some('#' | ';') => parser.skip_comment(),
+            Some('[') => section = Some(parser.section_header()?),
+            Some(c) if c.is_ascii_alphabetic() => {
+                let Some((name, subsection)) = &section else {
+                    bail!("line {}: variable outside any section", parser.line);
+                };
+                let key = parser.key();
+                let value = parser.value()?;
+                entries.push(Entry {
+                    section: name.clone(),
+                    subsection: subsection.clone(),
+                    key,
+                    value,
+                });
+            }
+            Some(c) => bail!("line {}: unexpected {:?}", parser.line, c),
+        }
+    }
+    Ok(entries)
+}
+
+/// A cursor over the characters of a config file.
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// The current line number, for error messages.
+    line: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) {
+        if self.peek() == Some('\n') {
+            self.line += 1;
+        }
+        self.pos += 1;
+    }
+
+    /// Skip spaces and tabs, but not newlines.
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(c) if c != '\n' && c.is_whitespace()) {
+            self.bump();
+        }
+    }
+
+    /// Skip to the end of the line, leaving the newline.
+    fn skip_comment(&mut self) {
+        while !matches!(self.peek(), None | Some('\n')) {
+            self.bump();
+        }
+    }
+
+    /// Parse a `[section]`, `[section "subsection"]` or `[section.subsection]` header. Section
+    /// names are case-insensitive and may contain dots; `[a.b "c"]` is section `a.b`.
+    fn section_header(&mut self) -> Result<(String, Option<String>)> {
+        self.bump();
+        let mut name = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '.')
+        {
+            name.push(c.to_ascii_lowercase());
+            self.bump();
+        }
+        if name.is_empty() {
+            bail!("line {}: invalid section header", self.line);
+        }
+        let subsection = match self.peek() {
+            Some(']') => {
+                self.bump();
+                // The deprecated `[section.subsection]` form.
+                return Ok(match name.split_once('.') {
+                    Some((section, subsection)) => {
+                        (section.to_string(), Some(subsection.to_string()))
+                    }
+                    None => (name, None),
+                });
+            }
+            // Git allows any whitespace before the subsection name, but requires some, and
+            // nothing between the closing quote and `]`.
+            Some(c) if c != '\n' && c.is_whitespace() => {
+                self.skip_whitespace();
+                if self.peek() != Some('"') {
+                    bail!("line {}: expected a quoted subsection name", self.line);
+                }
+                self.bump();
+                let mut subsection = String::new();
+                loop {
+                    match self.peek() {
+                        None | Some('\n') => {
+                            bail!("line {}: unterminated subsection name", self.line)
+                        }
+                        Some('"') => break,
+                        Some('\\') => {
+                            // Other escapes drop the backslash.
+                            self.bump();
+                            match self.peek() {
+                                None | Some('\n') => {
+                                    bail!("line {}: unterminated subsection name", self.line)
+                                }
+                                Some(c) => subsection.push(c),
+                            }
+                        }
+                        Some(c) => subsection.push(c),
+                    }
+                    self.bump();
+                }
+                self.bump();
+                subsection
+            }
+            _ => bail!("line {}: invalid section header", self.line),
+        };
+        if self.peek() != Some(']') {
+            bail!("line {}: invalid section header", self.line);
+        }
+        self.bump();
+        Ok((name, Some(subsection)))
+    }
+
+    /// Parse a variable name: letters, digits and `-`, starting with a letter.
+    fn key(&mut self) -> String {
+        let mut key = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        {
+            key.push(c.to_ascii_lowercase());
+            self.bump();
+        }
+        key
+    }
+
+    /// Parse what follows a variable name: `= value`, or nothing for a boolean true.
+    fn value(&mut self) -> Result<Option<String>> {
+        self.skip_whitespace();
+        match self.peek() {
+            None | Some('\n') => return Ok(None),
+            Some('#' | ';') => {
+                self.skip_comment();
+                return Ok(None);
+            }
+            Some('=') => self.bump(),
+            Some(c) => bail!("line {}: unexpected {:?} after variable name", self.line, c),
+        }
+        self.skip_whitespace();
+        let mut value = String::new();
+        let mut quoted = false;
+        // Whitespace outside quotes is kept only between other characters, as single spaces.
+        let mut spaces = 0;
+        loop {
+            let c = match self.peek() {
+                None | Some('\n') if quoted => bail!("line {}: unterminated quote", self.line),
+                None | Some('\n') => break,
+                Some('#' | ';') if !quoted => {
+                    self.skip_comment();
+                    break;
+                }
+                Some(c) if c.is_whitespace() && !quoted => {
+                    if !value.is_empty() {
+                        spaces += 1;
+                    }
+                    self.bump();
+                    continue;
+                }
+                Some(c) => c,
+            };
+            value.extend(std::iter::repeat_n(' ', spaces));
+            spaces = 0;
+            self.bump();
+            match c {
+                '"' => quoted = !quoted,
+                '\\' => {
+                    let escaped = match self.peek() {
+                        Some('\n') => None,
+                        Some('\r') if self.chars.get(self.pos + 1) == Some(&'\n') => {
+                            self.bump();
+                            None
+                        }
+                        Some('n') => Some('\n'),
+                        Some('t') => Some('\t'),
+                        Some('b') => Some('\u{8}'),
+                        Some(c @ ('"' | '\\')) => Some(c),
+                        Some(c) => bail!("line {}: invalid escape \\{}", self.line, c),
+                        None => bail!("line {}: backslash at end of file", self.line),
+                    };
+                    self.bump();
+                    value.extend(escaped);
+                }
+                c => value.push(c),
+            }
+        }
+        Ok(Some(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::create_git_config;
+    use tempfile::TempDir;
+
+    /// The remotes in `contents`, sorted by name.
+    fn remotes(contents: &str) -> Result<Vec<(String, String)>> {
+        let temp_dir = TempDir::new()?;
+        let config_path = create_git_config(temp_dir.path(), contents)?;
+        let mut remotes: Vec<_> = parse_git_config(&config_path)?.into_iter().collect();
+        remotes.sort();
+        Ok(remotes)
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, url)| (name.to_string(), url.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_git_config_one() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = create_git_config(
+            temp_dir.path(),
+            "[remote \"origin\"]\n    url = https://github.com/user/repo.git\n",
+        )?;
+
+        let remotes = parse_git_config(&config_path)?;
+
+        assert_eq!(remotes.len(), 1);
+        assert_eq!(
+            remotes.get("origin"),
+            Some(&"https://github.com/user/repo.git".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_git_config() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_content = r#"
+[remote "origin"]
+    url = https://github.com/user/repo.git
+[remote "upstream"]
+    url = https://github.com/upstream/repo.git
+"#;
+        create_git_config(temp_dir.path(), config_content)?;
+
+        let config_path = temp_dir.path().join(".git/config");
+        // print config path
+        println!("{}", config_path.display());
+        //print config content
+        println!("{}", std::fs::read_to_string(&config_path)?);
+
+        let remotes = parse_git_config(&config_path)?;
+
+        assert_eq!(remotes.len(), 2);
+        assert_eq!(
+            remotes.get("origin"),
+            Some(&"https://github.com/user/repo.git".to_string())
+        );
+        assert_eq!(
+            remotes.get("upstream"),
+            Some(&"https://github.com/upstream/repo.git".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixtures() -> Result<()> {
+        let fixtures: &[(&str, &[(&str, &str)])] = &[
+            (
+                include_str!("../fixtures/gitconfig/github-clone.config"),
+                &[("origin", "git@github.com:kthwaite/lg.git")],
+            ),
+            (
+                include_str!("../fixtures/gitconfig/fork.config"),
+                &[
+                    ("origin", "git@github.com:me/rust.git"),
+                    ("upstream", "https://github.com/rust-lang/rust.git"),
+                ],
+            ),
+            (
+                include_str!("../fixtures/gitconfig/comments.config"),
+                &[
+                    ("origin", "https://gitlab.com/group/project.git"),
+                    ("mirror", "ssh://git@example.com/project.git"),
+                ],
+            ),
+            (
+                include_str!("../fixtures/gitconfig/quoted.config"),
+                &[
+                    ("origin", "https://example.com/a;b#c.git"),
+                    ("spaced", "/srv/git/my project.git"),
+                    ("escaped", "/srv/git/\"quoted\"\\repo"),
+                ],
+            ),
+            (
+                include_str!("../fixtures/gitconfig/escapes.config"),
+                &[
+                    ("backspace", "https://example.com/a\u{8}b.git"),
+                    ("tab", "https://example.com/tab\t.git"),
+                    ("newline", "https://example.com/newline\n.git"),
+                    ("quote", "https://example.com/\"quote\".git"),
+                    ("backslash", "https://example.com/back\\slash.git"),
+                ],
+            ),
+            (
+                include_str!("../fixtures/gitconfig/continuation.config"),
+                &[("origin", "https://example.com/very/long/path/to/repo.git")],
+            ),
+            (
+                include_str!("../fixtures/gitconfig/whitespace.config"),
+                &[
+                    ("origin", "https://github.com/user/repo.git"),
+                    ("tabbed", "https://example.com/tabbed.git"),
+                    ("upper", "https://example.com/upper.git"),
+                ],
+            ),
+            (
+                include_str!("../fixtures/gitconfig/subsections.config"),
+                &[
+                    ("Origin", "https://example.com/case.git"),
+                    ("legacy", "https://example.com/legacy.git"),
+                    ("same-line", "https://example.com/same-line.git"),
+                    ("we\"ird\\name", "https://example.com/weird.git"),
+                ],
+            ),
+            (
+                include_str!("../fixtures/gitconfig/submodules.config"),
+                &[("origin", "https://github.com/org/superproject.git")],
+            ),
+            (
+                include_str!("../fixtures/gitconfig/windows.config"),
+                &[("origin", "https://dev.azure.com/org/project/_git/repo")],
+            ),
+            (include_str!("../fixtures/gitconfig/bare.config"), &[]),
+            // As written by old and current Git, and by GUIs and IDEs.
+            (
+                include_str!("../fixtures/gitconfig/git-1.7.config"),
+                &[("origin", "git://github.com/user/old.git")],
+            ),
+            (
+                include_str!("../fixtures/gitconfig/tortoisegit.config"),
+                &[("origin", "git@github.com:user/windows.git")],
+            ),
+            (
+                include_str!("../fixtures/gitconfig/egit.config"),
+                &[
+                    ("origin", "https://git.eclipse.org/r/project.git"),
+                    ("gerrit", "ssh://me@git.eclipse.org:29418/project.git"),
+                ],
+            ),
+            (
+                include_str!("../fixtures/gitconfig/github-desktop.config"),
+                &[
+                    ("origin", "https://github.com/me/app.git"),
+                    ("upstream", "https://github.com/org/app.git"),
+                ],
+            ),
+            (
+                include_str!("../fixtures/gitconfig/mixed-case.config"),
+                &[
+                    ("origin", "https://example.com/shouting.git"),
+                    ("Tabbed", "https://example.com/tabbed.git"),
+                    ("spaced", "https://example.com/spaced.git"),
+                    ("legacy", "https://example.com/legacy.git"),
+                ],
+            ),
+        ];
+        for (contents, expected) in fixtures {
+            let mut expected = pairs(expected);
+            expected.sort();
+            assert_eq!(remotes(contents)?, expected, "{}", contents);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_multiple_urls() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = create_git_config(
+            temp_dir.path(),
+            include_str!("../fixtures/gitconfig/multiple-urls.config"),
+        )?;
+        let remotes = parse_git_config(&config_path)?;
+        assert_eq!(remotes["origin"], "https://github.com/user/repo.git");
+        assert_eq!(remotes["backup"], "/mnt/backup/repo.git");
+
+        let urls = read_remote_urls(&config_path)?;
+        assert_eq!(
+            urls["origin"],
+            RemoteUrls {
+                fetch: vec![
+                    "https://github.com/user/repo.git".to_string(),
+                    "https://gitlab.com/user/repo.git".to_string(),
+                ],
+                push: Vec::new(),
+            }
+        );
+        assert_eq!(
+            urls["backup"].push,
+            ["ssh://backup@example.com/repo.git", "/mnt/other/repo.git"]
+        );
+        assert!(urls["origin"].is_multiple() && urls["backup"].is_multiple());
+        assert!(!urls["plain"].is_multiple());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_primary_remote() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let remotes = "[remote \"upstream\"]\n\turl = https://github.com/kthwaite/lg.git\n\
+                       [remote \"fork\"]\n\turl = git@github.com:me/lg.git\n";
+        let config_path = create_git_config(temp_dir.path(), remotes)?;
+        assert_eq!(read_primary_remote(&config_path)?.as_deref(), Some("fork"));
+
+        let config_path = create_git_config(
+            temp_dir.path(),
+            &format!("[checkout]\n\tdefaultRemoteName = upstream\n{}", remotes),
+        )?;
+        assert_eq!(
+            read_primary_remote(&config_path)?.as_deref(),
+            Some("upstream")
+        );
+
+        // A name with no such remote falls back to the usual choice.
+        let config_path = create_git_config(
+            temp_dir.path(),
+            &format!("[checkout]\n\tdefaultRemoteName = missing\n{}", remotes),
+        )?;
+        assert_eq!(read_primary_remote(&config_path)?.as_deref(), Some("fork"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_partial_clone() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = create_git_config(
+            temp_dir.path(),
+            "[remote \"origin\"]\n\turl = https://github.com/kthwaite/lg.git\n",
+        )?;
+        assert_eq!(read_partial_clone(&config_path)?, None);
+
+        // As `git clone --filter=blob:none` writes it.
+        let config_path = create_git_config(
+            temp_dir.path(),
+            "[remote \"origin\"]\n\turl = https://github.com/kthwaite/lg.git\n\
+             \tpromisor = true\n\tpartialclonefilter = blob:none\n",
+        )?;
+        assert_eq!(
+            read_partial_clone(&config_path)?,
+            Some(PartialClone {
+                remote: "origin".to_string(),
+                filter: Some("blob:none".to_string()),
+            })
+        );
+
+        // As Git before 2.24 wrote it, and with a promisor turned off.
+        let config_path = create_git_config(
+            temp_dir.path(),
+            "[core]\n\trepositoryformatversion = 1\n\
+             [extensions]\n\tpartialClone = upstream\n\
+             [remote \"origin\"]\n\tpromisor = true\n\tpromisor = false\n",
+        )?;
+        assert_eq!(
+            read_partial_clone(&config_path)?,
+            Some(PartialClone {
+                remote: "upstream".to_string(),
+                filter: None,
+            })
+        );
+        let config_path = create_git_config(
+            temp_dir.path(),
+            "[remote \"origin\"]\n\tpromisor = true\n\tpromisor = false\n",
+        )?;
+        assert_eq!(read_partial_clone(&config_path)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let entries = parse("[Core]\n\tBare\n\tautocrlf = \"in\"put  # true\n[a \"B\"]k=\n")?;
+        let entry =
+            |section: &str, subsection: Option<&str>, key: &str, value: Option<&str>| Entry {
+                section: section.to_string(),
+                subsection: subsection.map(str::to_string),
+                key: key.to_string(),
+                value: value.map(str::to_string),
+            };
+        assert_eq!(
+            entries,
+            vec![
+                entry("core", None, "bare", None),
+                entry("core", None, "autocrlf", Some("input")),
+                entry("a", Some("B"), "k", Some("")),
+            ]
+        );
+        assert_eq!(
+            parse("[A.b\t \"C\"]\nk\n")?,
+            vec![entry("a.b", Some("C"), "k", None)]
+        );
+        assert_eq!(
+            parse("[x]\n  a = one  two\t three \n")?[0].value.as_deref(),
+            Some("one  two  three")
+        );
+        assert_eq!(
+            parse("[x]\na = \"  kept  \"\n")?[0].value.as_deref(),
+            Some("  kept  ")
+        );
+        assert_eq!(
+            parse("[x]\na = 1\\n2\\t3\n")?[0].value.as_deref(),
+            Some("1\n2\t3")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() -> Result<()> {
+        for contents in [
+            "url = https://example.com\n",
+            "[remote \"origin\"\n",
+            "[remote \"origin]\n",
+            "[remote origin]\n",
+            "[]\n",
+            "[x]\na = \"unterminated\n",
+            "[x]\na = bad \\q escape\n",
+            "[x]\n%a = 1\n",
+            "[x]\na b\n",
+            // Git needs whitespace before the subsection, and none after it.
+            "[remote\"origin\"]\n",
+            "[remote \"origin\" ]\n",
+            "[ remote \"origin\"]\n",
+        ] {
+            assert!(parse(contents).is_err(), "{:?}", contents);
+        }
+        assert!(parse("[remote \"origin\"]\n  url = x\n  url = y\n").is_ok());
+
+        let temp_dir = TempDir::new()?;
+        let config_path = create_git_config(temp_dir.path(), "[remote \"origin\"\n")?;
+        let err = parse_git_config(&config_path).unwrap_err();
+        assert_eq!(crate::model::ScanError::code_for(&err), "invalid_data");
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+//! A log of scan summaries, for reviewing how a directory changes over time.
+//!
+//! When `history` is set in the `[scan]` section of the configuration, each listing appends a
+//! line of JSON to `history.jsonl` in the data directory, which `lg history` prints.
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::dirs::data_dir;
+use crate::locale;
+use crate::metrics::Metrics;
+use crate::GitDirectory;
+
+/// The summary of one scan.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    /// When the scan was made, in seconds since the Unix epoch.
+    pub time: u64,
+    /// The absolute path of the directory scanned.
+    pub root: PathBuf,
+    pub repositories: usize,
+    /// Repositories with uncommitted or untracked changes.
+    pub dirty: usize,
+}
+
+impl Record {
+    /// Summarise the scan of `dir` made now, reading the status of each repository.
+    /// * `dir` - A scan result whose root path is absolute.
+    pub fn new(dir: &GitDirectory) -> Self {
+        let metrics = Metrics::collect(dir);
+        Record {
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            root: dir.path.clone(),
+            repositories: metrics.repositories,
+            dirty: metrics.dirty,
+        }
+    }
+}
+
+/// The location of the history file.
+pub fn file() -> Result<PathBuf> {
+    data_dir()
+        .map(|dir| dir.join("history.jsonl"))
+        .ok_or_else(|| anyhow!(locale::text("no-data-dir", &[])))
+}
+
+/// Append `record` to the history file at `path`.
+pub fn append(path: &Path, record: &Record) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| locale::failed("create", &dir))?;
+    }
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    // A single write, so concurrent scans append whole lines.
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(&line))
+        .with_context(|| locale::failed("write", &path))
+}
+
+/// Read the records in the history file at `path`, oldest first; none if it does not exist.
+pub fn read(path: &Path) -> Result<Vec<Record>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| locale::failed("read", &path)),
+    };
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).with_context(|| {
+                locale::text(
+                    "failed-parse-line",
+                    &[("line", &(i + 1)), ("path", &format_args!("{:?}", path))],
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_append_and_read() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("lg/history.jsonl");
+        assert_eq!(read(&path)?, Vec::new());
+
+        let record = |time, repositories| Record {
+            time,
+            root: PathBuf::from("/srv/build"),
+            repositories,
+            dirty: 1,
+        };
+        append(&path, &record(100, 3))?;
+        append(&path, &record(200, 4))?;
+        assert_eq!(read(&path)?, vec![record(100, 3), record(200, 4)]);
+
+        fs::write(&path, "{\"time\": 1}\n")?;
+        assert!(read(&path).is_err());
+        Ok(())
+    }
+}
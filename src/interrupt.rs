@@ -0,0 +1,40 @@
+//! Handling Ctrl-C during a long scan, so that `lg` finishes writing what it found so far
+//! instead of dying mid-write and leaving truncated output.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{anyhow, Result};
+
+use crate::locale;
+
+/// The exit status after an interruption: 128 plus `SIGINT`'s number, as shells report it.
+pub const EXIT_INTERRUPTED: u8 = 130;
+
+static FLAG: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+/// A flag set when `lg` is interrupted with Ctrl-C or asked to terminate, installing the
+/// handler that sets it on the first call. A second interruption exits at once, for when
+/// stopping gracefully takes too long.
+pub fn flag() -> Result<Arc<AtomicBool>> {
+    if let Some(flag) = FLAG.get() {
+        return Ok(flag.clone());
+    }
+    let flag = Arc::new(AtomicBool::new(false));
+    let handler_flag = flag.clone();
+    ctrlc::set_handler(move || {
+        if handler_flag.swap(true, Ordering::Relaxed) {
+            std::process::exit(EXIT_INTERRUPTED.into());
+        }
+        tracing::warn!(
+            code = "interrupted",
+            "interrupted; writing the results so far (interrupt again to quit now)"
+        );
+    })
+    .map_err(|e| anyhow!(e).context(locale::text("failed-ctrl-c", &[])))?;
+    Ok(FLAG.get_or_init(|| flag).clone())
+}
+
+/// Whether `lg` has been interrupted since [`flag`] was first called.
+pub fn interrupted() -> bool {
+    FLAG.get().is_some_and(|flag| flag.load(Ordering::Relaxed))
+}
@@ -0,0 +1,96 @@
+//! Find Git repositories in a directory tree and read their remotes.
+//!
+//! This is the library behind the `lg` command-line tool. The core API is:
+//!
+//! - [`Scanner`] ([`scan`]) walks a directory and returns a [`GitDirectory`] tree, or yields
+//!   repositories one at a time; [`find_git_configs`] is a shorthand for a simple scan, and
+//!   [`scan_with`] calls back for each repository without building a tree.
+//! - [`GitDirectory`] ([`model`]) is that tree; [`GitDirectory::repos`] flattens it.
+//! - [`parse_git_config`] ([`gitconfig`]) reads the remotes from a `.git/config` file.
+//! - [`output::write`] prints a tree in the formats `lg` lists repositories in.
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use lg::{find_git_configs, Backend};
+//!
+//! let tree = find_git_configs(Path::new("."), true, Backend::Native)?;
+//! for (path, repo) in tree.repos() {
+//!     println!("{} {:?}", path.display(), repo.primary_remote());
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! The remaining modules implement the `lg` subcommands and are public so the binary can use
+//! them; they are less stable than the core API.
+pub mod annex;
+pub mod audit;
+pub mod auth;
+pub mod backend;
+pub mod backup;
+pub mod bulk;
+pub mod ci;
+pub mod clone;
+pub mod config;
+pub mod credentials;
+pub mod daemon;
+pub mod diff;
+pub mod dirs;
+pub mod exec;
+pub mod export;
+pub mod forge;
+pub mod fuzzy;
+pub mod ghq;
+pub mod gitconfig;
+pub mod history;
+pub mod index;
+pub mod interrupt;
+pub mod license;
+pub mod locale;
+pub mod logging;
+pub mod mcp;
+pub mod metrics;
+pub mod mirror;
+pub mod model;
+pub mod net;
+pub mod netfs;
+pub mod notify;
+pub mod nuon;
+pub mod obsidian;
+pub mod operation;
+pub mod organize;
+pub mod output;
+pub mod paths;
+pub mod pick;
+pub mod plugin;
+pub mod preview;
+pub mod project;
+pub mod prompt;
+pub mod push;
+pub mod record;
+pub mod refs;
+pub mod report;
+pub mod scan;
+pub mod serve;
+pub mod shell;
+pub mod snapshot;
+pub mod sparse;
+pub mod stash;
+pub mod status;
+pub mod term;
+#[cfg(test)]
+mod testutil;
+pub mod time;
+pub mod tmux;
+pub mod update;
+pub mod url;
+pub mod vcs;
+pub mod vcstool;
+pub mod verify;
+pub mod watch;
+pub mod zoxide;
+
+pub use backend::Backend;
+pub use gitconfig::parse_git_config;
+pub use model::{primary_remote, GitDirectory};
+pub use scan::{find_git_configs, scan_with, Scanner, Visit};
@@ -0,0 +1,167 @@
+//! Recognising a repository's license from the license files at the top of its working tree.
+//!
+//! Only the start of each file is read, and matched against the wording of common licenses, so
+//! this is a quick guess rather than an audit: a license it does not know, or one that has been
+//! edited, is found but left unidentified.
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// How much of a license file is read to identify it.
+const READ_LIMIT: u64 = 4096;
+
+/// The prefixes of license file names, ignoring case, in the order they are preferred.
+const FILE_NAMES: &[&str] = &["license", "licence", "copying", "unlicense"];
+
+/// Phrases that identify a license, each with the SPDX identifier of the license, checked in
+/// order; all of a license's phrases must appear.
+const PHRASES: &[(&str, &[&str])] = &[
+    (
+        "AGPL-3.0",
+        &["GNU AFFERO GENERAL PUBLIC LICENSE", "Version 3"],
+    ),
+    (
+        "LGPL-3.0",
+        &["GNU LESSER GENERAL PUBLIC LICENSE", "Version 3"],
+    ),
+    (
+        "LGPL-2.1",
+        &["GNU LESSER GENERAL PUBLIC LICENSE", "Version 2.1"],
+    ),
+    ("GPL-3.0", &["GNU GENERAL PUBLIC LICENSE", "Version 3"]),
+    ("GPL-2.0", &["GNU GENERAL PUBLIC LICENSE", "Version 2"]),
+    ("Apache-2.0", &["Apache License", "Version 2.0"]),
+    ("MPL-2.0", &["Mozilla Public License", "2.0"]),
+    ("BSL-1.0", &["Boost Software License"]),
+    ("Unlicense", &["This is free and unencumbered software"]),
+    (
+        "ISC",
+        &["Permission to use, copy, modify, and/or distribute this software"],
+    ),
+    ("MIT", &["Permission is hereby granted, free of charge"]),
+    (
+        "BSD-3-Clause",
+        &[
+            "Redistribution and use in source and binary forms",
+            "Neither the name",
+        ],
+    ),
+    (
+        "BSD-2-Clause",
+        &["Redistribution and use in source and binary forms"],
+    ),
+];
+
+/// A repository's license.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct License {
+    /// The license file, relative to the repository: the first of them, if there are several,
+    /// as there are for `LICENSE-APACHE` and `LICENSE-MIT`.
+    pub file: String,
+    /// The SPDX license expression for the license files that could be identified, joined with
+    /// `OR` if they name different licenses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spdx: Option<String>,
+}
+
+/// Find the license of the working tree at `path`, if it has a license file at the top.
+pub fn detect(path: &Path) -> Option<License> {
+    let mut files: Vec<_> = path
+        .read_dir()
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_file()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter_map(|name| {
+            let lower = name.to_ascii_lowercase();
+            let rank = FILE_NAMES
+                .iter()
+                .position(|prefix| lower.starts_with(prefix))?;
+            Some((rank, name))
+        })
+        .collect();
+    files.sort();
+    let (_, file) = files.first()?.clone();
+    let mut ids: Vec<&str> = Vec::new();
+    for (_, name) in &files {
+        if let Some(id) = identify(&path.join(name)) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    Some(License {
+        file,
+        spdx: (!ids.is_empty()).then(|| ids.join(" OR ")),
+    })
+}
+
+/// The SPDX identifier of the license in the file at `path`, if it is recognised.
+fn identify(path: &Path) -> Option<&'static str> {
+    let mut start = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(READ_LIMIT)
+        .read_to_end(&mut start)
+        .ok()?;
+    let start = String::from_utf8_lossy(&start);
+    // Line breaks fall anywhere in a phrase.
+    let text = start.split_whitespace().collect::<Vec<_>>().join(" ");
+    PHRASES
+        .iter()
+        .find(|(_, phrases)| phrases.iter().all(|phrase| text.contains(phrase)))
+        .map(|(id, _)| *id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect() -> std::io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        assert_eq!(detect(repo), None);
+
+        fs::write(
+            repo.join("LICENSE-MIT"),
+            "MIT License\n\nPermission is hereby granted, free of\ncharge, to any person",
+        )?;
+        fs::write(
+            repo.join("LICENSE-APACHE"),
+            "                  Apache License\n            Version 2.0, January 2004\n",
+        )?;
+        fs::write(repo.join("COPYING.md"), "Ask me first.\n")?;
+        assert_eq!(
+            detect(repo),
+            Some(License {
+                file: "LICENSE-APACHE".to_string(),
+                spdx: Some("Apache-2.0 OR MIT".to_string()),
+            })
+        );
+
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        fs::write(repo.join("Copying"), "Ask me first.\n")?;
+        assert_eq!(
+            detect(repo),
+            Some(License {
+                file: "Copying".to_string(),
+                spdx: None,
+            })
+        );
+        fs::write(
+            repo.join("COPYING.LESSER"),
+            "GNU LESSER GENERAL PUBLIC LICENSE\nVersion 3, 29 June 2007\n",
+        )?;
+        assert_eq!(
+            detect(repo).and_then(|license| license.spdx).as_deref(),
+            Some("LGPL-3.0")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+//! Translations of the messages `lg` shows people: the plain listing, working tree statuses and
+//! relative times, the outcomes and summaries of commands, prompts, the picker's status bar, the
+//! problems `lg config check` finds and errors.
+//!
+//! Some text stays in English: logs, machine-readable output (JSON, YAML, NUON, the HTML report
+//! and the answers of `lg serve` and `lg mcp`), the errors recorded against a path in a scan,
+//! which are part of its output, and what the operating system, Git and other tools say.
+//!
+//! Each language's messages are in `locales/<language>.ftl`, one `id = text` per line in the
+//! simple-message subset of Fluent, with arguments written `{ $name }`. A message missing from
+//! a language is shown in English.
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+/// A language `lg` has messages in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Fr,
+}
+
+static LOCALE: AtomicU8 = AtomicU8::new(Locale::En as u8);
+
+impl Locale {
+    /// The language of the locale `tag`, such as `de`, `de_DE.UTF-8` or `fr-CA`; none if it is
+    /// one `lg` has no messages in.
+    pub fn parse(tag: &str) -> Option<Locale> {
+        let language = tag.split(['_', '-', '.', '@']).next()?.to_lowercase();
+        match language.as_str() {
+            "en" | "c" | "posix" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            "fr" => Some(Locale::Fr),
+            _ => None,
+        }
+    }
+
+    /// The locale the environment selects for messages: the first of `LC_ALL`, `LC_MESSAGES`
+    /// and `LANG` that is set, as the C library reads them, or English if it is not one `lg`
+    /// has messages in.
+    pub fn from_env() -> Locale {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|var| std::env::var(var).ok().filter(|tag| !tag.is_empty()))
+            .and_then(|tag| Locale::parse(&tag))
+            .unwrap_or_default()
+    }
+
+    /// The text of the language's messages.
+    fn catalogue(self) -> &'static str {
+        match self {
+            Locale::En => include_str!("../locales/en.ftl"),
+            Locale::De => include_str!("../locales/de.ftl"),
+            Locale::Fr => include_str!("../locales/fr.ftl"),
+        }
+    }
+
+    /// The plural category of `count` in the language: `one` or `other`.
+    fn plural(self, count: usize) -> &'static str {
+        match (self, count) {
+            (Locale::Fr, 0 | 1) | (Locale::En | Locale::De, 1) => "one",
+            _ => "other",
+        }
+    }
+}
+
+/// Show messages in `locale` from now on.
+pub fn init(locale: Locale) {
+    LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+/// The locale last passed to [`init`], or English.
+pub fn get() -> Locale {
+    match LOCALE.load(Ordering::Relaxed) {
+        1 => Locale::De,
+        2 => Locale::Fr,
+        _ => Locale::En,
+    }
+}
+
+/// The message `id` in the current locale, or in English if the locale lacks it; none if
+/// English does too.
+pub fn lookup(id: &str) -> Option<&'static str> {
+    messages(get())
+        .get(id)
+        .or_else(|| messages(Locale::En).get(id))
+        .copied()
+}
+
+/// The message `id`, as [`lookup`] finds it, with each `{ $name }` replaced by the value of
+/// `name` in `args`; `id` itself if there is no such message.
+pub fn text(id: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut message = lookup(id).unwrap_or(id).to_string();
+    for (name, value) in args {
+        message = message.replace(&format!("{{ ${} }}", name), &value.to_string());
+    }
+    message
+}
+
+/// The message `id-one` or `id-other`, whichever the current locale uses for `count`, as
+/// [`text`] gives it, with `count` among its arguments.
+pub fn plural(id: &str, count: usize, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let id = format!("{}-{}", id, get().plural(count));
+    let mut args = args.to_vec();
+    args.push(("count", &count));
+    text(&id, &args)
+}
+
+/// That `lg` failed to do `action` to `what`: the message `failed-<action>`, such as `Failed to
+/// read { $what }`, with `what` quoted as `{:?}` quotes a path.
+pub fn failed(action: &str, what: &dyn fmt::Debug) -> String {
+    text(
+        &format!("failed-{}", action),
+        &[("what", &format_args!("{:?}", what))],
+    )
+}
+
+/// The messages of `locale`, by id, read from its catalogue the first time they are needed.
+fn messages(locale: Locale) -> &'static HashMap<&'static str, &'static str> {
+    static MESSAGES: [OnceLock<HashMap<&str, &str>>; 3] = [const { OnceLock::new() }; 3];
+    MESSAGES[locale as usize].get_or_init(|| parse(locale.catalogue()).collect())
+}
+
+/// The id and text of each message in `catalogue`, in order.
+fn parse(catalogue: &'static str) -> impl Iterator<Item = (&'static str, &'static str)> {
+    catalogue
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            Some((key.trim(), value.trim()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Locale::parse("de_DE.UTF-8"), Some(Locale::De));
+        assert_eq!(Locale::parse("fr-CA"), Some(Locale::Fr));
+        assert_eq!(Locale::parse("C.UTF-8"), Some(Locale::En));
+        assert_eq!(Locale::parse("ja_JP"), None);
+        assert_eq!(Locale::parse(""), None);
+    }
+
+    #[test]
+    fn test_catalogues() {
+        // Every message has an English text, and each other language translates each of them.
+        let ids =
+            |locale: Locale| -> Vec<&str> { parse(locale.catalogue()).map(|(id, _)| id).collect() };
+        let english = ids(Locale::En);
+        for locale in [Locale::De, Locale::Fr] {
+            assert_eq!(ids(locale), english, "{:?}", locale);
+        }
+    }
+
+    #[test]
+    fn test_text() {
+        // The locale is English, as nothing in the tests initializes it.
+        assert_eq!(
+            text("backup-summary", &[("sent", &2), ("unchanged", &1)]),
+            "sent 2 repositories, 1 unchanged"
+        );
+        assert_eq!(
+            plural("pick-confirm", 1, &[("action", &"Fetch")]),
+            "Fetch 1 repository?"
+        );
+        assert_eq!(text("no-such-message", &[]), "no-such-message");
+        assert_eq!(
+            messages(Locale::De).get("backup-summary").copied(),
+            Some("{ $sent } Repositories gesendet, { $unchanged } unverändert")
+        );
+        assert_eq!(Locale::Fr.plural(0), "one");
+        assert_eq!(Locale::De.plural(0), "other");
+    }
+}
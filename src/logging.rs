@@ -0,0 +1,57 @@
+//! Diagnostics on standard error, through `tracing`.
+use clap::ValueEnum;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+
+use crate::term::{Capabilities, Stream};
+
+/// How log lines are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// One human-readable line per event.
+    #[default]
+    Text,
+    /// One JSON object per event, including the spans it occurred in.
+    Json,
+}
+
+/// The most detailed level shown for `verbosity` repetitions of `-v`.
+///
+/// Warnings are always shown; `-v` adds progress, `-vv` each directory and repository, and
+/// `-vvv` everything, with the time spent in each span.
+pub fn level(verbosity: u8) -> LevelFilter {
+    match verbosity {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// Send diagnostics to standard error.
+/// * `level` - The most detailed level to show; see [`level`].
+/// * `format` - How to write each event.
+///
+/// `$LG_LOG` takes an `EnvFilter` directive, such as `lg::scan=trace`, overriding the level.
+pub fn init(level: LevelFilter, format: LogFormat) {
+    let filter = EnvFilter::builder()
+        .with_default_directive(level.into())
+        .with_env_var("LG_LOG")
+        .from_env_lossy();
+    let spans = if level == LevelFilter::TRACE {
+        FmtSpan::CLOSE
+    } else {
+        FmtSpan::NONE
+    };
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(spans)
+        .with_writer(std::io::stderr)
+        .with_ansi(Capabilities::detect(Stream::Stderr).color());
+    // A subscriber may already be set, by a test harness for instance; keep it.
+    let _ = match format {
+        LogFormat::Text => builder.without_time().with_target(false).try_init(),
+        LogFormat::Json => builder.json().with_current_span(true).try_init(),
+    };
+}
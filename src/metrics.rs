@@ -0,0 +1,148 @@
+//! Prometheus metrics describing the repositories under a directory.
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use crate::locale;
+use crate::status::Status;
+use crate::url::RemoteUrl;
+use crate::GitDirectory;
+
+/// Repository health counts for one scanned root.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Metrics {
+    pub repositories: usize,
+    pub dirty: usize,
+    /// Repositories whose branch is behind its upstream.
+    pub behind: usize,
+    /// Repositories whose status could not be read.
+    pub status_errors: usize,
+    /// Repositories by the host of their primary remote; `local` for hostless remotes.
+    pub by_host: BTreeMap<String, usize>,
+}
+
+impl Metrics {
+    /// Count the repositories in `dir`, reading the status of each.
+    pub fn collect(dir: &GitDirectory) -> Self {
+        let mut metrics = Metrics::default();
+        for (path, repo) in dir.repos() {
+            metrics.repositories += 1;
+            let host = repo
+                .primary_remote()
+                .and_then(|(_, url)| RemoteUrl::parse(url))
+                .map_or_else(|| "local".to_string(), |url| url.host);
+            *metrics.by_host.entry(host).or_default() += 1;
+            match Status::read(&path) {
+                Ok(status) => {
+                    metrics.dirty += usize::from(status.is_dirty());
+                    metrics.behind += usize::from(status.behind > 0);
+                }
+                Err(_) => metrics.status_errors += 1,
+            }
+        }
+        metrics
+    }
+
+    /// Render in the Prometheus text exposition format.
+    /// * `root` - The scanned directory, used as the `root` label.
+    /// * `duration` - How long the scan took.
+    pub fn render(&self, root: &Path, duration: Duration) -> String {
+        let root = escape_label(&root.to_string_lossy());
+        let mut out = String::new();
+        let mut gauge = |name: &str, help: &str, value: String| {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} gauge", name);
+            let _ = write!(out, "{}", value);
+        };
+        let sample = |name: &str, value: &dyn std::fmt::Display| {
+            format!("{}{{root=\"{}\"}} {}\n", name, root, value)
+        };
+        gauge(
+            "lg_repositories",
+            "Number of repositories found.",
+            sample("lg_repositories", &self.repositories),
+        );
+        gauge(
+            "lg_repositories_dirty",
+            "Number of repositories with uncommitted or untracked changes.",
+            sample("lg_repositories_dirty", &self.dirty),
+        );
+        gauge(
+            "lg_repositories_behind_upstream",
+            "Number of repositories whose branch is behind its upstream.",
+            sample("lg_repositories_behind_upstream", &self.behind),
+        );
+        gauge(
+            "lg_repository_status_errors",
+            "Number of repositories whose status could not be read.",
+            sample("lg_repository_status_errors", &self.status_errors),
+        );
+        gauge(
+            "lg_repositories_by_host",
+            "Number of repositories by the host of their primary remote.",
+            self.by_host
+                .iter()
+                .map(|(host, count)| {
+                    format!(
+                        "lg_repositories_by_host{{root=\"{}\",host=\"{}\"}} {}\n",
+                        root,
+                        escape_label(host),
+                        count
+                    )
+                })
+                .collect(),
+        );
+        gauge(
+            "lg_scan_duration_seconds",
+            "Time taken to scan and collect metrics.",
+            sample("lg_scan_duration_seconds", &duration.as_secs_f64()),
+        );
+        out
+    }
+}
+
+/// Escape a Prometheus label value.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Write `contents` to `lg.prom` in a node_exporter textfile collector directory.
+///
+/// The file is written under a temporary name and renamed so the collector never reads a
+/// partial file.
+pub fn write_textfile(dir: &Path, contents: &str) -> Result<()> {
+    let tmp = dir.join(format!(".lg.prom.{}", std::process::id()));
+    fs::write(&tmp, contents).with_context(|| locale::failed("write", &tmp))?;
+    let target = dir.join("lg.prom");
+    fs::rename(&tmp, &target).with_context(|| locale::failed("write", &target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics {
+            repositories: 3,
+            dirty: 1,
+            behind: 0,
+            status_errors: 0,
+            by_host: BTreeMap::from([("github.com".to_string(), 2), ("local".to_string(), 1)]),
+        };
+        let text = metrics.render(Path::new("/src/\"q\""), Duration::from_millis(1500));
+        assert!(text.contains("# TYPE lg_repositories gauge\n"));
+        assert!(text.contains("lg_repositories{root=\"/src/\\\"q\\\"\"} 3\n"));
+        assert!(text.contains("lg_repositories_dirty{root=\"/src/\\\"q\\\"\"} 1\n"));
+        assert!(text
+            .contains("lg_repositories_by_host{root=\"/src/\\\"q\\\"\",host=\"github.com\"} 2\n"));
+        assert!(text.contains("lg_scan_duration_seconds{root=\"/src/\\\"q\\\"\"} 1.5\n"));
+    }
+}
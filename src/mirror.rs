@@ -0,0 +1,248 @@
+//! Mirroring repositories to a secondary host, as `lg mirror` does: each repository gets a
+//! remote for its mirror, whose URL comes from a template, and its branches and tags are pushed
+//! there.
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::bulk::{self, Outcome};
+use crate::forge::Forge;
+use crate::locale;
+use crate::url::RemoteUrl;
+use crate::GitDirectory;
+
+/// The placeholders a mirror URL template may use.
+pub const PLACEHOLDERS: &[&str] = &["host", "owner", "repo", "path", "name"];
+
+/// The refspecs pushed to a mirror: every branch and tag, overwriting what is there.
+const REFSPECS: &[&str] = &["+refs/heads/*:refs/heads/*", "+refs/tags/*:refs/tags/*"];
+
+/// The URL of the mirror of `repo`, the repository at `path`, from `template`, in which
+/// `{host}`, `{owner}` and `{path}` are replaced by those of its primary remote, `{repo}` by
+/// the last part of the remote's path and `{name}` by the directory name. `None` if the
+/// template needs a part of the remote URL that it does not have.
+pub fn url_for(template: &str, path: &Path, repo: &GitDirectory) -> Option<String> {
+    let remote = repo
+        .primary_remote()
+        .and_then(|(_, url)| RemoteUrl::parse(url));
+    let mut out = template.to_string();
+    for placeholder in PLACEHOLDERS {
+        let key = format!("{{{}}}", placeholder);
+        if !out.contains(&key) {
+            continue;
+        }
+        let value = match *placeholder {
+            "host" => remote.as_ref()?.host.clone(),
+            "owner" => remote.as_ref()?.owner()?.to_string(),
+            "repo" => {
+                let remote_path = &remote.as_ref()?.path;
+                let name = remote_path
+                    .rsplit_once('/')
+                    .map_or(&**remote_path, |(_, n)| n);
+                name.to_string()
+            }
+            "path" => remote.as_ref()?.path.clone(),
+            "name" => path.file_name()?.to_string_lossy().into_owned(),
+            _ => unreachable!(),
+        };
+        out = out.replace(&key, &value);
+    }
+    Some(out)
+}
+
+/// What mirroring a repository did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mirrored {
+    /// Pushed to the mirror, having first added its remote if `added` and created the
+    /// repository on the forge if `created`.
+    Pushed { added: bool, created: bool },
+    /// Not pushed, the template needing a part of the primary remote's URL it lacks.
+    NoUrl,
+    /// Not pushed, the repository already having a remote of the mirror's name with this other
+    /// URL.
+    Conflict(String),
+    /// Adding the remote or pushing failed, with this message.
+    Failed(String),
+}
+
+impl Outcome for Mirrored {
+    fn label(&self) -> Option<&'static str> {
+        match self {
+            Mirrored::Pushed { .. } => Some("pushed"),
+            Mirrored::NoUrl | Mirrored::Conflict(_) => Some("skipped"),
+            Mirrored::Failed(_) => Some("failed"),
+        }
+    }
+}
+
+impl fmt::Display for Mirrored {
+    /// What happened, in the current locale.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Mirrored::Pushed { added, created } => [
+                (*created, "mirrored-created"),
+                (*added, "mirrored-added"),
+                (true, "outcome-pushed"),
+            ]
+            .into_iter()
+            .filter(|(done, _)| *done)
+            .map(|(_, id)| locale::text(id, &[]))
+            .collect::<Vec<_>>()
+            .join(", "),
+            Mirrored::NoUrl => locale::text("mirrored-no-url", &[]),
+            Mirrored::Conflict(url) => locale::text("mirrored-conflict", &[("url", url)]),
+            Mirrored::Failed(message) => {
+                locale::text("outcome-failed-with", &[("message", message)])
+            }
+        };
+        f.write_str(&text)
+    }
+}
+
+/// Make sure `repo`, the Git repository at `path`, has a remote called `remote` for its mirror
+/// at `url`, adding it if missing, and push every branch and tag to it. Branches and tags
+/// deleted locally are deleted from the mirror. With a `forge`, the repository is first created
+/// there if it does not exist.
+pub fn mirror(
+    path: &Path,
+    repo: &GitDirectory,
+    remote: &str,
+    url: &str,
+    forge: Option<&Forge>,
+) -> Mirrored {
+    let mut created = false;
+    if let Some(forge) = forge {
+        let Some(forge_path) = RemoteUrl::parse(url).map(|url| url.path) else {
+            return Mirrored::Failed(format!("cannot read the repository path of {}", url));
+        };
+        match forge.ensure_repo(&forge_path) {
+            Ok(c) => created = c,
+            Err(e) => return Mirrored::Failed(format!("{:#}", e)),
+        }
+    }
+    let added = match repo.remotes.get(remote) {
+        Some(existing) if existing != url => return Mirrored::Conflict(existing.clone()),
+        Some(_) => false,
+        None => {
+            if let Err(e) = bulk::git(path, &["remote", "add", remote, url]) {
+                return Mirrored::Failed(format!("{:#}", e));
+            }
+            true
+        }
+    };
+    let mut args = vec!["push", "--quiet", "--prune", remote];
+    args.extend(REFSPECS);
+    match bulk::git(path, &args) {
+        Ok(()) => Mirrored::Pushed { added, created },
+        Err(e) => Mirrored::Failed(format!("{:#}", e)),
+    }
+}
+
+/// Mirror each of `repos`, with their absolute paths, to the URL `template` gives it, through
+/// the remote `remote`, `jobs` at a time, creating missing repositories on `forge` if given.
+/// Returns each repository's mirror URL, if it has one, and what happened, in the order of
+/// `repos`.
+pub fn mirror_all(
+    repos: &[(PathBuf, GitDirectory)],
+    template: &str,
+    remote: &str,
+    forge: Option<&Forge>,
+    jobs: usize,
+) -> Result<Vec<(Option<String>, Mirrored)>> {
+    bulk::run_parallel(repos, jobs, |(path, repo)| {
+        match url_for(template, path, repo) {
+            Some(url) => {
+                let mirrored = mirror(path, repo, remote, &url, forge);
+                (Some(url), mirrored)
+            }
+            None => (None, Mirrored::NoUrl),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    use crate::testutil::git;
+
+    fn repo(url: &str) -> GitDirectory {
+        GitDirectory {
+            remotes: HashMap::from([("origin".to_string(), url.to_string())]),
+            ..GitDirectory::directory(PathBuf::from("lg"))
+        }
+    }
+
+    #[test]
+    fn test_url_for() {
+        let path = Path::new("/src/tools/lg");
+        let lg = repo("git@github.com:kthwaite/lg.git");
+        assert_eq!(
+            url_for("git@backup:{owner}/{repo}.git", path, &lg).as_deref(),
+            Some("git@backup:kthwaite/lg.git")
+        );
+        assert_eq!(
+            url_for("https://backup/{host}/{path}", path, &lg).as_deref(),
+            Some("https://backup/github.com/kthwaite/lg")
+        );
+        let nested = repo("https://gitlab.com/group/sub/lg");
+        assert_eq!(
+            url_for("git@backup:{owner}/{repo}.git", path, &nested).as_deref(),
+            Some("git@backup:group/sub/lg.git")
+        );
+        let local = repo("/srv/git/lg.git");
+        assert_eq!(url_for("git@backup:{owner}/{repo}.git", path, &local), None);
+        assert_eq!(
+            url_for("/mnt/mirrors/{name}.git", path, &local).as_deref(),
+            Some("/mnt/mirrors/lg.git")
+        );
+    }
+
+    #[test]
+    fn test_mirror() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let work = temp_dir.path().join("work");
+        let backup = temp_dir.path().join("backup.git");
+        git(temp_dir.path(), &["init", "-q", "-b", "main", "work"]);
+        git(temp_dir.path(), &["init", "-q", "--bare", "backup.git"]);
+        git(&work, &["commit", "-q", "--allow-empty", "-m", "root"]);
+        git(&work, &["tag", "v1"]);
+        let url = backup.to_string_lossy().into_owned();
+
+        let lg = repo("https://github.com/kthwaite/lg");
+        assert_eq!(
+            mirror(&work, &lg, "mirror", &url, None),
+            Mirrored::Pushed {
+                added: true,
+                created: false
+            }
+        );
+        let refs = std::process::Command::new("git")
+            .current_dir(&backup)
+            .args(["for-each-ref", "--format=%(refname)"])
+            .output()?;
+        assert_eq!(
+            String::from_utf8(refs.stdout)?,
+            "refs/heads/main\nrefs/tags/v1\n"
+        );
+
+        let mirrored = GitDirectory {
+            remotes: HashMap::from([("mirror".to_string(), url.clone())]),
+            ..lg.clone()
+        };
+        assert_eq!(
+            mirror(&work, &mirrored, "mirror", &url, None),
+            Mirrored::Pushed {
+                added: false,
+                created: false
+            }
+        );
+        assert_eq!(
+            mirror(&work, &mirrored, "mirror", "/elsewhere.git", None),
+            Mirrored::Conflict(url)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,427 @@
+//! The tree of directories and repositories produced by a scan.
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::annex::Annex;
+use crate::ci::CiSystem;
+use crate::license::License;
+use crate::operation::Operation;
+use crate::paths;
+use crate::project::ProjectType;
+use crate::push::Push;
+use crate::sparse::SparseCheckout;
+use crate::status::Tracking;
+use crate::time;
+use crate::vcs::VcsKind;
+
+/// A directory with a .git/config file, or another repository the scan was asked to detect,
+/// and possibly other subdirectories.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitDirectory {
+    /// The directory, relative to its parent; the root holds the scanned path as given.
+    #[serde(flatten, with = "paths::encoded")]
+    pub path: PathBuf,
+    /// Remote names and URLs; empty if the directory is not a repository.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub remotes: HashMap<String, String>,
+    /// The name of the remote that identifies the repository, as chosen by the scan; see
+    /// [`preferred_remote`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_remote: Option<String>,
+    /// Every URL of the remotes that have more than the one in `remotes`, by remote name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub urls: BTreeMap<String, RemoteUrls>,
+    /// The version control system of the repository; Git, and omitted, for other directories.
+    #[serde(default, skip_serializing_if = "VcsKind::is_git")]
+    pub vcs: VcsKind,
+    /// Where the repository's Git directory is, if not a `.git` directory inside it: the target
+    /// of a `.git` symlink or file, or `GIT_DIR`.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "paths::lossy"
+    )]
+    pub git_dir: Option<PathBuf>,
+    /// The repository's git-annex, if it uses one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annex: Option<Annex>,
+    /// What the working tree checks out, if it is a sparse checkout.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sparse_checkout: Option<SparseCheckout>,
+    /// Whether the repository is a partial clone, which fetches objects left out of the clone
+    /// from a promisor remote on demand, so needs the network for some operations.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial_clone: bool,
+    /// The filter a partial clone was made with, such as `blob:none`, if recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partial_clone_filter: Option<String>,
+    /// A merge, rebase, cherry-pick or revert that was started in the working tree and not
+    /// finished or aborted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_progress: Option<Operation>,
+    /// The ecosystems with a manifest, such as `Cargo.toml`, at the top of the working tree.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub project_types: Vec<ProjectType>,
+    /// The CI systems with configuration in the working tree, such as `.gitlab-ci.yml`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ci: Vec<CiSystem>,
+    /// The repository's license file, and its license if recognised; only read when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<License>,
+    /// The repository's `remote.pushDefault`, and where `git push` sends each branch; only read
+    /// when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub push: Option<Push>,
+    /// How each local branch with an upstream compares with it, by branch name; only read when
+    /// requested.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tracking: BTreeMap<String, Tracking>,
+    /// When the repository last fetched, in seconds since the Unix epoch; written as an
+    /// ISO 8601 UTC time.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "time::utc")]
+    pub last_fetch: Option<u64>,
+    /// Whether each remote could be reached, by remote name; only checked when requested.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub remote_checks: BTreeMap<String, RemoteCheck>,
+    /// When the repository's first commit was made, in seconds since the Unix epoch; written
+    /// as an ISO 8601 UTC time. Only read when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "time::utc")]
+    pub first_commit: Option<u64>,
+    /// The checked-out branch, or commit if detached; only read when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+    /// Metadata from collector plugins, keyed by plugin name; only collected when requested.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub plugins: BTreeMap<String, Value>,
+    /// Subdirectories that are, or contain, repositories.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<GitDirectory>,
+    /// Paths the scan could not read and skipped; only set on the root of a tree.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ScanError>,
+}
+
+/// The URLs of a remote that has several `url` values or separate `pushurl`s, as Git allows.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteUrls {
+    /// The `url` values in order. Git fetches from the first, which is the remote's URL in
+    /// [`GitDirectory::remotes`], and pushes to all of them unless there are push URLs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fetch: Vec<String>,
+    /// The `pushurl` values in order, which Git pushes to instead.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub push: Vec<String>,
+}
+
+impl RemoteUrls {
+    /// Whether the remote has more than a single fetch URL used for pushing too.
+    pub fn is_multiple(&self) -> bool {
+        self.fetch.len() > 1 || !self.push.is_empty()
+    }
+}
+
+/// Whether a remote could be reached, as `--check-remotes` finds with `git ls-remote`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteCheck {
+    pub reachable: bool,
+    /// Why the remote could not be reached.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A path a scan skipped because it could not be read.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanError {
+    /// The directory or repository that could not be read.
+    #[serde(flatten, with = "paths::encoded")]
+    pub path: PathBuf,
+    /// The kind of failure; see [`ScanError::code_for`].
+    #[serde(default)]
+    pub code: String,
+    /// What went wrong, including its causes.
+    pub message: String,
+}
+
+impl ScanError {
+    /// The code of the error recorded at the root of a scan that was interrupted, whose results
+    /// are partial.
+    pub const INTERRUPTED: &'static str = "interrupted";
+
+    /// Record that the scan of `root` was interrupted before it was finished.
+    pub fn interrupted(root: &Path) -> Self {
+        ScanError {
+            path: root.to_path_buf(),
+            code: Self::INTERRUPTED.to_string(),
+            message: "Interrupted; the results are partial".to_string(),
+        }
+    }
+
+    /// Record `error`, with its chain of causes, against `path`.
+    pub fn new(path: &Path, error: &anyhow::Error) -> Self {
+        ScanError {
+            path: path.to_path_buf(),
+            code: Self::code_for(error).to_string(),
+            message: format!("{:#}", error),
+        }
+    }
+
+    /// Log this as a warning on standard error, with `code` and `path` as fields, so that
+    /// `--log-format json` gives programs the same record as the output's `errors`. It is
+    /// logged from `lg::scan`, whichever walk skipped the path.
+    pub fn warn(&self) {
+        tracing::warn!(
+            target: "lg::scan",
+            code = self.code,
+            path = %self.path.display(),
+            "skipping: {}",
+            self.message
+        );
+    }
+
+    /// A stable name for the kind of failure behind `error`, for programs to match on:
+    /// `not_found`, `permission_denied`, `invalid_data` (such as a config that is not UTF-8),
+    /// `timed_out` (a network filesystem that did not respond), or `other`.
+    pub fn code_for(error: &anyhow::Error) -> &'static str {
+        let kind = error
+            .chain()
+            .find_map(|e| e.downcast_ref::<io::Error>())
+            .map(io::Error::kind);
+        match kind {
+            Some(io::ErrorKind::NotFound) => "not_found",
+            Some(io::ErrorKind::PermissionDenied) => "permission_denied",
+            Some(io::ErrorKind::InvalidData) => "invalid_data",
+            Some(io::ErrorKind::TimedOut) => "timed_out",
+            _ => "other",
+        }
+    }
+}
+
+/// A summary of the paths a scan skipped, counted by [`ScanError::code`], such as
+/// `skipped 3 paths that could not be read (2 permission_denied, 1 not_found)`; `None` if
+/// there are none. An interruption is not a path skipped, so is not counted.
+pub fn summarize(errors: &[ScanError]) -> Option<String> {
+    let errors: Vec<_> = errors
+        .iter()
+        .filter(|error| error.code != ScanError::INTERRUPTED)
+        .collect();
+    if errors.is_empty() {
+        return None;
+    }
+    let mut counts = BTreeMap::new();
+    for error in &errors {
+        *counts.entry(error.code.as_str()).or_insert(0) += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|(a, m), (b, n)| n.cmp(m).then(a.cmp(b)));
+    let counts: Vec<_> = counts
+        .iter()
+        .map(|(code, count)| format!("{} {}", count, code))
+        .collect();
+    Some(format!(
+        "skipped {} {} that could not be read ({})",
+        errors.len(),
+        if errors.len() == 1 { "path" } else { "paths" },
+        counts.join(", ")
+    ))
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+impl GitDirectory {
+    /// The remote that identifies this repository: the one the scan chose, if recorded,
+    /// otherwise as [`primary_remote`] chooses.
+    pub fn primary_remote(&self) -> Option<(&str, &str)> {
+        preferred_remote(&self.remotes, self.primary_remote.as_deref())
+    }
+
+    /// Keep only the repositories for which `keep` is true. The others are removed from the
+    /// tree, or, if repositories beneath them are kept, left as plain directories.
+    pub fn retain_repos(&mut self, keep: &impl Fn(&GitDirectory) -> bool) {
+        for child in &mut self.children {
+            child.retain_repos(keep);
+        }
+        self.children
+            .retain(|child| !child.remotes.is_empty() || !child.children.is_empty());
+        if !self.remotes.is_empty() && !keep(self) {
+            let mut dir = GitDirectory::directory(std::mem::take(&mut self.path));
+            dir.children = std::mem::take(&mut self.children);
+            dir.errors = std::mem::take(&mut self.errors);
+            *self = dir;
+        }
+    }
+
+    /// This tree with every repository in it, the root included, moved directly beneath a plain
+    /// directory at the root's path, each with its path joined onto the root's as in
+    /// [`GitDirectory::repos`].
+    pub fn flatten(mut self) -> Self {
+        let mut root = GitDirectory::directory(self.path.clone());
+        root.errors = std::mem::take(&mut self.errors);
+        self.flatten_into(Path::new(""), &mut root.children);
+        root
+    }
+
+    fn flatten_into(mut self, parent: &Path, out: &mut Vec<GitDirectory>) {
+        let path = parent.join(&self.path);
+        let children = std::mem::take(&mut self.children);
+        if !self.remotes.is_empty() {
+            self.path = path.clone();
+            out.push(self);
+        }
+        for child in children {
+            child.flatten_into(&path, out);
+        }
+    }
+
+    /// A directory at `path` that is not a repository, with nothing beneath it.
+    pub fn directory(path: PathBuf) -> Self {
+        GitDirectory {
+            path,
+            remotes: HashMap::new(),
+            primary_remote: None,
+            urls: BTreeMap::new(),
+            vcs: VcsKind::Git,
+            git_dir: None,
+            annex: None,
+            sparse_checkout: None,
+            partial_clone: false,
+            partial_clone_filter: None,
+            in_progress: None,
+            project_types: Vec::new(),
+            ci: Vec::new(),
+            license: None,
+            push: None,
+            tracking: BTreeMap::new(),
+            last_fetch: None,
+            remote_checks: BTreeMap::new(),
+            first_commit: None,
+            head: None,
+            plugins: BTreeMap::new(),
+            children: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Flatten the tree into every directory that has remotes, paired with its full path.
+    pub fn repos(&self) -> Vec<(PathBuf, &GitDirectory)> {
+        let mut repos = Vec::new();
+        self.collect_repos(Path::new(""), &mut repos);
+        repos
+    }
+
+    fn collect_repos<'a>(&'a self, parent: &Path, out: &mut Vec<(PathBuf, &'a GitDirectory)>) {
+        let path = parent.join(&self.path);
+        if !self.remotes.is_empty() {
+            out.push((path.clone(), self));
+        }
+        for child in &self.children {
+            child.collect_repos(&path, out);
+        }
+    }
+}
+
+/// The remote that identifies a repository: `origin` if present, then `default`, as Mercurial,
+/// Subversion and Fossil repositories have, otherwise the first by name.
+pub fn primary_remote(remotes: &HashMap<String, String>) -> Option<(&str, &str)> {
+    preferred_remote(remotes, None)
+}
+
+/// The remote that identifies a repository: `preferred` if the repository has a remote of that
+/// name, such as Git's `checkout.defaultRemoteName`, and otherwise as [`primary_remote`]
+/// chooses.
+pub fn preferred_remote<'a>(
+    remotes: &'a HashMap<String, String>,
+    preferred: Option<&str>,
+) -> Option<(&'a str, &'a str)> {
+    preferred
+        .and_then(|name| remotes.get_key_value(name))
+        .or_else(|| remotes.get_key_value("origin"))
+        .or_else(|| remotes.get_key_value("default"))
+        .or_else(|| remotes.iter().min_by_key(|(name, _)| *name))
+        .map(|(name, url)| (name.as_str(), url.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retain_repos() {
+        let repo = |path: &str, children| GitDirectory {
+            remotes: HashMap::from([("origin".to_string(), format!("/srv/{}", path))]),
+            children,
+            ..GitDirectory::directory(PathBuf::from(path))
+        };
+        let mut tree = GitDirectory {
+            children: vec![
+                repo("a", vec![repo("b", Vec::new())]),
+                repo("c", vec![repo("d", Vec::new())]),
+            ],
+            ..GitDirectory::directory(PathBuf::from("/src"))
+        };
+        tree.retain_repos(&|repo| repo.path != Path::new("a") && repo.path != Path::new("d"));
+        let paths: Vec<_> = tree.repos().into_iter().map(|(path, _)| path).collect();
+        assert_eq!(paths, [PathBuf::from("/src/a/b"), PathBuf::from("/src/c")]);
+        // `a` remains, as a plain directory, for `b` beneath it.
+        assert!(tree.children[0].remotes.is_empty());
+        assert_eq!(tree.children[1].children, []);
+    }
+
+    #[test]
+    fn test_flatten() {
+        let repo = |path: &str, children| GitDirectory {
+            remotes: HashMap::from([("origin".to_string(), format!("/srv/{}", path))]),
+            children,
+            ..GitDirectory::directory(PathBuf::from(path))
+        };
+        let tree = GitDirectory {
+            children: vec![
+                GitDirectory {
+                    children: vec![repo("b", Vec::new())],
+                    ..GitDirectory::directory(PathBuf::from("a"))
+                },
+                repo("c", vec![repo("d", Vec::new())]),
+            ],
+            ..repo("/src", Vec::new())
+        };
+        let flat = tree.flatten();
+        assert_eq!(flat.path, Path::new("/src"));
+        assert!(flat.remotes.is_empty());
+        let paths: Vec<_> = flat.children.iter().map(|child| &child.path).collect();
+        assert_eq!(paths, ["/src", "/src/a/b", "/src/c", "/src/c/d"]);
+        assert!(flat.children.iter().all(|child| child.children.is_empty()));
+    }
+
+    #[test]
+    fn test_summarize() {
+        let error = |code: &str| ScanError {
+            path: PathBuf::from("/src"),
+            code: code.to_string(),
+            message: String::new(),
+        };
+        assert_eq!(summarize(&[]), None);
+        assert_eq!(
+            summarize(&[error("other")]).as_deref(),
+            Some("skipped 1 path that could not be read (1 other)")
+        );
+        assert_eq!(
+            summarize(&[
+                error("not_found"),
+                error("permission_denied"),
+                error("permission_denied")
+            ])
+            .as_deref(),
+            Some("skipped 3 paths that could not be read (2 permission_denied, 1 not_found)")
+        );
+    }
+}
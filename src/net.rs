@@ -0,0 +1,512 @@
+//! Running network operations, such as checking that remotes can be reached, against many
+//! hosts at once without overwhelming any one of them or hanging on one that does not answer.
+//!
+//! A [`Network`] runs each operation with a timeout, retries those that fail in ways that may
+//! pass with exponential backoff, and limits how many run against each host at once. A host
+//! that cannot be reached at all is given up on, so its other operations fail at once, and
+//! when every host tried is unreachable the network is taken to be offline and the remaining
+//! operations are not attempted.
+//!
+//! [`check_remotes`] uses it for `lg --check-remotes`.
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::bulk;
+use crate::model::RemoteCheck;
+use crate::refs;
+use crate::url::RemoteUrl;
+use crate::GitDirectory;
+
+/// How many hosts must be unreachable, with none reached, before the network is taken to be
+/// offline.
+const OFFLINE_AFTER: usize = 2;
+
+/// How operations are run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Policy {
+    /// The most operations run at once.
+    pub jobs: usize,
+    /// The most operations run against one host at once.
+    pub per_host: usize,
+    /// How long an attempt may take.
+    pub timeout: Duration,
+    /// How many times an attempt that failed in a way that may pass is retried.
+    pub retries: u32,
+    /// How long to wait before the first retry; each later one waits twice as long.
+    pub backoff: Duration,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy {
+            jobs: 16,
+            per_host: 4,
+            timeout: Duration::from_secs(20),
+            retries: 2,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Why an attempt failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Failure {
+    /// The host could not be reached or did not answer in time.
+    Unreachable(String),
+    /// The host answered with an error that may pass, such as a server error.
+    Transient(String),
+    /// The host answered with an error that retrying will not change, such as a refusal.
+    Permanent(String),
+}
+
+impl Failure {
+    fn message(self) -> String {
+        match self {
+            Failure::Unreachable(message)
+            | Failure::Transient(message)
+            | Failure::Permanent(message) => message,
+        }
+    }
+}
+
+/// Runs operations under a [`Policy`].
+pub struct Network {
+    policy: Policy,
+    hosts: Mutex<Hosts>,
+    freed: Condvar,
+}
+
+/// What is known of the hosts operations have been run against.
+#[derive(Default)]
+struct Hosts {
+    /// The number of operations running against each host.
+    active: HashMap<String, usize>,
+    /// Hosts that could not be reached.
+    unreachable: HashSet<String>,
+    /// Whether any host has been reached.
+    reached: bool,
+}
+
+impl Hosts {
+    fn offline(&self) -> bool {
+        !self.reached && self.unreachable.len() >= OFFLINE_AFTER
+    }
+}
+
+impl Network {
+    pub fn new(policy: Policy) -> Self {
+        Network {
+            policy,
+            hosts: Mutex::default(),
+            freed: Condvar::new(),
+        }
+    }
+
+    /// Run `attempt` on each of `items`, against the host `host` gives for it, returning the
+    /// results in the order of `items`. `attempt` is given the time it may take.
+    pub fn run_all<I: Sync, T: Send>(
+        &self,
+        items: &[I],
+        host: impl Fn(&I) -> String + Send + Sync,
+        attempt: impl Fn(&I, Duration) -> Result<T, Failure> + Send + Sync,
+    ) -> Result<Vec<Result<T, String>>> {
+        bulk::run_parallel(items, self.policy.jobs, |item| {
+            self.run(&host(item), |timeout| attempt(item, timeout))
+        })
+    }
+
+    /// Run `attempt` against `host`, retrying it as the policy allows.
+    pub fn run<T>(
+        &self,
+        host: &str,
+        attempt: impl Fn(Duration) -> Result<T, Failure>,
+    ) -> Result<T, String> {
+        let mut tries = 0;
+        loop {
+            self.acquire(host)?;
+            let result = attempt(self.policy.timeout);
+            self.release(host, &result);
+            let failure = match result {
+                Ok(value) => return Ok(value),
+                Err(Failure::Permanent(message)) => return Err(message),
+                Err(failure) if tries >= self.policy.retries => {
+                    if matches!(failure, Failure::Unreachable(_)) {
+                        self.give_up(host);
+                    }
+                    return Err(failure.message());
+                }
+                Err(failure) => failure,
+            };
+            tracing::debug!(host, tries, "retrying: {}", failure.message());
+            thread::sleep(self.policy.backoff * 2u32.saturating_pow(tries));
+            tries += 1;
+        }
+    }
+
+    /// Wait for a free slot for `host`; fails if the host, or the network, is given up on.
+    fn acquire(&self, host: &str) -> Result<(), String> {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            if hosts.offline() {
+                return Err("offline: no host could be reached".to_string());
+            }
+            if hosts.unreachable.contains(host) {
+                return Err(format!("{} could not be reached", host));
+            }
+            let active = hosts.active.entry(host.to_string()).or_default();
+            if *active < self.policy.per_host.max(1) {
+                *active += 1;
+                return Ok(());
+            }
+            hosts = self.freed.wait(hosts).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// Free the slot for `host` taken for an attempt, noting what the attempt found.
+    fn release<T>(&self, host: &str, result: &Result<T, Failure>) {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(active) = hosts.active.get_mut(host) {
+            *active -= 1;
+        }
+        match result {
+            Err(Failure::Unreachable(_)) => {}
+            _ => hosts.reached = true,
+        }
+        drop(hosts);
+        self.freed.notify_all();
+    }
+
+    /// Give up on `host`, which could not be reached after retrying.
+    fn give_up(&self, host: &str) {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        if hosts.unreachable.insert(host.to_string()) {
+            tracing::warn!(host, "giving up on unreachable host");
+        }
+        drop(hosts);
+        self.freed.notify_all();
+    }
+}
+
+/// Check that each remote of each Git repository in `dir` can be reached, with
+/// `git ls-remote`, recording the results in [`GitDirectory::remote_checks`]. Remotes that are
+/// local paths are not checked. Returns how many could not be reached.
+pub fn check_remotes(dir: &mut GitDirectory, network: &Network) -> Result<usize> {
+    let mut remotes: Vec<(PathBuf, String, String)> = Vec::new();
+    for (path, repo) in dir.repos() {
+        if !repo.vcs.is_git() {
+            continue;
+        }
+        for (name, url) in &repo.remotes {
+            if let Some(remote) = RemoteUrl::parse(url) {
+                remotes.push((path.clone(), name.clone(), remote.host));
+            }
+        }
+    }
+    remotes.sort();
+    let results = network.run_all(
+        &remotes,
+        |(_, _, host)| host.clone(),
+        |(path, name, _), timeout| {
+            let mut command = refs::git_command(path);
+            command
+                .args(["ls-remote", name, "HEAD"])
+                .env("GIT_TERMINAL_PROMPT", "0");
+            // Fail rather than ask for a passphrase or to trust a host key.
+            if std::env::var_os("GIT_SSH_COMMAND").is_none() {
+                command.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+            }
+            output_within(&mut command, timeout).map(|_| ())
+        },
+    )?;
+    let mut checks: HashMap<PathBuf, BTreeMap<String, RemoteCheck>> = HashMap::new();
+    let mut unreachable = 0;
+    for ((path, name, _), result) in remotes.into_iter().zip(results) {
+        let check = RemoteCheck {
+            reachable: result.is_ok(),
+            error: result.err(),
+        };
+        unreachable += usize::from(!check.reachable);
+        checks.entry(path).or_default().insert(name, check);
+    }
+    record_checks(dir, Path::new(""), &mut checks);
+    Ok(unreachable)
+}
+
+fn record_checks(
+    dir: &mut GitDirectory,
+    parent: &Path,
+    checks: &mut HashMap<PathBuf, BTreeMap<String, RemoteCheck>>,
+) {
+    let path = parent.join(&dir.path);
+    if let Some(found) = checks.remove(&path) {
+        dir.remote_checks = found;
+    }
+    for child in &mut dir.children {
+        record_checks(child, &path, checks);
+    }
+}
+
+/// Run `command` to completion, killing it if it takes longer than `timeout`, and classify how
+/// it failed from what it printed to standard error.
+pub fn output_within(command: &mut Command, timeout: Duration) -> Result<Output, Failure> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Failure::Permanent(format!("Failed to run {:?}: {}", command, e)))?;
+    // Read the output as it comes, so that a command printing a lot does not block.
+    let read = |pipe: Option<Box<dyn Read + Send>>| {
+        thread::spawn(move || {
+            let mut bytes = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut bytes);
+            }
+            bytes
+        })
+    };
+    let stdout = read(
+        child
+            .stdout
+            .take()
+            .map(|p| Box::new(p) as Box<dyn Read + Send>),
+    );
+    let stderr = read(
+        child
+            .stderr
+            .take()
+            .map(|p| Box::new(p) as Box<dyn Read + Send>),
+    );
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(20)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Failure::Unreachable(format!(
+                    "no answer within {}s",
+                    timeout.as_secs()
+                )));
+            }
+            Err(e) => return Err(Failure::Permanent(format!("Failed to wait: {}", e))),
+        }
+    };
+    let output = Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    };
+    if output.status.success() {
+        return Ok(output);
+    }
+    Err(classify(String::from_utf8_lossy(&output.stderr).trim()))
+}
+
+/// How a Git or SSH command failed, from the last line of its error output.
+fn classify(stderr: &str) -> Failure {
+    const UNREACHABLE: &[&str] = &[
+        "could not resolve host",
+        "could not resolve hostname",
+        "name or service not known",
+        "connection refused",
+        "connection timed out",
+        "operation timed out",
+        "network is unreachable",
+        "no route to host",
+        "failed to connect",
+    ];
+    const TRANSIENT: &[&str] = &[
+        "the requested url returned error: 5",
+        "the requested url returned error: 429",
+        "connection reset",
+        "early eof",
+        "remote end hung up unexpectedly",
+    ];
+    let message = stderr
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("failed")
+        .trim()
+        .to_string();
+    let lower = stderr.to_lowercase();
+    if UNREACHABLE.iter().any(|pattern| lower.contains(pattern)) {
+        Failure::Unreachable(message)
+    } else if TRANSIENT.iter().any(|pattern| lower.contains(pattern)) {
+        Failure::Transient(message)
+    } else {
+        Failure::Permanent(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::testutil;
+
+    fn policy() -> Policy {
+        Policy {
+            jobs: 8,
+            per_host: 2,
+            timeout: Duration::from_secs(1),
+            retries: 2,
+            backoff: Duration::from_millis(1),
+        }
+    }
+
+    #[test]
+    fn test_per_host_limit() -> Result<()> {
+        let network = Network::new(policy());
+        let running = AtomicUsize::new(0);
+        let most = AtomicUsize::new(0);
+        let items: Vec<_> = (0..16).collect();
+        let results = network.run_all(
+            &items,
+            |_| "example.com".to_string(),
+            |item, _| {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                most.fetch_max(now, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(5));
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(*item)
+            },
+        )?;
+        assert_eq!(results, items.into_iter().map(Ok).collect::<Vec<_>>());
+        assert!(most.load(Ordering::SeqCst) <= 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_retries() {
+        let network = Network::new(policy());
+        let tries = AtomicUsize::new(0);
+        let result = network.run("example.com", |_| {
+            match tries.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err(Failure::Transient("502".to_string())),
+                _ => Ok("ok"),
+            }
+        });
+        assert_eq!(result, Ok("ok"));
+        assert_eq!(tries.load(Ordering::SeqCst), 3);
+
+        // Permanent failures are not retried.
+        let tries = AtomicUsize::new(0);
+        let result: Result<(), _> = network.run("example.com", |_| {
+            tries.fetch_add(1, Ordering::SeqCst);
+            Err(Failure::Permanent("Repository not found".to_string()))
+        });
+        assert_eq!(result, Err("Repository not found".to_string()));
+        assert_eq!(tries.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let network = Network::new(policy());
+        let tries = AtomicUsize::new(0);
+        let unreachable = |_| -> Result<(), Failure> {
+            tries.fetch_add(1, Ordering::SeqCst);
+            Err(Failure::Unreachable("no answer".to_string()))
+        };
+        assert!(network.run("dead.example", unreachable).is_err());
+        assert_eq!(tries.load(Ordering::SeqCst), 3);
+        // The host is given up on.
+        assert_eq!(
+            network.run("dead.example", unreachable),
+            Err("dead.example could not be reached".to_string())
+        );
+        assert_eq!(tries.load(Ordering::SeqCst), 3);
+
+        // With a second host unreachable and none reached, the network is offline.
+        assert!(network.run("other.example", unreachable).is_err());
+        assert_eq!(
+            network.run("up.example", |_| Ok(())),
+            Err("offline: no host could be reached".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_remotes() -> Result<()> {
+        // A server speaking Git's dumb HTTP protocol, with one branch.
+        let server = tiny_http::Server::http("127.0.0.1:0").map_err(|e| anyhow::anyhow!(e))?;
+        let addr = server.server_addr().to_ip().unwrap();
+        thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let body = match request.url().split('?').next() {
+                    Some("/me/up.git/info/refs") => {
+                        format!("{}\trefs/heads/main\n", "1".repeat(40))
+                    }
+                    Some("/me/up.git/HEAD") => "ref: refs/heads/main\n".to_string(),
+                    _ => {
+                        let _ = request.respond(tiny_http::Response::empty(404));
+                        continue;
+                    }
+                };
+                let _ = request.respond(tiny_http::Response::from_string(body));
+            }
+        });
+
+        let temp_dir = tempfile::TempDir::new()?;
+        let repo = temp_dir.path().join("repo");
+        std::fs::create_dir(&repo)?;
+        let git = |args: &[&str]| testutil::git(&repo, args);
+        git(&["init", "-q"]);
+        git(&[
+            "remote",
+            "add",
+            "origin",
+            &format!("http://{}/me/up.git", addr),
+        ]);
+        git(&[
+            "remote",
+            "add",
+            "gone",
+            &format!("http://{}/me/gone.git", addr),
+        ]);
+        git(&["remote", "add", "local", "/nowhere"]);
+
+        let mut dir = crate::Scanner::builder().build()?.scan(temp_dir.path())?;
+        let network = Network::new(policy());
+        assert_eq!(check_remotes(&mut dir, &network)?, 1);
+        let checks = &dir.children[0].remote_checks;
+        assert_eq!(checks.len(), 2);
+        assert!(checks["origin"].reachable);
+        assert!(!checks["gone"].reachable);
+        assert!(checks["gone"].error.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_within() {
+        let output = output_within(
+            Command::new("git").arg("--version"),
+            Duration::from_secs(10),
+        );
+        assert!(output.is_ok_and(|output| output.stdout.starts_with(b"git version")));
+        let output = output_within(
+            Command::new("sh").args(["-c", "sleep 5"]),
+            Duration::from_millis(100),
+        );
+        assert!(matches!(output, Err(Failure::Unreachable(_))));
+        assert_eq!(
+            classify("fatal: unable to access 'https://x/': Could not resolve host: x"),
+            Failure::Unreachable(
+                "fatal: unable to access 'https://x/': Could not resolve host: x".to_string()
+            )
+        );
+        assert_eq!(
+            classify("remote: Repository not found.\nfatal: repository 'x' not found"),
+            Failure::Permanent("fatal: repository 'x' not found".to_string())
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! Recognising network filesystems, where a dead server or automount can block a scan.
+//!
+//! Mount points are looked up in the system's mount table rather than by reading the
+//! directory, which is what hangs: `/proc/self/mountinfo` on Linux and the output of `mount`
+//! on macOS and the BSDs. Other platforms report no network filesystems.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+/// What a scan does on reaching a directory where a network filesystem is mounted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum NetworkFs {
+    /// Log a warning and scan it, skipping it if it does not respond within [`TIMEOUT`].
+    #[default]
+    Warn,
+    /// Leave it out of the scan.
+    Skip,
+    /// Scan it like any other directory.
+    Scan,
+}
+
+/// How long a network filesystem may take to list a directory before it is skipped.
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Filesystem types that are served over a network, or by a process that may stop answering.
+const NETWORK_TYPES: &[&str] = &[
+    "9p",
+    "afpfs",
+    "afs",
+    "autofs",
+    "beegfs",
+    "ceph",
+    "cifs",
+    "davfs",
+    "fuse",
+    "glusterfs",
+    "gpfs",
+    "lustre",
+    "macfuse",
+    "ncpfs",
+    "nfs",
+    "nfs4",
+    "osxfuse",
+    "smb3",
+    "smbfs",
+    "sshfs",
+    "webdav",
+];
+
+/// A filesystem mounted at a directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mount {
+    /// The directory it is mounted at.
+    pub point: PathBuf,
+    /// Its type, as the mount table names it, such as `nfs4` or `fuse.sshfs`.
+    pub fstype: String,
+}
+
+impl Mount {
+    /// Whether this is a network filesystem, or a FUSE filesystem, which may be one.
+    pub fn is_network(&self) -> bool {
+        let fstype = self.fstype.as_str();
+        NETWORK_TYPES.contains(&fstype)
+            || fstype.starts_with("fuse.")
+            || fstype.starts_with("nfs")
+            || fstype.starts_with("smb")
+    }
+}
+
+/// The type of the network filesystem mounted at `path`, if one is; `None` for directories
+/// within one, so that a scan is warned once, where it crosses into it.
+pub fn mount_type(path: &Path) -> Option<&'static str> {
+    static MOUNTS: OnceLock<Vec<Mount>> = OnceLock::new();
+    let mounts = MOUNTS.get_or_init(|| read_mounts().unwrap_or_default());
+    if mounts.is_empty() {
+        return None;
+    }
+    let path = std::path::absolute(path).ok()?;
+    // Mounts stacked on one directory are listed bottom first.
+    mounts
+        .iter()
+        .rev()
+        .find(|mount| mount.point == path)
+        .filter(|mount| mount.is_network())
+        .map(|mount| mount.fstype.as_str())
+}
+
+/// Whether `dir` can be listed within `timeout`. The listing runs on a thread of its own,
+/// which is left behind if it hangs; an error counts as a response.
+pub fn responds(dir: &Path, timeout: Duration) -> bool {
+    let (sender, receiver) = mpsc::channel();
+    let dir = dir.to_path_buf();
+    thread::spawn(move || {
+        let _ = sender.send(fs::read_dir(&dir).map(|mut entries| entries.next().is_some()));
+    });
+    receiver.recv_timeout(timeout).is_ok()
+}
+
+#[cfg(target_os = "linux")]
+fn read_mounts() -> Option<Vec<Mount>> {
+    fs::read_to_string("/proc/self/mountinfo")
+        .ok()
+        .map(|mountinfo| parse_mountinfo(&mountinfo))
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd"
+))]
+fn read_mounts() -> Option<Vec<Mount>> {
+    let output = std::process::Command::new("mount").output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| parse_mount_output(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd"
+)))]
+fn read_mounts() -> Option<Vec<Mount>> {
+    None
+}
+
+/// Read the mounts in Linux's `/proc/self/mountinfo`, whose lines are
+/// `id parent major:minor root point options [optional...] - type source superoptions`.
+pub fn parse_mountinfo(contents: &str) -> Vec<Mount> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(' ');
+            let point = fields.nth(4)?;
+            let fstype = fields.skip_while(|field| *field != "-").nth(1)?;
+            Some(Mount {
+                point: PathBuf::from(unescape_octal(point)),
+                fstype: fstype.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Read the mounts in the output of `mount` on macOS and the BSDs, whose lines are
+/// `source on point (type, options...)`.
+pub fn parse_mount_output(contents: &str) -> Vec<Mount> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(" on ")?;
+            let (point, options) = rest.rsplit_once(" (")?;
+            let fstype = options.split([',', ')']).next()?.trim();
+            Some(Mount {
+                point: PathBuf::from(point),
+                fstype: fstype.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Undo the `\NNN` octal escapes the kernel writes for spaces and other special characters.
+fn unescape_octal(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = bytes.get(i + 1..i + 4).filter(|_| bytes[i] == b'\\');
+        match escape
+            .and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok())
+        {
+            Some(byte) => {
+                out.push(byte);
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_mountinfo() {
+        let mounts = parse_mountinfo(
+            "22 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw\n\
+             40 22 0:35 / /net rw,relatime shared:20 - autofs systemd-1 rw,fd=47\n\
+             41 22 0:36 / /mnt/nas\\040share rw,relatime - nfs4 nas:/export rw,vers=4.2\n\
+             42 22 0:37 / /home/me/remote rw,nosuid - fuse.sshfs me@host: rw\n",
+        );
+        assert_eq!(mounts.len(), 4);
+        assert_eq!(mounts[2].point, PathBuf::from("/mnt/nas share"));
+        assert_eq!(mounts[2].fstype, "nfs4");
+        let network: Vec<_> = mounts.iter().map(Mount::is_network).collect();
+        assert_eq!(network, [false, true, true, true]);
+    }
+
+    #[test]
+    fn test_parse_mount_output() {
+        let mounts = parse_mount_output(
+            "/dev/disk3s1s1 on / (apfs, sealed, local, read-only, journaled)\n\
+             //me@nas/share on /Volumes/share (smbfs, nodev, nosuid, mounted by me)\n\
+             nas:/export on /Volumes/My Files (nfs)\n",
+        );
+        assert_eq!(mounts.len(), 3);
+        assert!(!mounts[0].is_network());
+        assert_eq!(mounts[1].fstype, "smbfs");
+        assert_eq!(mounts[2].point, PathBuf::from("/Volumes/My Files"));
+        assert!(mounts[2].is_network());
+    }
+
+    #[test]
+    fn test_responds() -> std::io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        assert!(responds(temp_dir.path(), TIMEOUT));
+        assert!(responds(&temp_dir.path().join("missing"), TIMEOUT));
+        Ok(())
+    }
+}
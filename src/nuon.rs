@@ -0,0 +1,90 @@
+//! Writing [Nushell object notation](https://www.nushell.sh/book/loading_data.html#nuon).
+use serde_json::Value;
+
+/// Render `value` as NUON.
+pub fn value(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => string(s),
+        Value::Array(items) => format!(
+            "[{}]",
+            items.iter().map(self::value).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Object(map) => format!(
+            "{{{}}}",
+            map.iter()
+                .map(|(k, v)| format!("{}: {}", string(k), self::value(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Render a table literal, `[[a, b]; [1, 2], [3, 4]]`, with one row per line.
+/// * `columns` - The column names, in order.
+/// * `rows` - The values of each row, in column order.
+pub fn table(columns: &[&str], rows: &[Vec<Value>]) -> String {
+    if rows.is_empty() {
+        return "[]".to_string();
+    }
+    let header = columns
+        .iter()
+        .map(|c| string(c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let rows = rows
+        .iter()
+        .map(|row| {
+            format!(
+                "  [{}]",
+                row.iter().map(value).collect::<Vec<_>>().join(", ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",\n");
+    format!("[[{}];\n{}\n]", header, rows)
+}
+
+/// Quote `s` as a double-quoted Nushell string.
+fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_table() {
+        let rows = vec![
+            vec![json!("/src/a \"b\""), json!(null)],
+            vec![json!("C:\\src"), json!({ "origin": "x" })],
+        ];
+        assert_eq!(
+            table(&["path", "remotes"], &rows),
+            r#"[["path", "remotes"];
+  ["/src/a \"b\"", null],
+  ["C:\\src", {"origin": "x"}]
+]"#
+        );
+        assert_eq!(table(&["path"], &[]), "[]");
+        assert_eq!(value(&json!("a\u{1}")), "\"a\\u{1}\"");
+    }
+}
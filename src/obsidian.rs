@@ -0,0 +1,214 @@
+//! Writing a directory of Markdown notes, one per repository, for an Obsidian vault or any
+//! other Markdown knowledge base.
+//!
+//! Each note starts with YAML frontmatter that Obsidian reads as properties and tags. Notes
+//! written by `lg` are marked with `generator: lg`, so that a later export can remove the notes
+//! of repositories that have gone while leaving notes written by hand alone.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::export::relative_repos;
+use crate::locale;
+use crate::paths;
+use crate::refs;
+use crate::status::Status;
+use crate::url::RemoteUrl;
+use crate::vcs::VcsKind;
+use crate::GitDirectory;
+
+/// The name of the note that links to every repository's note.
+pub const INDEX_NOTE: &str = "Repositories.md";
+
+/// The line that marks a note as written by `lg`.
+const MARKER: &str = "generator: lg";
+
+/// The frontmatter of a repository's note.
+#[derive(Serialize)]
+struct Frontmatter<'a> {
+    generator: &'static str,
+    path: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    remotes: BTreeMap<&'a str, &'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    branch: Option<String>,
+    /// `clean` or `dirty`, for Git working trees.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<&'static str>,
+    tags: Vec<String>,
+}
+
+/// Write a note for each repository in `dir` into `dest`, at its path relative to `dir`, and
+/// an [`INDEX_NOTE`] linking to them all. Notes from an earlier export whose repositories are
+/// no longer found are removed. Returns the number of repositories written.
+pub fn write_vault(dir: &GitDirectory, dest: &Path) -> Result<usize> {
+    let root = paths::canonicalize(&dir.path).unwrap_or_else(|_| dir.path.clone());
+    let mut written = BTreeSet::new();
+    let mut index = format!("---\n{}\ntags:\n- repos\n---\n# Repositories\n\n", MARKER);
+    let repos = relative_repos(dir);
+    for (relative, repo) in &repos {
+        let name = if relative == Path::new(".") {
+            root.file_name()
+                .map_or_else(|| "root".to_string(), |n| n.to_string_lossy().into_owned())
+        } else {
+            relative.to_string_lossy().replace('\\', "/")
+        };
+        let note = dest.join(format!("{}.md", name));
+        if let Some(parent) = note.parent() {
+            fs::create_dir_all(parent).with_context(|| locale::failed("create", &parent))?;
+        }
+        let path = root.join(relative);
+        fs::write(&note, note_for(&path, repo)?).with_context(|| locale::failed("write", &note))?;
+        written.insert(note);
+        let summary = repo
+            .primary_remote()
+            .and_then(|(_, url)| RemoteUrl::parse(url))
+            .map_or_else(String::new, |url| format!(" - {}", url));
+        index.push_str(&format!("- [[{}]]{}\n", name, summary));
+    }
+    let index_note = dest.join(INDEX_NOTE);
+    fs::write(&index_note, index).with_context(|| locale::failed("write", &index_note))?;
+    written.insert(index_note);
+    remove_stale(dest, &written)?;
+    Ok(repos.len())
+}
+
+/// The note for the repository `repo` at `path`.
+fn note_for(path: &Path, repo: &GitDirectory) -> Result<String> {
+    let name = path
+        .file_name()
+        .map_or_else(|| path.to_string_lossy(), |n| n.to_string_lossy());
+    let mut tags = vec!["repo".to_string()];
+    let urls: Vec<_> = repo
+        .remotes
+        .values()
+        .filter_map(|url| RemoteUrl::parse(url))
+        .collect();
+    let hosts: BTreeSet<_> = urls.iter().map(|url| tag(&url.host)).collect();
+    tags.extend(hosts.into_iter().map(|host| format!("host/{}", host)));
+    tags.extend(
+        repo.project_types
+            .iter()
+            .map(|kind| format!("lang/{}", kind.name())),
+    );
+    let git = repo.vcs == VcsKind::Git;
+    let frontmatter = Frontmatter {
+        generator: "lg",
+        path: path.to_string_lossy().into_owned(),
+        remotes: repo
+            .remotes
+            .iter()
+            .map(|(name, url)| (name.as_str(), url.as_str()))
+            .collect(),
+        branch: git
+            .then(|| refs::head(path).map(|head| head.name().to_string()))
+            .flatten(),
+        status: git
+            .then(|| Status::read(path).ok())
+            .flatten()
+            .map(|status| if status.is_dirty() { "dirty" } else { "clean" }),
+        tags,
+    };
+    let mut note = format!(
+        "---\n{}---\n# {}\n\n`{}`\n",
+        serde_yaml::to_string(&frontmatter)?,
+        name,
+        path.display()
+    );
+    let mut remotes: Vec<_> = repo.remotes.iter().collect();
+    remotes.sort();
+    if !remotes.is_empty() {
+        note.push_str("\n## Remotes\n\n");
+        for (name, url) in remotes {
+            match RemoteUrl::parse(url) {
+                Some(parsed) => {
+                    note.push_str(&format!("- {}: [{}]({})\n", name, url, parsed.web_url()))
+                }
+                None => note.push_str(&format!("- {}: `{}`\n", name, url)),
+            }
+        }
+    }
+    Ok(note)
+}
+
+/// `s` as an Obsidian tag, which may not contain `.` or spaces.
+fn tag(s: &str) -> String {
+    s.replace(['.', ' '], "-")
+}
+
+/// Remove the notes beneath `dest` that `lg` wrote before but not this time.
+fn remove_stale(dest: &Path, written: &BTreeSet<PathBuf>) -> Result<()> {
+    let Ok(entries) = fs::read_dir(dest) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            remove_stale(&path, written)?;
+        } else if path.extension().is_some_and(|extension| extension == "md")
+            && !written.contains(&path)
+            && fs::read_to_string(&path)
+                .is_ok_and(|contents| contents.starts_with(&format!("---\n{}\n", MARKER)))
+        {
+            fs::remove_file(&path).with_context(|| locale::failed("remove", &path))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    use crate::project::ProjectType;
+
+    #[test]
+    fn test_write_vault() -> Result<()> {
+        let src = TempDir::new()?;
+        let dest = TempDir::new()?;
+        let repo = |path: &str, url: &str| GitDirectory {
+            remotes: HashMap::from([("origin".to_string(), url.to_string())]),
+            vcs: VcsKind::Hg,
+            ..GitDirectory::directory(PathBuf::from(path))
+        };
+        let tree = GitDirectory {
+            children: vec![
+                GitDirectory {
+                    project_types: vec![ProjectType::Rust],
+                    ..repo("lg", "git@github.com:kthwaite/lg.git")
+                },
+                GitDirectory {
+                    children: vec![repo("api", "https://git.sr.ht/~me/api")],
+                    ..GitDirectory::directory(PathBuf::from("work"))
+                },
+            ],
+            ..GitDirectory::directory(src.path().to_path_buf())
+        };
+        // A note of a repository that has gone, and one written by hand.
+        fs::write(dest.path().join("old.md"), "---\ngenerator: lg\n---\n")?;
+        fs::write(dest.path().join("mine.md"), "# Mine\n")?;
+
+        assert_eq!(write_vault(&tree, dest.path())?, 2);
+        let root = paths::canonicalize(src.path())?;
+        let note = fs::read_to_string(dest.path().join("lg.md"))?;
+        assert!(note.starts_with("---\ngenerator: lg\n"));
+        assert!(note.contains(&format!("path: {}\n", root.join("lg").display())));
+        assert!(note.contains("origin: git@github.com:kthwaite/lg.git\n"));
+        assert!(note.contains("tags:\n- repo\n- host/github-com\n- lang/rust\n"));
+        assert!(note.contains(
+            "- origin: [git@github.com:kthwaite/lg.git](https://github.com/kthwaite/lg)\n"
+        ));
+        assert!(dest.path().join("work/api.md").is_file());
+        let index = fs::read_to_string(dest.path().join(INDEX_NOTE))?;
+        assert!(index
+            .ends_with("- [[lg]] - github.com/kthwaite/lg\n- [[work/api]] - git.sr.ht/~me/api\n"));
+        assert!(!dest.path().join("old.md").exists());
+        assert!(dest.path().join("mine.md").exists());
+        Ok(())
+    }
+}
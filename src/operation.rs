@@ -0,0 +1,82 @@
+//! Operations left in progress in a working tree, such as a merge with unresolved conflicts or
+//! a rebase that stopped, which Git records with files in the Git directory until they are
+//! finished or aborted.
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::refs;
+
+/// An operation in progress.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    /// `git merge`, or a pull that merges: `MERGE_HEAD`.
+    Merge,
+    /// `git rebase` or `git am`: `rebase-merge/` or `rebase-apply/`.
+    Rebase,
+    /// `git cherry-pick`: `CHERRY_PICK_HEAD`.
+    CherryPick,
+    /// `git revert`: `REVERT_HEAD`.
+    Revert,
+}
+
+impl Operation {
+    /// The operation's name as output shows it, e.g. `cherry_pick`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Operation::Merge => "merge",
+            Operation::Rebase => "rebase",
+            Operation::CherryPick => "cherry_pick",
+            Operation::Revert => "revert",
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The operation in progress in the Git working tree at `path`, if any. A rebase that stopped
+/// at a conflicting pick is reported as the rebase.
+pub fn read(path: &Path) -> Option<Operation> {
+    // Each worktree keeps its own, in its own Git directory.
+    let git_dir = refs::git_dir(path)?;
+    if git_dir.join("rebase-merge").is_dir() || git_dir.join("rebase-apply").is_dir() {
+        Some(Operation::Rebase)
+    } else if git_dir.join("MERGE_HEAD").is_file() {
+        Some(Operation::Merge)
+    } else if git_dir.join("CHERRY_PICK_HEAD").is_file() {
+        Some(Operation::CherryPick)
+    } else if git_dir.join("REVERT_HEAD").is_file() {
+        Some(Operation::Revert)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        fs::create_dir_all(repo.join(".git"))?;
+        assert_eq!(read(repo), None);
+
+        fs::write(repo.join(".git/CHERRY_PICK_HEAD"), "0\n")?;
+        assert_eq!(read(repo), Some(Operation::CherryPick));
+        fs::write(repo.join(".git/MERGE_HEAD"), "0\n")?;
+        assert_eq!(read(repo), Some(Operation::Merge));
+        fs::create_dir(repo.join(".git/rebase-merge"))?;
+        assert_eq!(read(repo), Some(Operation::Rebase));
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+//! Moving repositories into a canonical layout, as `lg organize` does: each repository goes to
+//! the path a template gives it from its primary remote, such as `{host}/{owner}/{repo}` for
+//! ghq's layout. Nothing inside a repository is changed. The moves made are recorded in a
+//! manifest, from which [`undo`] moves them back.
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::dirs::data_dir;
+use crate::locale;
+use crate::mirror;
+use crate::paths;
+use crate::GitDirectory;
+
+/// The layout of ghq, and `lg organize`'s default.
+pub const DEFAULT_LAYOUT: &str = "{host}/{owner}/{repo}";
+
+/// Where a repository goes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Planned {
+    /// Moved to this path.
+    Move(PathBuf),
+    /// Already where the layout puts it.
+    InPlace,
+    /// Left alone, the layout needing a part of the primary remote's URL it lacks, or giving
+    /// no path beneath the root.
+    NoUrl,
+    /// Left alone, its place being taken by this path, which exists or another repository is
+    /// moving to.
+    Collision(PathBuf),
+    /// Left alone, moving it breaking links Git keeps outside the working tree: it is a linked
+    /// worktree or submodule, or has linked worktrees.
+    Linked,
+}
+
+impl fmt::Display for Planned {
+    /// Where the repository goes, in the current locale.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Planned::Move(to) => locale::text("planned-move", &[("path", &to.display())]),
+            Planned::InPlace => locale::text("planned-in-place", &[]),
+            Planned::NoUrl => locale::text("planned-no-url", &[]),
+            Planned::Collision(path) => {
+                locale::text("planned-collision", &[("path", &path.display())])
+            }
+            Planned::Linked => locale::text("planned-linked", &[]),
+        };
+        f.write_str(&text)
+    }
+}
+
+/// A move made, as the manifest records it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Move {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Where each repository of `dir` goes under `root` by `layout`, in which `{host}`, `{owner}`,
+/// `{repo}`, `{path}` and `{name}` are as for [`mirror::url_for`]. Repositories are in the
+/// order they are to be moved: those nested in others first.
+/// * `dir` - A scan result whose root path is absolute.
+pub fn plan(dir: &GitDirectory, layout: &str, root: &Path) -> Vec<(PathBuf, Planned)> {
+    let mut repos = dir.repos();
+    // Deepest first, so a repository nested in another is moved before the one holding it.
+    repos.sort_by(|a, b| {
+        b.0.components()
+            .count()
+            .cmp(&a.0.components().count())
+            .then_with(|| a.0.cmp(&b.0))
+    });
+    let targets: Vec<_> = repos
+        .iter()
+        .map(|(path, repo)| {
+            let relative: PathBuf = mirror::url_for(layout, path, repo)?
+                .split('/')
+                .filter(|part| !matches!(*part, "" | "." | ".."))
+                .collect();
+            // The parts come from remote URLs, which may name anything, so they must stay
+            // beneath the root.
+            (!relative.as_os_str().is_empty() && paths::is_contained(&relative))
+                .then(|| root.join(relative))
+        })
+        .collect();
+    let mut claimed: HashMap<&Path, usize> = HashMap::new();
+    for (path, target) in repos.iter().map(|(path, _)| path).zip(&targets) {
+        if let Some(target) = target.as_deref().filter(|target| target != path) {
+            *claimed.entry(target).or_default() += 1;
+        }
+    }
+    repos
+        .iter()
+        .zip(&targets)
+        .map(|((path, _), target)| {
+            let planned = match target {
+                None => Planned::NoUrl,
+                Some(target) if target == path => Planned::InPlace,
+                Some(_) if is_linked(path) => Planned::Linked,
+                Some(target) if claimed[target.as_path()] > 1 || target.exists() => {
+                    Planned::Collision(target.clone())
+                }
+                Some(target) => Planned::Move(target.clone()),
+            };
+            (path.clone(), planned)
+        })
+        .collect()
+}
+
+/// Whether the working tree at `path` is linked to from elsewhere or links elsewhere: its
+/// `.git` is a file, as in linked worktrees and submodules, or it has linked worktrees.
+fn is_linked(path: &Path) -> bool {
+    let git = path.join(".git");
+    if git.is_file() {
+        return true;
+    }
+    fs::read_dir(git.join("worktrees")).is_ok_and(|mut entries| entries.next().is_some())
+}
+
+/// Make the moves of `planned`, recording each in the manifest at `manifest` as it is made.
+/// Calls `report` with each repository's path and whether its move succeeded, which it may not
+/// if its place was taken since it was planned. Directories the moves leave empty are removed,
+/// up to `stop`.
+pub fn apply(
+    planned: &[(PathBuf, Planned)],
+    manifest: &Path,
+    stop: &Path,
+    mut report: impl FnMut(&Path, &Path, Result<()>),
+) -> Result<()> {
+    let mut moves = Vec::new();
+    for (from, planned) in planned {
+        let Planned::Move(to) = planned else {
+            continue;
+        };
+        let result = move_repo(from, to, stop);
+        if result.is_ok() {
+            moves.push(Move {
+                from: from.clone(),
+                to: to.clone(),
+            });
+            save_manifest(manifest, &moves)?;
+        }
+        report(from, to, result);
+    }
+    Ok(())
+}
+
+/// Move the repositories recorded in the manifest at `manifest` back, newest first, calling
+/// `report` with each move undone and whether undoing it succeeded.
+pub fn undo(manifest: &Path, mut report: impl FnMut(&Move, Result<()>)) -> Result<()> {
+    let contents = fs::read(manifest).with_context(|| locale::failed("read", &manifest))?;
+    let moves: Vec<Move> =
+        serde_json::from_slice(&contents).with_context(|| locale::failed("parse", &manifest))?;
+    for m in moves.iter().rev() {
+        let stop = common_ancestor(&m.from, &m.to);
+        report(m, move_repo(&m.to, &m.from, &stop));
+    }
+    Ok(())
+}
+
+/// A path for the manifest of moves made now, in the data directory, as it is needed for as
+/// long as the moves may be undone. Named by the time in nanoseconds and the process ID, so
+/// runs at the same time have their own.
+pub fn new_manifest() -> Result<PathBuf> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    data_dir()
+        .map(|dir| {
+            dir.join("organize")
+                .join(format!("{}-{}.json", now, std::process::id()))
+        })
+        .ok_or_else(|| anyhow!(locale::text("no-data-dir", &[])))
+}
+
+/// Move the directory `from` to `to`, creating `to`'s parents, and remove the directories
+/// above `from` that this leaves empty, up to `stop`.
+fn move_repo(from: &Path, to: &Path, stop: &Path) -> Result<()> {
+    if to.exists() {
+        bail!(locale::text(
+            "already-exists",
+            &[("path", &format_args!("{:?}", to))]
+        ));
+    }
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).with_context(|| locale::failed("create", &parent))?;
+    }
+    fs::rename(from, to).with_context(|| {
+        locale::text(
+            "failed-move",
+            &[
+                ("from", &format_args!("{:?}", from)),
+                ("to", &format_args!("{:?}", to)),
+            ],
+        )
+    })?;
+    for dir in from.ancestors().skip(1) {
+        if dir == stop || !dir.starts_with(stop) || fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn common_ancestor(a: &Path, b: &Path) -> PathBuf {
+    a.components()
+        .zip(b.components())
+        .take_while(|(x, y)| x == y)
+        .map(|(x, _)| x)
+        .collect()
+}
+
+fn save_manifest(path: &Path, moves: &[Move]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| locale::failed("create", &dir))?;
+    }
+    fs::write(path, serde_json::to_vec_pretty(moves)?)
+        .with_context(|| locale::failed("write", &path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    use crate::testutil::create_repo;
+
+    #[test]
+    fn test_organize() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let src = crate::paths::canonicalize(temp_dir.path())?;
+        create_repo(&src.join("misc/lg"), "git@github.com:kthwaite/lg.git")?;
+        create_repo(
+            &src.join("github.com/me/site"),
+            "https://github.com/me/site",
+        )?;
+        create_repo(&src.join("a/tool"), "https://gitlab.com/me/tool")?;
+        create_repo(&src.join("b/tool"), "git@gitlab.com:me/tool.git")?;
+        create_repo(&src.join("old/dots"), "https://github.com/me/dots")?;
+        fs::create_dir_all(src.join("github.com/me/dots"))?;
+
+        let dir = crate::Scanner::builder()
+            .recurse(true)
+            .build()?
+            .scan(&src)?;
+        let planned: HashMap<_, _> = plan(&dir, DEFAULT_LAYOUT, &src).into_iter().collect();
+        assert_eq!(
+            planned[&src.join("misc/lg")],
+            Planned::Move(src.join("github.com/kthwaite/lg"))
+        );
+        assert_eq!(planned[&src.join("github.com/me/site")], Planned::InPlace);
+        assert_eq!(
+            planned[&src.join("a/tool")],
+            Planned::Collision(src.join("gitlab.com/me/tool"))
+        );
+        assert_eq!(
+            planned[&src.join("old/dots")],
+            Planned::Collision(src.join("github.com/me/dots"))
+        );
+
+        let manifest = src.join("manifest.json");
+        let planned: Vec<_> = planned.into_iter().collect();
+        let mut moved = 0;
+        apply(&planned, &manifest, &src, |_, _, result| {
+            result.unwrap();
+            moved += 1;
+        })?;
+        assert_eq!(moved, 1);
+        assert!(src.join("github.com/kthwaite/lg/.git").is_dir());
+        // The directory left empty is removed.
+        assert!(!src.join("misc").exists());
+
+        undo(&manifest, |_, result| result.unwrap())?;
+        assert!(src.join("misc/lg/.git").is_dir());
+        assert!(!src.join("github.com/kthwaite").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_contained() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let src = crate::paths::canonicalize(temp_dir.path())?;
+        let root = src.join("sorted");
+        create_repo(&src.join("escape"), "https://example.com/../../../etc/lg")?;
+        create_repo(&src.join("dots"), "https://example.com/./..")?;
+
+        let dir = crate::Scanner::builder()
+            .recurse(true)
+            .build()?
+            .scan(&src)?;
+        let planned: HashMap<_, _> = plan(&dir, "{path}", &root).into_iter().collect();
+        assert_eq!(
+            planned[&src.join("escape")],
+            Planned::Move(root.join("etc/lg"))
+        );
+        assert_eq!(planned[&src.join("dots")], Planned::NoUrl);
+        Ok(())
+    }
+}
@@ -0,0 +1,579 @@
+//! Writing scan results in the list output formats.
+use std::io::{self, Read, Write};
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::locale;
+use crate::model::ScanError;
+use crate::nuon;
+use crate::record::Column;
+use crate::status;
+use crate::time::TimeFormat;
+use crate::GitDirectory;
+
+/// The output format to use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Plain,
+    Yaml,
+    Json,
+    /// Nushell object notation: a table with one row per repository
+    Nuon,
+}
+
+/// The version of the JSON and YAML output, written as its `schema_version` field.
+///
+/// It is incremented whenever a field is removed, renamed or changes type. Adding a field does
+/// not change it, so consumers should ignore fields they do not know.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The JSON Schema describing the JSON and YAML output.
+pub const SCHEMA: &str = include_str!("../schema/output.schema.json");
+
+/// The top-level JSON and YAML document: the root directory tagged with the schema version.
+#[derive(Serialize, Deserialize)]
+struct Document<T> {
+    /// Absent in output from before versioning, which is read as version 0.
+    #[serde(default)]
+    schema_version: u32,
+    /// The scan was interrupted, so the tree holds only what was found before then.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    partial: bool,
+    #[serde(flatten)]
+    root: T,
+}
+
+/// Write `dir` in `format`.
+/// * `columns` - The columns of tabular formats; empty for [`Column::DEFAULT`].
+/// * `time_format` - How plain output shows times; JSON and YAML always use ISO 8601.
+/// * `compact` - Write JSON on a single line rather than pretty-printed.
+/// * `out` - Where to write.
+///
+/// Tabular formats join repository paths onto `dir.path`, so it should be absolute.
+pub fn write(
+    dir: &GitDirectory,
+    format: OutputFormat,
+    columns: &[Column],
+    time_format: TimeFormat,
+    compact: bool,
+    out: &mut impl Write,
+) -> Result<()> {
+    match format {
+        OutputFormat::Plain => write_plain(dir, 0, time_format, out)?,
+        OutputFormat::Yaml => writeln!(out, "{}", serde_yaml::to_string(&document(dir))?)?,
+        OutputFormat::Json if compact => {
+            writeln!(out, "{}", serde_json::to_string(&document(dir))?)?
+        }
+        OutputFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(&document(dir))?)?,
+        OutputFormat::Nuon => {
+            let columns = if columns.is_empty() {
+                Column::DEFAULT
+            } else {
+                columns
+            };
+            let names: Vec<_> = columns.iter().map(|c| c.name()).collect();
+            writeln!(
+                out,
+                "{}",
+                nuon::table(&names, &crate::record::rows(dir, columns))
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn document(root: &GitDirectory) -> Document<&GitDirectory> {
+    Document {
+        schema_version: SCHEMA_VERSION,
+        partial: root
+            .errors
+            .iter()
+            .any(|error| error.code == ScanError::INTERRUPTED),
+        root,
+    }
+}
+
+/// Read a tree previously written with `-f json` or `-f yaml`.
+///
+/// Fails if the output was written with a newer [`SCHEMA_VERSION`]; unknown fields are
+/// otherwise ignored.
+pub fn read(mut input: impl Read) -> Result<GitDirectory> {
+    let mut contents = String::new();
+    input
+        .read_to_string(&mut contents)
+        .with_context(|| locale::text("failed-read-scan", &[]))?;
+    // JSON is a subset of YAML, so one parser reads both.
+    let document: Document<GitDirectory> =
+        serde_yaml::from_str(&contents).with_context(|| locale::text("failed-parse-scan", &[]))?;
+    if document.schema_version > SCHEMA_VERSION {
+        bail!(locale::text(
+            "scan-schema-version",
+            &[
+                ("version", &document.schema_version),
+                ("supported", &SCHEMA_VERSION)
+            ]
+        ));
+    }
+    Ok(document.root)
+}
+
+/// Write the path of each repository under `dir`, joined onto `dir.path`, one per line.
+pub fn write_paths(dir: &GitDirectory, out: &mut impl Write) -> io::Result<()> {
+    for (path, _) in dir.repos() {
+        writeln!(out, "{}", path.display())?;
+    }
+    Ok(())
+}
+
+/// Write the given Git directory structure in plain text.
+/// * `dir` - The directory to print.
+/// * `indent` - The number of spaces to indent the output.
+/// * `time_format` - How to show times.
+/// * `out` - Where to write.
+pub fn write_plain(
+    dir: &GitDirectory,
+    indent: usize,
+    time_format: TimeFormat,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    write!(
+        out,
+        "{}{}: {}",
+        "  ".repeat(indent),
+        field("path"),
+        dir.path.display()
+    )?;
+    if !dir.project_types.is_empty() {
+        let names: Vec<_> = dir.project_types.iter().map(|kind| kind.name()).collect();
+        write!(out, " [{}]", names.join(", "))?;
+    }
+    writeln!(out)?;
+    if !dir.vcs.is_git() {
+        writeln!(
+            out,
+            "{}{}: {}",
+            "  ".repeat(indent + 1),
+            field("vcs"),
+            dir.vcs.name()
+        )?;
+    }
+    if let Some(git_dir) = &dir.git_dir {
+        writeln!(
+            out,
+            "{}{}: {}",
+            "  ".repeat(indent + 1),
+            field("git-dir"),
+            git_dir.display()
+        )?;
+    }
+    if let Some(annex) = &dir.annex {
+        writeln!(out, "{}{}:", "  ".repeat(indent + 1), field("annex"))?;
+        if let Some(uuid) = &annex.uuid {
+            writeln!(
+                out,
+                "{}  {}: {}",
+                "  ".repeat(indent + 1),
+                field("uuid"),
+                uuid
+            )?;
+        }
+        if !annex.remotes.is_empty() {
+            let remotes = annex.remotes.join(", ");
+            writeln!(
+                out,
+                "{}  {}: {}",
+                "  ".repeat(indent + 1),
+                field("remotes"),
+                remotes
+            )?;
+        }
+        if let Some(size) = annex.size {
+            let size = locale::plural("size-bytes", size as usize, &[]);
+            writeln!(
+                out,
+                "{}  {}: {}",
+                "  ".repeat(indent + 1),
+                field("size"),
+                size
+            )?;
+        }
+    }
+    if let Some(sparse) = &dir.sparse_checkout {
+        let mode = field(if sparse.cone { "cone" } else { "patterns" });
+        let label = field("sparse-checkout");
+        writeln!(out, "{}{}: {}", "  ".repeat(indent + 1), label, mode)?;
+        if !sparse.patterns.is_empty() {
+            let patterns = sparse.patterns.join(", ");
+            writeln!(
+                out,
+                "{}  {}: {}",
+                "  ".repeat(indent + 1),
+                field("patterns"),
+                patterns
+            )?;
+        }
+    }
+    if !dir.ci.is_empty() {
+        let names: Vec<_> = dir.ci.iter().map(|system| system.name()).collect();
+        writeln!(
+            out,
+            "{}{}: {}",
+            "  ".repeat(indent + 1),
+            field("ci"),
+            names.join(", ")
+        )?;
+    }
+    if let Some(license) = &dir.license {
+        let spdx = license.spdx.clone().unwrap_or_else(|| field("unknown"));
+        writeln!(
+            out,
+            "{}{}: {} ({})",
+            "  ".repeat(indent + 1),
+            field("license"),
+            spdx,
+            license.file
+        )?;
+    }
+    if let Some(push) = &dir.push {
+        writeln!(out, "{}{}:", "  ".repeat(indent + 1), field("push"))?;
+        if let Some(remote) = &push.default_remote {
+            writeln!(
+                out,
+                "{}  {}: {}",
+                "  ".repeat(indent + 1),
+                field("default"),
+                remote
+            )?;
+        }
+        for (branch, target) in &push.branches {
+            match &target.branch {
+                Some(to) => writeln!(
+                    out,
+                    "{}  {}: {}/{}",
+                    "  ".repeat(indent + 1),
+                    branch,
+                    target.remote,
+                    to
+                )?,
+                None => writeln!(
+                    out,
+                    "{}  {}: {}",
+                    "  ".repeat(indent + 1),
+                    branch,
+                    locale::text("push-nothing", &[("remote", &target.remote)])
+                )?,
+            }
+        }
+    }
+    if !dir.tracking.is_empty() {
+        writeln!(out, "{}{}:", "  ".repeat(indent + 1), field("tracking"))?;
+        for (branch, tracking) in &dir.tracking {
+            let merge = tracking.merge.trim_start_matches("refs/heads/");
+            let divergence = status::divergence(tracking.ahead, tracking.behind);
+            let track = match (tracking.gone, divergence.is_empty()) {
+                (true, _) => format!(" [{}]", field("gone")),
+                (false, true) => String::new(),
+                (false, false) => format!(" [{}]", divergence.join(", ")),
+            };
+            writeln!(
+                out,
+                "{}  {}: {}/{}{}",
+                "  ".repeat(indent + 1),
+                branch,
+                tracking.remote,
+                merge,
+                track
+            )?;
+        }
+    }
+    if let Some(last_fetch) = dir.last_fetch {
+        writeln!(
+            out,
+            "{}{}: {}",
+            "  ".repeat(indent + 1),
+            field("last-fetch"),
+            time_format.format(last_fetch)
+        )?;
+    }
+    if !dir.remote_checks.is_empty() {
+        writeln!(
+            out,
+            "{}{}:",
+            "  ".repeat(indent + 1),
+            field("remote-checks")
+        )?;
+        for (remote, check) in &dir.remote_checks {
+            match &check.error {
+                Some(error) => writeln!(out, "{}  {}: {}", "  ".repeat(indent + 1), remote, error)?,
+                None => writeln!(
+                    out,
+                    "{}  {}: {}",
+                    "  ".repeat(indent + 1),
+                    remote,
+                    field("ok")
+                )?,
+            }
+        }
+    }
+    if let Some(first_commit) = dir.first_commit {
+        writeln!(
+            out,
+            "{}{}: {}",
+            "  ".repeat(indent + 1),
+            field("first-commit"),
+            time_format.format(first_commit)
+        )?;
+    }
+    if let Some(operation) = dir.in_progress {
+        let label = field("in-progress");
+        writeln!(out, "{}{}: {}", "  ".repeat(indent + 1), label, operation)?;
+    }
+    if dir.partial_clone {
+        // The filter says more than `true` does, when the clone recorded one.
+        let filter = dir.partial_clone_filter.as_deref().unwrap_or("true");
+        let label = field("partial-clone");
+        writeln!(out, "{}{}: {}", "  ".repeat(indent + 1), label, filter)?;
+    }
+    if dir.remotes.len() > 1 {
+        // Which of several remotes identifies the repository is otherwise left to guesswork.
+        if let Some((primary, _)) = dir.primary_remote() {
+            writeln!(
+                out,
+                "{}{}: {}",
+                "  ".repeat(indent + 1),
+                field("primary-remote"),
+                primary
+            )?;
+        }
+    }
+    if !dir.remotes.is_empty() {
+        writeln!(out, "{}{}:", "  ".repeat(indent + 1), field("remotes"))?;
+        for (name, url) in &dir.remotes {
+            writeln!(out, "{}  {}: {}", "  ".repeat(indent + 1), name, url)?;
+            // The remote's other URLs, if any, beneath it.
+            if let Some(urls) = dir.urls.get(name) {
+                for url in urls.fetch.iter().skip(1) {
+                    writeln!(
+                        out,
+                        "{}    {}: {}",
+                        "  ".repeat(indent + 1),
+                        field("fetch"),
+                        url
+                    )?;
+                }
+                for url in &urls.push {
+                    writeln!(
+                        out,
+                        "{}    {}: {}",
+                        "  ".repeat(indent + 1),
+                        field("push"),
+                        url
+                    )?;
+                }
+            }
+        }
+    }
+    if !dir.children.is_empty() {
+        writeln!(out, "{}{}:", "  ".repeat(indent), field("children"))?;
+        for child in &dir.children {
+            write_plain(child, indent + 1, time_format, out)?;
+        }
+    }
+    Ok(())
+}
+
+/// The name of the field `id` of the plain listing, in the current locale.
+fn field(id: &str) -> String {
+    locale::text(&format!("field-{}", id), &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::PathBuf;
+
+    use crate::annex::Annex;
+    use crate::ci::CiSystem;
+    use crate::license::License;
+    use crate::model::{RemoteUrls, ScanError};
+    use crate::project::ProjectType;
+    use crate::push::{Push, PushTarget};
+    use crate::sparse::SparseCheckout;
+    use crate::status::Tracking;
+
+    #[test]
+    fn test_json_matches_schema_version() -> Result<()> {
+        let schema: serde_json::Value = serde_json::from_str(SCHEMA)?;
+        assert_eq!(
+            schema["properties"]["schema_version"]["const"],
+            SCHEMA_VERSION
+        );
+
+        let dir = GitDirectory {
+            ..GitDirectory::directory(PathBuf::from("/src"))
+        };
+        let mut out = Vec::new();
+        write(
+            &dir,
+            OutputFormat::Json,
+            &[],
+            TimeFormat::Iso,
+            false,
+            &mut out,
+        )?;
+        let json: serde_json::Value = serde_json::from_slice(&out)?;
+        assert_eq!(
+            json,
+            serde_json::json!({ "schema_version": SCHEMA_VERSION, "path": "/src" })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let tree = GitDirectory {
+            children: vec![GitDirectory {
+                remotes: HashMap::from([
+                    (
+                        "origin".to_string(),
+                        "git@github.com:kthwaite/lg.git".to_string(),
+                    ),
+                    ("fork".to_string(), "https://gitlab.com/me/lg".to_string()),
+                ]),
+                primary_remote: Some("fork".to_string()),
+                urls: BTreeMap::from([(
+                    "fork".to_string(),
+                    RemoteUrls {
+                        fetch: vec!["https://gitlab.com/me/lg".to_string()],
+                        push: vec!["git@gitlab.com:me/lg.git".to_string()],
+                    },
+                )]),
+                annex: Some(Annex {
+                    uuid: Some("5c4a7d3e-0000-4000-8000-000000000001".to_string()),
+                    remotes: vec!["origin".to_string()],
+                    size: Some(1024),
+                }),
+                sparse_checkout: Some(SparseCheckout {
+                    cone: true,
+                    patterns: vec!["src".to_string(), "docs".to_string()],
+                }),
+                partial_clone: true,
+                partial_clone_filter: Some("blob:none".to_string()),
+                project_types: vec![ProjectType::Rust, ProjectType::Python],
+                ci: vec![CiSystem::Github, CiSystem::Jenkins],
+                license: Some(License {
+                    file: "LICENSE".to_string(),
+                    spdx: Some("MIT".to_string()),
+                }),
+                push: Some(Push {
+                    default_remote: Some("fork".to_string()),
+                    branches: BTreeMap::from([
+                        (
+                            "main".to_string(),
+                            PushTarget {
+                                remote: "fork".to_string(),
+                                branch: Some("main".to_string()),
+                            },
+                        ),
+                        (
+                            "wip".to_string(),
+                            PushTarget {
+                                remote: "origin".to_string(),
+                                branch: None,
+                            },
+                        ),
+                    ]),
+                }),
+                tracking: BTreeMap::from([(
+                    "main".to_string(),
+                    Tracking {
+                        remote: "origin".to_string(),
+                        merge: "refs/heads/main".to_string(),
+                        ahead: 2,
+                        behind: 1,
+                        gone: false,
+                    },
+                )]),
+                last_fetch: Some(1_717_234_200),
+                first_commit: Some(1_514_764_800),
+                head: Some("main".to_string()),
+                plugins: BTreeMap::from([("size".to_string(), serde_json::json!({ "files": 3 }))]),
+                ..GitDirectory::directory(PathBuf::from("lg"))
+            }],
+            errors: vec![ScanError {
+                path: PathBuf::from("/src/private"),
+                code: "permission_denied".to_string(),
+                message: "Permission denied".to_string(),
+            }],
+            ..GitDirectory::directory(PathBuf::from("/src"))
+        };
+        for format in [OutputFormat::Json, OutputFormat::Yaml] {
+            let mut out = Vec::new();
+            write(&tree, format, &[], TimeFormat::Iso, false, &mut out)?;
+            assert_eq!(read(out.as_slice())?, tree);
+        }
+        let mut out = Vec::new();
+        write_plain(&tree, 0, TimeFormat::Iso, &mut out)?;
+        let plain = String::from_utf8(out)?;
+        assert!(plain
+            .contains("fork: https://gitlab.com/me/lg\n        push: git@gitlab.com:me/lg.git\n"));
+        assert!(plain.contains("    annex:\n      uuid: 5c4a7d3e"));
+        assert!(plain.contains("    primary_remote: fork\n"));
+        assert!(plain.contains("    partial_clone: blob:none\n"));
+        assert!(plain.contains("  path: lg [rust, python]\n"));
+        assert!(plain.contains("    ci: github, jenkins\n"));
+        assert!(plain.contains("    license: MIT (LICENSE)\n"));
+        assert!(plain.contains("    last_fetch: 2024-06-01T09:30:00Z\n"));
+        assert!(plain.contains("    first_commit: 2018-01-01T00:00:00Z\n"));
+        assert!(plain.contains("    tracking:\n      main: origin/main [ahead 2, behind 1]\n"));
+        assert!(plain.contains(
+            "    push:\n      default: fork\n      main: fork/main\n      wip: origin (nothing)\n"
+        ));
+        assert!(plain.contains("    sparse_checkout: cone\n      patterns: src, docs\n"));
+
+        assert_eq!(
+            read(r#"{"path": "/src", "extra": 1}"#.as_bytes())?.path,
+            tree.path
+        );
+        assert!(read(r#"{"schema_version": 999, "path": "/src"}"#.as_bytes()).is_err());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_paths() -> Result<()> {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let tree = GitDirectory {
+            children: vec![GitDirectory {
+                remotes: HashMap::from([("origin".to_string(), "/srv/cafe".to_string())]),
+                ..GitDirectory::directory(PathBuf::from(OsStr::from_bytes(b"caf\xe9")))
+            }],
+            ..GitDirectory::directory(PathBuf::from("/src"))
+        };
+        for format in [OutputFormat::Json, OutputFormat::Yaml] {
+            let mut out = Vec::new();
+            write(&tree, format, &[], TimeFormat::Iso, false, &mut out)?;
+            assert_eq!(read(out.as_slice())?, tree);
+        }
+        let mut out = Vec::new();
+        write(
+            &tree,
+            OutputFormat::Json,
+            &[],
+            TimeFormat::Iso,
+            false,
+            &mut out,
+        )?;
+        let json: serde_json::Value = serde_json::from_slice(&out)?;
+        assert_eq!(json["children"][0]["path"], "caf\u{fffd}");
+        assert_eq!(json["children"][0]["path_base64"], "Y2Fm6Q==");
+        assert!(json.get("path_base64").is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+//! Platform-specific path handling: Windows long and UNC paths, filesystem identity, and
+//! writing paths that are not valid UTF-8.
+//!
+//! Windows limits ordinary paths to 260 characters, which deep trees such as `node_modules`
+//! exceed. Scans therefore walk the verbatim (`\\?\`) form of the root, and paths are turned
+//! back into the ordinary form, with `\` separators, before they are shown.
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// An identifier that is equal for two paths on the same filesystem; see [`filesystem`].
+#[cfg(unix)]
+pub type FilesystemId = u64;
+/// An identifier that is equal for two paths on the same filesystem; see [`filesystem`].
+#[cfg(windows)]
+pub type FilesystemId = PathBuf;
+/// An identifier that is equal for two paths on the same filesystem; see [`filesystem`].
+#[cfg(not(any(unix, windows)))]
+pub type FilesystemId = ();
+
+/// The filesystem `path` is on, following symlinks.
+///
+/// On Unix this is the device number; on Windows, the drive or UNC share, so that junctions to
+/// another drive count as crossing filesystems.
+#[cfg(unix)]
+pub fn filesystem(path: &Path) -> io::Result<FilesystemId> {
+    use std::os::unix::fs::MetadataExt;
+    Ok(fs::metadata(path)?.dev())
+}
+
+/// The filesystem `path` is on, following symlinks.
+///
+/// On Unix this is the device number; on Windows, the drive or UNC share, so that junctions to
+/// another drive count as crossing filesystems.
+#[cfg(windows)]
+pub fn filesystem(path: &Path) -> io::Result<FilesystemId> {
+    use std::path::Component;
+    match fs::canonicalize(path)?.components().next() {
+        Some(Component::Prefix(prefix)) => Ok(PathBuf::from(prefix.as_os_str())),
+        _ => Ok(PathBuf::new()),
+    }
+}
+
+/// The filesystem `path` is on; every path is on the same one on this platform.
+#[cfg(not(any(unix, windows)))]
+pub fn filesystem(path: &Path) -> io::Result<FilesystemId> {
+    fs::metadata(path).map(|_| ())
+}
+
+/// An identifier that is equal for two paths to the same file; see [`file_id`].
+#[cfg(unix)]
+pub type FileId = (u64, u64);
+/// An identifier that is equal for two paths to the same file; see [`file_id`].
+#[cfg(not(unix))]
+pub type FileId = PathBuf;
+
+/// The file `path` leads to, following symlinks, so that paths reaching one directory through
+/// a bind mount, a firmlink or a link are recognised as the same.
+///
+/// On Unix this is the device and inode numbers; elsewhere, the canonical path.
+#[cfg(unix)]
+pub fn file_id(path: &Path) -> io::Result<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.dev(), metadata.ino()))
+}
+
+/// The file `path` leads to, following symlinks, so that paths reaching one directory through
+/// a bind mount, a firmlink or a link are recognised as the same.
+///
+/// On Unix this is the device and inode numbers; elsewhere, the canonical path.
+#[cfg(not(unix))]
+pub fn file_id(path: &Path) -> io::Result<FileId> {
+    fs::canonicalize(path)
+}
+
+/// `path` as an absolute path with symlinks resolved, like [`fs::canonicalize`], but in the
+/// ordinary form on Windows rather than the `\\?\` form.
+pub fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    fs::canonicalize(path).map(|path| simplify(&path))
+}
+
+/// The form of `path` to read the filesystem through: on Windows, the verbatim form of its
+/// absolute path, which is not limited in length. Other platforms use `path` as it is.
+pub fn verbatim(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    if let Some(verbatim) = std::path::absolute(path)
+        .ok()
+        .and_then(|path| path.to_str().and_then(windows_verbatim))
+    {
+        return PathBuf::from(verbatim);
+    }
+    path.to_path_buf()
+}
+
+/// The form of `path` to show: on Windows, without a verbatim prefix and with `\` as the
+/// only separator. Other platforms use `path` as it is.
+pub fn simplify(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    if let Some(path) = path.to_str() {
+        return PathBuf::from(windows_simplify(path));
+    }
+    path.to_path_buf()
+}
+
+/// Whether `path` stays beneath the directory it is joined to: it is relative, and has no `..`
+/// and no root or drive. Paths read from files `lg` did not write are checked with this before
+/// anything is written to them.
+pub fn is_contained(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+/// The bytes of `path` as the OS stores them: exactly so on Unix, and as WTF-8 on Windows.
+pub fn to_bytes(path: &Path) -> &[u8] {
+    path.as_os_str().as_encoded_bytes()
+}
+
+/// The path whose [`to_bytes`] are `bytes`; `None` if they do not form a path here.
+///
+/// Off Unix, only valid UTF-8 is accepted.
+pub fn from_bytes(bytes: Vec<u8>) -> Option<PathBuf> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        Some(PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+    }
+    #[cfg(not(unix))]
+    {
+        String::from_utf8(bytes).ok().map(PathBuf::from)
+    }
+}
+
+/// Serde support for a flattened path field that survives paths that are not valid UTF-8.
+///
+/// The path is written as `path`, a string with any invalid UTF-8 replaced by U+FFFD, and, only
+/// when that loses information, `path_base64`, the base64 of [`to_bytes`]. Reading prefers
+/// `path_base64` when it is present. Use as `#[serde(flatten, with = "paths::encoded")]`.
+pub mod encoded {
+    use std::borrow::Cow;
+    use std::path::{Path, PathBuf};
+
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    struct Encoded<'a> {
+        path: Cow<'a, str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path_base64: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct Decoded {
+        path: PathBuf,
+        #[serde(default)]
+        path_base64: Option<String>,
+    }
+
+    /// Write `path` as `path` and, if needed, `path_base64`.
+    pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+        let lossy = path.to_string_lossy();
+        let path_base64 = match lossy {
+            Cow::Borrowed(_) => None,
+            Cow::Owned(_) => Some(STANDARD.encode(super::to_bytes(path))),
+        };
+        Encoded {
+            path: lossy,
+            path_base64,
+        }
+        .serialize(serializer)
+    }
+
+    /// Read a path written by [`serialize`].
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+        let decoded = Decoded::deserialize(deserializer)?;
+        let Some(encoded) = decoded.path_base64 else {
+            return Ok(decoded.path);
+        };
+        let bytes = STANDARD.decode(encoded).map_err(D::Error::custom)?;
+        Ok(super::from_bytes(bytes).unwrap_or(decoded.path))
+    }
+}
+
+/// Serde support for an optional path field that is only informational, written as a string
+/// with any invalid UTF-8 replaced by U+FFFD. Use as
+/// `#[serde(default, skip_serializing_if = "Option::is_none", with = "paths::lossy")]`.
+pub mod lossy {
+    use std::path::PathBuf;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Write `path` as a string.
+    pub fn serialize<S: Serializer>(
+        path: &Option<PathBuf>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        path.as_ref()
+            .map(|path| path.to_string_lossy())
+            .serialize(serializer)
+    }
+
+    /// Read a path written by [`serialize`].
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<PathBuf>, D::Error> {
+        Option::<PathBuf>::deserialize(deserializer)
+    }
+}
+
+/// The verbatim form of an absolute Windows path: `C:\src` becomes `\\?\C:\src` and
+/// `\\server\share` becomes `\\?\UNC\server\share`. `None` for relative or verbatim paths.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn windows_verbatim(path: &str) -> Option<String> {
+    if path.starts_with(r"\\?\") {
+        return None;
+    }
+    let path = path.replace('/', r"\");
+    if let Some(unc) = path.strip_prefix(r"\\") {
+        return Some(format!(r"\\?\UNC\{}", unc));
+    }
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && &bytes[1..3] == br":\" {
+        return Some(format!(r"\\?\{}", path));
+    }
+    None
+}
+
+/// The ordinary form of a Windows path: the inverse of [`windows_verbatim`], also replacing
+/// `/` with `\`.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn windows_simplify(path: &str) -> String {
+    let path = match path.strip_prefix(r"\\?\UNC\") {
+        Some(unc) => format!(r"\\{}", unc),
+        None => path.strip_prefix(r"\\?\").unwrap_or(path).to_string(),
+    };
+    path.replace('/', r"\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_forms() {
+        assert_eq!(
+            windows_verbatim(r"C:\Users\me/source").as_deref(),
+            Some(r"\\?\C:\Users\me\source")
+        );
+        assert_eq!(
+            windows_verbatim(r"\\server\share\src").as_deref(),
+            Some(r"\\?\UNC\server\share\src")
+        );
+        assert_eq!(windows_verbatim(r"\\?\C:\src"), None);
+        assert_eq!(windows_verbatim(r"src\lg"), None);
+
+        assert_eq!(windows_simplify(r"\\?\C:\src/lg"), r"C:\src\lg");
+        assert_eq!(
+            windows_simplify(r"\\?\UNC\server\share\src"),
+            r"\\server\share\src"
+        );
+        assert_eq!(windows_simplify(r"C:\src"), r"C:\src");
+    }
+
+    #[test]
+    fn test_is_contained() {
+        assert!(is_contained(Path::new("src/lg")));
+        assert!(is_contained(Path::new("./src/lg")));
+        assert!(!is_contained(Path::new("../lg")));
+        assert!(!is_contained(Path::new("src/../../lg")));
+        assert!(!is_contained(Path::new("/etc/lg")));
+    }
+
+    #[test]
+    fn test_filesystem() -> io::Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        std::fs::create_dir(temp_dir.path().join("sub"))?;
+        assert_eq!(
+            filesystem(temp_dir.path())?,
+            filesystem(&temp_dir.path().join("sub"))?
+        );
+        assert!(filesystem(&temp_dir.path().join("missing")).is_err());
+        Ok(())
+    }
+}
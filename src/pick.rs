@@ -0,0 +1,828 @@
+//! The interactive picker behind `lg pick`: browse and filter the indexed repositories, then
+//! choose one or mark several and apply a bulk action to them.
+//!
+//! The picker draws on standard error, like fzf, so that `cd "$(lg pick)"` works. Keys can
+//! be rebound, and bound to commands, in the `[keys]` table of the configuration file; see
+//! [`Keymap`].
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{self, Stderr};
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::{Frame, Terminal};
+
+use crate::bulk::Action;
+use crate::fuzzy;
+use crate::index::IndexEntry;
+use crate::locale;
+use crate::preview::Preview;
+use crate::shell;
+use crate::term::{Capabilities, Stream};
+
+/// The narrowest terminal, in columns, that the preview is shown beside the list in.
+const MIN_PREVIEW_WIDTH: u16 = 80;
+
+/// The keys bound when the configuration binds none, and the names they are bound to.
+const DEFAULT_KEYS: &[(&str, &str)] = &[
+    ("k", "up"),
+    ("up", "up"),
+    ("j", "down"),
+    ("down", "down"),
+    ("pageup", "page-up"),
+    ("pagedown", "page-down"),
+    ("g", "top"),
+    ("home", "top"),
+    ("G", "bottom"),
+    ("end", "bottom"),
+    ("space", "mark"),
+    ("/", "filter"),
+    ("*", "mark-all"),
+    ("u", "unmark-all"),
+    ("f", "fetch"),
+    ("p", "pull"),
+    ("a", "archive"),
+    ("D", "delete"),
+    ("v", "preview"),
+    ("r", "refresh"),
+    ("enter", "choose"),
+    ("q", "quit"),
+    ("esc", "quit"),
+];
+
+/// The bindings listed in the status bar, in order, with the messages describing them there.
+const HELP: &[(Binding, &str)] = &[
+    (Binding::Mark, "pick-key-mark"),
+    (Binding::Filter, "pick-key-filter"),
+    (Binding::Apply(Action::Fetch), "pick-key-fetch"),
+    (Binding::Apply(Action::Pull), "pick-key-pull"),
+    (Binding::Apply(Action::Archive), "pick-key-archive"),
+    (Binding::Apply(Action::Delete), "pick-key-delete"),
+    (Binding::Preview, "pick-key-preview"),
+    (Binding::Choose, "pick-key-choose"),
+    (Binding::Quit, "pick-key-quit"),
+];
+
+/// What a key does when browsing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Binding {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    /// Start editing the filter.
+    Filter,
+    /// Mark or unmark the highlighted repository and move down.
+    Mark,
+    /// Mark every match, or unmark them if they are all marked.
+    MarkAll,
+    UnmarkAll,
+    /// Show or hide the preview.
+    Preview,
+    /// Read the highlighted repository's preview again.
+    Refresh,
+    /// Print the highlighted repository's path and exit.
+    Choose,
+    Quit,
+    /// Ask to apply the action to [`Picker::targets`].
+    Apply(Action),
+    /// Run the command template in each of [`Picker::targets`], then return to the picker.
+    Run(String),
+}
+
+impl Binding {
+    /// The binding called `name` in the configuration, or a [`Binding::Run`] of `name` if no
+    /// built-in one is.
+    pub fn parse(name: &str) -> Binding {
+        match name {
+            "up" => Binding::Up,
+            "down" => Binding::Down,
+            "page-up" => Binding::PageUp,
+            "page-down" => Binding::PageDown,
+            "top" => Binding::Top,
+            "bottom" => Binding::Bottom,
+            "filter" => Binding::Filter,
+            "mark" => Binding::Mark,
+            "mark-all" => Binding::MarkAll,
+            "unmark-all" => Binding::UnmarkAll,
+            "preview" => Binding::Preview,
+            "refresh" => Binding::Refresh,
+            "choose" => Binding::Choose,
+            "quit" => Binding::Quit,
+            "fetch" => Binding::Apply(Action::Fetch),
+            "pull" => Binding::Apply(Action::Pull),
+            "archive" => Binding::Apply(Action::Archive),
+            "delete" => Binding::Apply(Action::Delete),
+            template => Binding::Run(template.to_string()),
+        }
+    }
+}
+
+/// A key press, ignoring Shift, which is already reflected in the character typed.
+type Key = (KeyCode, KeyModifiers);
+
+fn key_of(event: KeyEvent) -> Key {
+    (event.code, event.modifiers - KeyModifiers::SHIFT)
+}
+
+/// Parse a key as written in the configuration: a single character such as `o` or `D`, a name
+/// such as `enter`, `space`, `pagedown` or `f5`, or either of those after `ctrl-`.
+fn parse_key(s: &str) -> Result<Key> {
+    let (modifiers, name) = match s.strip_prefix("ctrl-") {
+        Some(name) => (KeyModifiers::CONTROL, name),
+        None => (KeyModifiers::NONE, s),
+    };
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match name {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            _ => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => bail!(locale::text(
+                    "unknown-key",
+                    &[("key", &format_args!("{:?}", s))]
+                )),
+            },
+        },
+    };
+    Ok((code, modifiers))
+}
+
+/// How a key is shown in the status bar.
+fn key_name((code, modifiers): Key) -> String {
+    let name = match code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("f{}", n),
+        code => code.to_string().to_lowercase().replace(' ', ""),
+    };
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        format!("ctrl-{}", name)
+    } else {
+        name
+    }
+}
+
+/// The keys bound when browsing, in the order they were bound.
+///
+/// The `[keys]` table of the configuration file adds to and overrides the defaults. Each entry
+/// maps a key to the name of a built-in binding (see [`Binding::parse`]), to `"none"` to unbind
+/// it, or to a command template run with the platform shell in the highlighted repository, or
+/// in each marked one, with `{path}` replaced by its quoted path:
+///
+/// ```toml
+/// [keys]
+/// o = "code {path}"
+/// t = "gitui -d {path}"
+/// x = "delete"
+/// D = "none"
+/// ```
+#[derive(Clone, Debug)]
+pub struct Keymap(Vec<(Key, Binding)>);
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap(
+            DEFAULT_KEYS
+                .iter()
+                .map(|(key, name)| (parse_key(key).unwrap(), Binding::parse(name)))
+                .collect(),
+        )
+    }
+}
+
+impl Keymap {
+    /// The default keymap with the bindings in `keys`, from the `[keys]` table, applied.
+    pub fn new(keys: &BTreeMap<String, String>) -> Result<Self> {
+        let mut keymap = Keymap::default();
+        for (key, name) in keys {
+            let key = parse_key(key)
+                .with_context(|| locale::text("in-config-section", &[("section", &"keys")]))?;
+            keymap.0.retain(|(bound, _)| *bound != key);
+            if name != "none" {
+                keymap.0.push((key, Binding::parse(name)));
+            }
+        }
+        Ok(keymap)
+    }
+
+    fn get(&self, key: Key) -> Option<&Binding> {
+        self.0
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, binding)| binding)
+    }
+
+    /// The status bar's list of keys: the first key for each of [`HELP`], then every command.
+    fn help(&self) -> String {
+        let builtin = HELP.iter().filter_map(|(binding, label)| {
+            let (key, _) = self.0.iter().find(|(_, bound)| bound == binding)?;
+            Some(format!("{} {}", key_name(*key), locale::text(label, &[])))
+        });
+        let commands = self.0.iter().filter_map(|(key, binding)| match binding {
+            Binding::Run(template) => Some(format!("{} {}", key_name(*key), template)),
+            _ => None,
+        });
+        builtin.chain(commands).collect::<Vec<_>>().join("  ")
+    }
+}
+
+/// What the user asked for when the picker closed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// Print this repository's path.
+    Choose(PathBuf),
+    /// Apply the action to these repositories; the user has confirmed it.
+    Apply(Action, Vec<PathBuf>),
+}
+
+/// Whether the picker should keep running after a key press.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Step {
+    Continue,
+    Quit,
+    Done(Outcome),
+    /// Leave the terminal to these commands, one after another, then carry on.
+    Run(Vec<String>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    /// Keys move, mark and pick actions.
+    Browse,
+    /// Keys edit the filter.
+    Filter,
+    /// Waiting for the user to confirm the action on [`Picker::targets`].
+    Confirm(Action),
+}
+
+/// The state of the picker, independent of the terminal.
+pub struct Picker {
+    entries: Vec<IndexEntry>,
+    query: String,
+    /// Indices into `entries` of those matching `query`, best first.
+    matches: Vec<usize>,
+    /// The highlighted position in `matches`.
+    cursor: usize,
+    /// Indices into `entries` of the marked repositories.
+    marked: BTreeSet<usize>,
+    mode: Mode,
+    /// Whether to show the preview of the highlighted repository beside the list.
+    show_preview: bool,
+    /// Previews read so far, by index into `entries`.
+    previews: HashMap<usize, Preview>,
+    keymap: Keymap,
+    /// Shown in the status bar until the next key press, e.g. that a command failed.
+    notice: Option<String>,
+}
+
+impl Picker {
+    /// A picker over `entries`, initially filtered by `query`.
+    pub fn new(entries: Vec<IndexEntry>, query: &str, keymap: Keymap) -> Self {
+        let mut picker = Picker {
+            entries,
+            query: query.to_string(),
+            matches: Vec::new(),
+            cursor: 0,
+            marked: BTreeSet::new(),
+            mode: Mode::Browse,
+            show_preview: true,
+            previews: HashMap::new(),
+            keymap,
+            notice: None,
+        };
+        picker.refilter();
+        picker
+    }
+
+    fn refilter(&mut self) {
+        self.matches = if self.query.trim().is_empty() {
+            (0..self.entries.len()).collect()
+        } else {
+            fuzzy::rank_indices(std::slice::from_ref(&self.query), &self.entries)
+                .into_iter()
+                .map(|(_, i)| i)
+                .collect()
+        };
+        self.cursor = self.cursor.min(self.matches.len().saturating_sub(1));
+    }
+
+    /// Read the preview of the highlighted repository, if it is shown and not read yet.
+    pub fn load_preview(&mut self) {
+        if let Some(i) = self.current().filter(|_| self.show_preview) {
+            self.previews
+                .entry(i)
+                .or_insert_with(|| Preview::read(&self.entries[i]));
+        }
+    }
+
+    /// The highlighted repository, if any match the filter.
+    fn current(&self) -> Option<usize> {
+        self.matches.get(self.cursor).copied()
+    }
+
+    /// The repositories an action applies to: those marked, or else the highlighted one.
+    fn targets(&self) -> Vec<PathBuf> {
+        let indices: Vec<usize> = if self.marked.is_empty() {
+            self.current().into_iter().collect()
+        } else {
+            self.marked.iter().copied().collect()
+        };
+        indices
+            .into_iter()
+            .map(|i| self.entries[i].path.clone())
+            .collect()
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let last = self.matches.len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+    }
+
+    fn toggle(&mut self, i: usize) {
+        if !self.marked.remove(&i) {
+            self.marked.insert(i);
+        }
+    }
+
+    /// Mark every match, or unmark them all if they are already marked.
+    fn toggle_all(&mut self) {
+        if self.matches.iter().all(|i| self.marked.contains(i)) {
+            for i in &self.matches {
+                self.marked.remove(i);
+            }
+        } else {
+            self.marked.extend(self.matches.iter().copied());
+        }
+    }
+
+    fn confirm(&mut self, action: Action) {
+        if !self.targets().is_empty() {
+            self.mode = Mode::Confirm(action);
+        }
+    }
+
+    /// Update the picker for a key press.
+    pub fn handle(&mut self, key: KeyEvent) -> Step {
+        self.notice = None;
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Step::Quit;
+        }
+        match self.mode {
+            Mode::Confirm(action) => match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    return Step::Done(Outcome::Apply(action, self.targets()))
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc | KeyCode::Char('q') => {
+                    self.mode = Mode::Browse
+                }
+                _ => {}
+            },
+            Mode::Filter => match key.code {
+                KeyCode::Char(c) => {
+                    self.query.push(c);
+                    self.refilter();
+                }
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.refilter();
+                }
+                KeyCode::Up => self.move_by(-1),
+                KeyCode::Down => self.move_by(1),
+                KeyCode::Enter | KeyCode::Esc | KeyCode::Tab => self.mode = Mode::Browse,
+                _ => {}
+            },
+            Mode::Browse => {
+                if let Some(binding) = self.keymap.get(key_of(key)).cloned() {
+                    return self.apply(binding);
+                }
+            }
+        }
+        Step::Continue
+    }
+
+    fn apply(&mut self, binding: Binding) -> Step {
+        match binding {
+            Binding::Up => self.move_by(-1),
+            Binding::Down => self.move_by(1),
+            Binding::PageUp => self.move_by(-10),
+            Binding::PageDown => self.move_by(10),
+            Binding::Top => self.cursor = 0,
+            Binding::Bottom => self.move_by(isize::MAX),
+            Binding::Filter => self.mode = Mode::Filter,
+            Binding::Mark => {
+                if let Some(i) = self.current() {
+                    self.toggle(i);
+                    self.move_by(1);
+                }
+            }
+            Binding::MarkAll => self.toggle_all(),
+            Binding::UnmarkAll => self.marked.clear(),
+            Binding::Preview => self.show_preview = !self.show_preview,
+            Binding::Refresh => {
+                if let Some(i) = self.current() {
+                    self.previews.remove(&i);
+                }
+            }
+            Binding::Choose => {
+                if let Some(i) = self.current() {
+                    return Step::Done(Outcome::Choose(self.entries[i].path.clone()));
+                }
+            }
+            Binding::Quit => return Step::Quit,
+            Binding::Apply(action) => self.confirm(action),
+            Binding::Run(template) => {
+                let scripts: Vec<String> = self
+                    .targets()
+                    .iter()
+                    .map(|path| shell::expand_path(&template, path))
+                    .collect();
+                if !scripts.is_empty() {
+                    return Step::Run(scripts);
+                }
+            }
+        }
+        Step::Continue
+    }
+
+    /// Draw the picker over the whole frame.
+    pub fn draw(&self, frame: &mut Frame) {
+        let [main_area, status_area] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let list_area = if self.show_preview && main_area.width >= MIN_PREVIEW_WIDTH {
+            let [list_area, preview_area] =
+                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .areas(main_area);
+            self.draw_preview(frame, preview_area);
+            list_area
+        } else {
+            main_area
+        };
+
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|&i| {
+                let entry = &self.entries[i];
+                let mark = if self.marked.contains(&i) {
+                    "[x] "
+                } else {
+                    "[ ] "
+                };
+                let mut spans = vec![
+                    Span::raw(mark),
+                    Span::raw(entry.path.to_string_lossy().into_owned()),
+                ];
+                if let Some((_, url)) = entry.primary_remote() {
+                    spans.push(Span::styled(
+                        format!("  {}", url),
+                        Style::new().add_modifier(Modifier::DIM),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items).highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(self.current().map(|_| self.cursor));
+        frame.render_stateful_widget(list, list_area, &mut state);
+
+        let status = match (&self.mode, &self.notice) {
+            (Mode::Filter, _) => format!("/{}", self.query),
+            (_, Some(notice)) => notice.clone(),
+            _ => locale::text(
+                "pick-status",
+                &[
+                    ("matches", &self.matches.len()),
+                    ("total", &self.entries.len()),
+                    ("marked", &self.marked.len()),
+                    ("keys", &self.keymap.help()),
+                ],
+            ),
+        };
+        frame.render_widget(Paragraph::new(status), status_area);
+
+        if let Mode::Confirm(action) = self.mode {
+            self.draw_confirm(frame, action);
+        }
+    }
+
+    /// Draw the preview of the highlighted repository in `area`.
+    fn draw_preview(&self, frame: &mut Frame, area: Rect) {
+        let Some(i) = self.current() else {
+            return;
+        };
+        let lines: Vec<Line> = match self.previews.get(&i) {
+            Some(preview) => preview.lines().into_iter().map(Line::raw).collect(),
+            None => vec![Line::raw(locale::text("pick-loading", &[]))],
+        };
+        let title = format!(" {} ", self.entries[i].name());
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::new().borders(Borders::LEFT).title(title)),
+            area,
+        );
+    }
+
+    /// Draw the summary of what an action will apply to, over the middle of the frame.
+    fn draw_confirm(&self, frame: &mut Frame, action: Action) {
+        let targets = self.targets();
+        let mut lines: Vec<Line> = targets
+            .iter()
+            .map(|path| Line::raw(path.to_string_lossy().into_owned()))
+            .collect();
+        lines.push(Line::raw(""));
+        lines.push(Line::raw(locale::text("pick-confirm-hint", &[])));
+
+        let area = frame.area();
+        let width = area.width.saturating_sub(4).min(80);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        let title = format!(
+            " {} ",
+            locale::plural("pick-confirm", targets.len(), &[("action", &action.verb())])
+        );
+        frame.render_widget(Clear, popup);
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::new().borders(Borders::ALL).title(title)),
+            popup,
+        );
+    }
+}
+
+/// The terminal, in raw mode on the alternate screen until dropped.
+struct Screen(Terminal<CrosstermBackend<Stderr>>);
+
+impl Screen {
+    fn enter() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut stderr = io::stderr();
+        if let Err(e) = execute!(stderr, EnterAlternateScreen) {
+            let _ = terminal::disable_raw_mode();
+            return Err(e.into());
+        }
+        Ok(Screen(Terminal::new(CrosstermBackend::new(stderr))?))
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(self.0.backend_mut(), LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Run each of `scripts` with the terminal, stopping at the first that fails. Their output goes
+/// to standard error, since standard output may be being captured for the chosen path.
+fn run_commands(scripts: &[String]) -> Result<()> {
+    for script in scripts {
+        let status = shell::command(script)
+            .stdout(io::stderr())
+            .status()
+            .with_context(|| locale::failed("run", script))?;
+        if !status.success() {
+            bail!(locale::text(
+                "command-failed",
+                &[
+                    ("command", &format_args!("{:?}", script)),
+                    ("reason", &status)
+                ]
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Run the picker on the terminal until the user chooses, confirms an action, or quits.
+/// * `entries` - The repositories to pick from.
+/// * `query` - The initial filter.
+/// * `keymap` - The keys to browse with.
+pub fn run(entries: Vec<IndexEntry>, query: &str, keymap: Keymap) -> Result<Option<Outcome>> {
+    if !Capabilities::detect(Stream::Stderr).interactive() {
+        bail!(locale::text("pick-needs-terminal", &[]));
+    }
+    let mut picker = Picker::new(entries, query, keymap);
+    let mut screen = Screen::enter()?;
+    loop {
+        picker.load_preview();
+        screen.0.draw(|frame| picker.draw(frame))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match picker.handle(key) {
+            Step::Continue => {}
+            Step::Quit => return Ok(None),
+            Step::Done(outcome) => return Ok(Some(outcome)),
+            Step::Run(scripts) => {
+                drop(screen);
+                if let Err(e) = run_commands(&scripts) {
+                    picker.notice = Some(format!("{:#}", e));
+                }
+                // The commands may have changed what the previews show.
+                picker.previews.clear();
+                screen = Screen::enter()?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    use ratatui::backend::TestBackend;
+
+    use crate::status::Status;
+
+    fn entry(path: &str) -> IndexEntry {
+        IndexEntry {
+            path: PathBuf::from(path),
+            remotes: HashMap::from([(
+                "origin".to_string(),
+                format!("https://example.com{}", path),
+            )]),
+            primary_remote: None,
+            head: None,
+        }
+    }
+
+    fn press(picker: &mut Picker, keys: &str) -> Step {
+        let mut step = Step::Continue;
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\x1b' => KeyCode::Esc,
+                c => KeyCode::Char(c),
+            };
+            step = picker.handle(KeyEvent::from(code));
+        }
+        step
+    }
+
+    fn screen(picker: &Picker) -> Result<String> {
+        screen_of_width(picker, 60)
+    }
+
+    fn screen_of_width(picker: &Picker, width: u16) -> Result<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, 8))?;
+        terminal.draw(|frame| picker.draw(frame))?;
+        let buffer = terminal.backend().buffer();
+        Ok(buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    #[test]
+    fn test_picker() -> Result<()> {
+        let entries = vec![entry("/src/lg"), entry("/src/lgtm"), entry("/src/tools")];
+
+        let mut picker = Picker::new(entries.clone(), "", Keymap::default());
+        assert_eq!(
+            press(&mut picker, "j\n"),
+            Step::Done(Outcome::Choose(PathBuf::from("/src/lgtm")))
+        );
+
+        let mut picker = Picker::new(entries.clone(), "", Keymap::default());
+        assert_eq!(press(&mut picker, "/too\x1b"), Step::Continue);
+        assert!(screen(&picker)?.contains("1/3 repositories"));
+        assert_eq!(
+            press(&mut picker, "\n"),
+            Step::Done(Outcome::Choose(PathBuf::from("/src/tools")))
+        );
+
+        let mut picker = Picker::new(entries.clone(), "", Keymap::default());
+        press(&mut picker, "  ");
+        assert!(screen(&picker)?.contains("[x] /src/lgtm"));
+        press(&mut picker, "D");
+        let shown = screen(&picker)?;
+        assert!(shown.contains("Delete 2 repositories?"), "{}", shown);
+        assert_eq!(press(&mut picker, "n"), Step::Continue);
+        assert_eq!(
+            press(&mut picker, "fy"),
+            Step::Done(Outcome::Apply(
+                Action::Fetch,
+                vec![PathBuf::from("/src/lg"), PathBuf::from("/src/lgtm")]
+            ))
+        );
+
+        let mut picker = Picker::new(entries, "lg", Keymap::default());
+        press(&mut picker, "*");
+        assert_eq!(picker.targets().len(), 2);
+        press(&mut picker, "*");
+        assert_eq!(picker.targets().len(), 1);
+        assert_eq!(press(&mut picker, "q"), Step::Quit);
+        Ok(())
+    }
+
+    #[test]
+    fn test_preview() -> Result<()> {
+        let mut picker = Picker::new(
+            vec![entry("/src/lg"), entry("/src/tools")],
+            "",
+            Keymap::default(),
+        );
+        picker.previews.insert(
+            0,
+            Preview {
+                status: Ok(Status {
+                    branch: Some("main".to_string()),
+                    ..Status::default()
+                }),
+                commits: Ok(vec!["0123abc Add lg pick".to_string()]),
+                remotes: Vec::new(),
+            },
+        );
+        let shown = screen_of_width(&picker, 100)?;
+        assert!(shown.contains(" lg "), "{}", shown);
+        assert!(shown.contains("main clean"), "{}", shown);
+        assert!(shown.contains("0123abc Add lg pick"), "{}", shown);
+        assert!(!screen(&picker)?.contains("main clean"));
+
+        press(&mut picker, "j");
+        assert!(screen_of_width(&picker, 100)?.contains("Loading..."));
+        press(&mut picker, "v");
+        assert!(!screen_of_width(&picker, 100)?.contains("Loading..."));
+        Ok(())
+    }
+
+    #[test]
+    fn test_keymap() -> Result<()> {
+        let keys = BTreeMap::from([
+            ("o".to_string(), "code {path}".to_string()),
+            ("x".to_string(), "delete".to_string()),
+            ("D".to_string(), "none".to_string()),
+            ("ctrl-g".to_string(), "bottom".to_string()),
+        ]);
+        let mut picker = Picker::new(
+            vec![entry("/src/lg"), entry("/src/it's")],
+            "",
+            Keymap::new(&keys)?,
+        );
+        let help = screen_of_width(&picker, 200)?;
+        assert!(help.contains("x delete  v preview"), "{}", help);
+        assert!(help.contains("o code {path}"), "{}", help);
+
+        assert_eq!(press(&mut picker, "D"), Step::Continue);
+        assert!(!screen(&picker)?.contains("Delete"));
+        press(&mut picker, "x");
+        assert!(screen(&picker)?.contains("Delete 1 repository?"));
+        press(&mut picker, "n");
+
+        picker.handle(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL));
+        assert_eq!(
+            press(&mut picker, "o"),
+            Step::Run(vec![r"code '/src/it'\''s'".to_string()])
+        );
+        press(&mut picker, "g");
+        assert_eq!(
+            press(&mut picker, "  o"),
+            Step::Run(vec![
+                "code '/src/lg'".to_string(),
+                r"code '/src/it'\''s'".to_string()
+            ])
+        );
+
+        assert!(Keymap::new(&BTreeMap::from([(
+            "hyper-x".to_string(),
+            "quit".to_string()
+        )]))
+        .is_err());
+        assert_eq!(parse_key("f5")?, (KeyCode::F(5), KeyModifiers::NONE));
+        assert_eq!(key_name(parse_key("pagedown")?), "pagedown");
+        assert_eq!(key_name(parse_key("ctrl-space")?), "ctrl-space");
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+//! External plugins: `lg-<name>` executables on `PATH` that speak JSON.
+//!
+//! A plugin is run as `lg-<name> <command>` with a JSON document on standard input:
+//!
+//! - `collect` receives `{"protocol": 1, "repositories": [{"path": ..., "remotes": {...}}]}` and
+//!   must print a JSON array with one value per repository, in order, using `null` for none.
+//!   Each value is stored under the plugin's name in the repository's `plugins` field.
+//! - `output` receives the document `lg -f json` would print, and whatever it prints is passed
+//!   through as the output.
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+
+use crate::locale;
+use crate::output::{self, OutputFormat};
+use crate::time::TimeFormat;
+use crate::GitDirectory;
+
+/// The version of the plugin protocol, sent as `protocol` to collectors.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+const PREFIX: &str = "lg-";
+
+/// An executable plugin found on `PATH`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Plugin {
+    /// The name the plugin is referred to by, without the `lg-` prefix.
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Every plugin on `PATH`, by name; where names repeat, the first on `PATH` wins.
+pub fn discover() -> BTreeMap<String, Plugin> {
+    let mut plugins = BTreeMap::new();
+    for dir in env::split_paths(&env::var_os("PATH").unwrap_or_default()) {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = plugin_name(&path) else {
+                continue;
+            };
+            if is_executable(&path) {
+                plugins.entry(name.clone()).or_insert(Plugin { name, path });
+            }
+        }
+    }
+    plugins
+}
+
+/// The plugin called `name`.
+pub fn find(name: &str) -> Result<Plugin> {
+    discover().remove(name).with_context(|| {
+        locale::text(
+            "plugin-missing",
+            &[
+                ("name", &format_args!("{:?}", name)),
+                ("program", &format_args!("{}{}", PREFIX, name)),
+            ],
+        )
+    })
+}
+
+fn plugin_name(path: &Path) -> Option<String> {
+    let name = if cfg!(windows) {
+        path.file_stem()
+    } else {
+        path.file_name()
+    }?;
+    let name = name.to_str()?.strip_prefix(PREFIX)?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("exe") || ext.eq_ignore_ascii_case("bat"))
+}
+
+impl Plugin {
+    /// Run `lg-<name> <command>` with `input` on standard input and return its standard output.
+    fn run(&self, command: &str, input: &[u8]) -> Result<Vec<u8>> {
+        let mut child = Command::new(&self.path)
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| locale::failed("run-plugin", &self.path))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // The plugin may exit without reading its input; that is not an error.
+            let _ = stdin.write_all(input);
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!(locale::text(
+                "exited-with",
+                &[
+                    ("command", &format_args!("{} {}", self.name, command)),
+                    ("status", &output.status)
+                ]
+            ));
+        }
+        Ok(output.stdout)
+    }
+
+    /// Collect this plugin's metadata for every repository in `dir`.
+    pub fn collect(&self, dir: &mut GitDirectory) -> Result<()> {
+        let repositories: Vec<_> = dir
+            .repos()
+            .into_iter()
+            .map(|(path, repo)| json!({ "path": path.to_string_lossy(), "remotes": repo.remotes }))
+            .collect();
+        let input = json!({ "protocol": PROTOCOL_VERSION, "repositories": repositories });
+        let stdout = self.run("collect", input.to_string().as_bytes())?;
+        let values: Vec<Value> = serde_json::from_slice(&stdout)
+            .with_context(|| locale::text("plugin-invalid-json", &[("name", &self.name)]))?;
+        if values.len() != repositories.len() {
+            bail!(locale::text(
+                "plugin-value-count",
+                &[
+                    ("name", &self.name),
+                    ("values", &values.len()),
+                    ("repositories", &repositories.len())
+                ]
+            ));
+        }
+        let mut values = values.into_iter();
+        assign(dir, &self.name, &mut values);
+        Ok(())
+    }
+
+    /// Format `dir` with this plugin, writing its output to `out`.
+    pub fn output(&self, dir: &GitDirectory, out: &mut impl Write) -> Result<()> {
+        let mut input = Vec::new();
+        output::write(
+            dir,
+            OutputFormat::Json,
+            &[],
+            TimeFormat::Iso,
+            false,
+            &mut input,
+        )?;
+        out.write_all(&self.run("output", &input)?)?;
+        Ok(())
+    }
+}
+
+/// Store `values` in the repositories of `dir`, in the order of [`GitDirectory::repos`].
+fn assign(dir: &mut GitDirectory, name: &str, values: &mut impl Iterator<Item = Value>) {
+    if !dir.remotes.is_empty() {
+        match values.next() {
+            Some(Value::Null) | None => {}
+            Some(value) => {
+                dir.plugins.insert(name.to_string(), value);
+            }
+        }
+    }
+    for child in &mut dir.children {
+        assign(child, name, values);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plugin_name() {
+        assert_eq!(
+            plugin_name(Path::new("/usr/bin/lg-size")).as_deref(),
+            Some("size")
+        );
+        assert_eq!(plugin_name(Path::new("/usr/bin/lg-")), None);
+        assert_eq!(plugin_name(Path::new("/usr/bin/lg")), None);
+    }
+}
@@ -0,0 +1,129 @@
+//! What `lg pick` shows beside the list for the highlighted repository: its recent commits,
+//! working tree status and remotes.
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::index::IndexEntry;
+use crate::locale;
+use crate::refs;
+use crate::status::Status;
+
+/// How many commits the preview lists.
+pub const COMMITS: usize = 10;
+
+/// A repository's preview; the parts read from Git hold the error if reading failed.
+#[derive(Clone, Debug)]
+pub struct Preview {
+    pub status: Result<Status, String>,
+    /// `git log --oneline` for the most recent commits.
+    pub commits: Result<Vec<String>, String>,
+    /// Remote names and URLs, sorted by name.
+    pub remotes: Vec<(String, String)>,
+}
+
+impl Preview {
+    /// Read the preview of `entry` by running `git status` and `git log`.
+    pub fn read(entry: &IndexEntry) -> Self {
+        let mut remotes: Vec<_> = entry
+            .remotes
+            .iter()
+            .map(|(name, url)| (name.clone(), url.clone()))
+            .collect();
+        remotes.sort();
+        Preview {
+            status: Status::read(&entry.path).map_err(|e| format!("{:#}", e)),
+            commits: log(&entry.path, COMMITS).map_err(|e| format!("{:#}", e)),
+            remotes,
+        }
+    }
+
+    /// The preview as lines of text, in sections for the status, commits and remotes.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        match &self.status {
+            Ok(status) => lines.push(status.to_string()),
+            Err(e) => lines.push(e.clone()),
+        }
+        lines.push(String::new());
+        match &self.commits {
+            Ok(commits) if commits.is_empty() => lines.push(locale::text("no-commits", &[])),
+            Ok(commits) => lines.extend(commits.iter().cloned()),
+            Err(e) => lines.push(e.clone()),
+        }
+        lines.push(String::new());
+        lines.extend(
+            self.remotes
+                .iter()
+                .map(|(name, url)| format!("{}  {}", name, url)),
+        );
+        lines
+    }
+}
+
+/// The most recent `count` commits on `HEAD` at `path`, as `git log --oneline` prints them;
+/// empty if there are none yet.
+pub fn log(path: &Path, count: usize) -> Result<Vec<String>> {
+    let output = refs::git_command(path)
+        .args(["log", "--oneline", "--no-decorate", "--no-color"])
+        .arg(format!("-{}", count))
+        .output()
+        .with_context(|| locale::text("failed-run-git", &[("args", &"log")]))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // A repository with no commits yet has nothing to show; that is not an error.
+        if stderr.contains("does not have any commits") {
+            return Ok(Vec::new());
+        }
+        bail!(locale::text(
+            "git-failed-in",
+            &[
+                ("command", &"log"),
+                ("path", &format_args!("{:?}", path)),
+                ("error", &stderr.trim())
+            ]
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let preview = Preview {
+            status: Ok(Status {
+                branch: Some("main".to_string()),
+                untracked: 1,
+                ..Status::default()
+            }),
+            commits: Ok(vec!["0123abc Add lg pick".to_string()]),
+            remotes: vec![("origin".to_string(), "https://example.com/lg".to_string())],
+        };
+        assert_eq!(
+            preview.lines(),
+            vec![
+                "main 1 untracked",
+                "",
+                "0123abc Add lg pick",
+                "",
+                "origin  https://example.com/lg"
+            ]
+        );
+
+        let failed = Preview {
+            status: Err("git status failed".to_string()),
+            commits: Ok(Vec::new()),
+            remotes: Vec::new(),
+        };
+        assert_eq!(
+            failed.lines(),
+            vec!["git status failed", "", "No commits", ""]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Recognising the ecosystems a repository's projects belong to by their manifest files.
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// An ecosystem whose manifest can sit at the top of a repository.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectType {
+    Rust,
+    Node,
+    Deno,
+    Go,
+    Python,
+    Ruby,
+    Php,
+    Java,
+    Scala,
+    Clojure,
+    Dotnet,
+    Elixir,
+    Erlang,
+    Haskell,
+    Ocaml,
+    Swift,
+    Dart,
+    Julia,
+    Zig,
+    Nix,
+    Cmake,
+}
+
+/// Manifest file names, and extensions as `*.ext`, by the ecosystem they mark.
+const MANIFESTS: &[(ProjectType, &[&str])] = &[
+    (ProjectType::Rust, &["Cargo.toml"]),
+    (ProjectType::Node, &["package.json"]),
+    (ProjectType::Deno, &["deno.json", "deno.jsonc"]),
+    (ProjectType::Go, &["go.mod"]),
+    (
+        ProjectType::Python,
+        &[
+            "pyproject.toml",
+            "setup.py",
+            "setup.cfg",
+            "requirements.txt",
+            "Pipfile",
+        ],
+    ),
+    (ProjectType::Ruby, &["Gemfile", "*.gemspec"]),
+    (ProjectType::Php, &["composer.json"]),
+    (
+        ProjectType::Java,
+        &["pom.xml", "build.gradle", "build.gradle.kts"],
+    ),
+    (ProjectType::Scala, &["build.sbt"]),
+    (ProjectType::Clojure, &["project.clj", "deps.edn"]),
+    (ProjectType::Dotnet, &["*.sln", "*.csproj", "*.fsproj"]),
+    (ProjectType::Elixir, &["mix.exs"]),
+    (ProjectType::Erlang, &["rebar.config"]),
+    (ProjectType::Haskell, &["stack.yaml", "*.cabal"]),
+    (ProjectType::Ocaml, &["dune-project", "*.opam"]),
+    (ProjectType::Swift, &["Package.swift"]),
+    (ProjectType::Dart, &["pubspec.yaml"]),
+    (ProjectType::Julia, &["Project.toml"]),
+    (ProjectType::Zig, &["build.zig"]),
+    (ProjectType::Nix, &["flake.nix", "default.nix", "shell.nix"]),
+    (ProjectType::Cmake, &["CMakeLists.txt"]),
+];
+
+impl ProjectType {
+    /// The name used in output.
+    pub fn name(self) -> &'static str {
+        match self {
+            ProjectType::Rust => "rust",
+            ProjectType::Node => "node",
+            ProjectType::Deno => "deno",
+            ProjectType::Go => "go",
+            ProjectType::Python => "python",
+            ProjectType::Ruby => "ruby",
+            ProjectType::Php => "php",
+            ProjectType::Java => "java",
+            ProjectType::Scala => "scala",
+            ProjectType::Clojure => "clojure",
+            ProjectType::Dotnet => "dotnet",
+            ProjectType::Elixir => "elixir",
+            ProjectType::Erlang => "erlang",
+            ProjectType::Haskell => "haskell",
+            ProjectType::Ocaml => "ocaml",
+            ProjectType::Swift => "swift",
+            ProjectType::Dart => "dart",
+            ProjectType::Julia => "julia",
+            ProjectType::Zig => "zig",
+            ProjectType::Nix => "nix",
+            ProjectType::Cmake => "cmake",
+        }
+    }
+}
+
+/// Whether a file called `file_name` is `manifest`, which may be an extension as `*.ext`.
+fn matches(manifest: &str, file_name: &str) -> bool {
+    match manifest.strip_prefix('*') {
+        Some(extension) => file_name.len() > extension.len() && file_name.ends_with(extension),
+        None => file_name == manifest,
+    }
+}
+
+/// The ecosystems with a manifest at the top of the working tree at `path`, in the order of
+/// [`ProjectType`]; empty if it cannot be read.
+pub fn detect(path: &Path) -> Vec<ProjectType> {
+    let Ok(entries) = fs::read_dir(path) else {
+        return Vec::new();
+    };
+    let names: Vec<_> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| !file_type.is_dir()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    MANIFESTS
+        .iter()
+        .filter(|(_, manifests)| {
+            manifests
+                .iter()
+                .any(|manifest| names.iter().any(|name| matches(manifest, name)))
+        })
+        .map(|(kind, _)| *kind)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect() -> std::io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        assert_eq!(detect(repo), []);
+        assert_eq!(detect(&repo.join("missing")), []);
+
+        for file in ["package.json", "Cargo.toml", "lg.cabal", "README.md"] {
+            fs::write(repo.join(file), "")?;
+        }
+        // A directory named like a manifest is not one.
+        fs::create_dir(repo.join("go.mod"))?;
+        assert_eq!(
+            detect(repo),
+            [ProjectType::Rust, ProjectType::Node, ProjectType::Haskell]
+        );
+        assert_eq!(ProjectType::Haskell.name(), "haskell");
+        Ok(())
+    }
+}
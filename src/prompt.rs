@@ -0,0 +1,80 @@
+//! Compact repository descriptions for shell prompts, answered from the index alone.
+use crate::index::IndexEntry;
+use crate::url::RemoteUrl;
+
+/// The placeholders understood by [`render`].
+pub const PLACEHOLDERS: &[&str] = &["name", "path", "remote", "url", "host", "owner"];
+
+/// Expand the `{placeholder}`s in `template` for `entry`.
+///
+/// Placeholders without a value (e.g. `{host}` for a local remote) expand to an empty string,
+/// and unknown placeholders are left untouched.
+pub fn render(template: &str, entry: &IndexEntry) -> String {
+    let remote = entry.primary_remote();
+    let parsed = remote.and_then(|(_, url)| RemoteUrl::parse(url));
+    let mut out = template.to_string();
+    for placeholder in PLACEHOLDERS {
+        let key = format!("{{{}}}", placeholder);
+        if !out.contains(&key) {
+            continue;
+        }
+        let value = match *placeholder {
+            "name" => entry.name(),
+            "path" => entry.path.display().to_string(),
+            "remote" => remote.map(|(name, _)| name.to_string()).unwrap_or_default(),
+            "url" => remote.map(|(_, url)| url.to_string()).unwrap_or_default(),
+            "host" => parsed.as_ref().map(|u| u.host.clone()).unwrap_or_default(),
+            "owner" => parsed
+                .as_ref()
+                .and_then(|u| u.owner())
+                .unwrap_or_default()
+                .to_string(),
+            _ => unreachable!(),
+        };
+        out = out.replace(&key, &value);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_render() {
+        let entry = IndexEntry {
+            path: PathBuf::from("/src/lg"),
+            remotes: HashMap::from([
+                (
+                    "upstream".to_string(),
+                    "git@github.com:kthwaite/lg.git".to_string(),
+                ),
+                ("fork".to_string(), "/srv/git/lg".to_string()),
+            ]),
+            primary_remote: None,
+            head: None,
+        };
+        assert_eq!(
+            render("{name} {remote} {host}/{owner} {unknown}", &entry),
+            "lg fork / {unknown}"
+        );
+        let entry = IndexEntry {
+            primary_remote: Some("upstream".to_string()),
+            ..entry
+        };
+        assert_eq!(render("{remote} {owner}", &entry), "upstream kthwaite");
+        let entry = IndexEntry {
+            remotes: HashMap::from([(
+                "origin".to_string(),
+                "git@github.com:kthwaite/lg.git".to_string(),
+            )]),
+            ..entry
+        };
+        assert_eq!(
+            render("{name}@{host}:{owner}", &entry),
+            "lg@github.com:kthwaite"
+        );
+    }
+}
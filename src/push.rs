@@ -0,0 +1,216 @@
+//! Where a plain `git push` sends each branch, worked out from the repository's config as Git
+//! does, including triangular workflows that fetch from one remote and push to another.
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::gitconfig::Entry;
+use crate::refs;
+
+/// A repository's push configuration.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Push {
+    /// The repository's `remote.pushDefault`, the remote branches push to unless they set
+    /// `pushRemote`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_remote: Option<String>,
+    /// Where `git push` would send each local branch, by branch name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub branches: BTreeMap<String, PushTarget>,
+}
+
+/// Where `git push` sends a branch.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PushTarget {
+    /// The remote pushed to.
+    pub remote: String,
+    /// The branch on the remote that is updated; absent when `git push` would refuse or push
+    /// nothing, such as for `push.default=simple` without an upstream of the same name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+}
+
+/// Read the push configuration of the Git working tree at `path`.
+pub fn read(path: &Path) -> Result<Push> {
+    let entries = refs::config(path)?;
+    let default_remote = last(&entries, "remote", None, "pushdefault");
+    let branches = refs::list(path, "refs/heads/")
+        .into_iter()
+        .map(|branch| {
+            let target = target(&entries, &branch, default_remote.as_deref());
+            (branch, target)
+        })
+        .collect();
+    Ok(Push {
+        default_remote,
+        branches,
+    })
+}
+
+/// The last value of `section.subsection.key` in `entries`, which is the one Git uses.
+fn last(entries: &[Entry], section: &str, subsection: Option<&str>, key: &str) -> Option<String> {
+    entries
+        .iter()
+        .rev()
+        .find(|entry| entry.is(section, key) && entry.subsection.as_deref() == subsection)
+        .and_then(|entry| entry.value.clone())
+}
+
+/// Where `git push` sends `branch`: to `branch.<name>.pushRemote`, else `remote.pushDefault`,
+/// else the branch's upstream remote, else `origin`; the remote branch follows the remote's
+/// `push` refspecs if it has any, otherwise `push.default`.
+fn target(entries: &[Entry], branch: &str, default_remote: Option<&str>) -> PushTarget {
+    let upstream_remote = last(entries, "branch", Some(branch), "remote");
+    let remote = last(entries, "branch", Some(branch), "pushremote")
+        .or_else(|| default_remote.map(str::to_string))
+        .or_else(|| upstream_remote.clone())
+        .unwrap_or_else(|| "origin".to_string());
+    let refspecs: Vec<_> = entries
+        .iter()
+        .filter(|entry| entry.is("remote", "push") && entry.subsection.as_deref() == Some(&remote))
+        .filter_map(|entry| entry.value.as_deref())
+        .collect();
+    let branch_name = if refspecs.is_empty() {
+        by_push_default(entries, branch, &remote, upstream_remote.as_deref())
+    } else {
+        refspecs
+            .iter()
+            .find_map(|refspec| map_refspec(refspec, branch))
+    };
+    PushTarget {
+        remote,
+        branch: branch_name,
+    }
+}
+
+/// The remote branch `branch` is pushed to under `push.default`.
+fn by_push_default(
+    entries: &[Entry],
+    branch: &str,
+    remote: &str,
+    upstream_remote: Option<&str>,
+) -> Option<String> {
+    let merge = last(entries, "branch", Some(branch), "merge")
+        .map(|merge| merge.trim_start_matches("refs/heads/").to_string());
+    // Git fetches from `origin` when the branch has no upstream remote.
+    let triangular = upstream_remote.unwrap_or("origin") != remote;
+    let upstream = merge.filter(|_| upstream_remote == Some(remote));
+    let auto_setup = entries
+        .iter()
+        .rev()
+        .find(|entry| entry.is("push", "autosetupremote") && entry.subsection.is_none())
+        .is_some_and(Entry::is_true);
+    let push_default = last(entries, "push", None, "default").unwrap_or_default();
+    match push_default.to_ascii_lowercase().as_str() {
+        "nothing" => None,
+        // `matching` pushes the branch if the remote has one of the same name, which only the
+        // remote knows.
+        "current" | "matching" => Some(branch.to_string()),
+        "upstream" | "tracking" => upstream,
+        // `simple`, the default.
+        _ if triangular => Some(branch.to_string()),
+        _ => match upstream {
+            Some(upstream) => (upstream == branch).then_some(upstream),
+            None => auto_setup.then(|| branch.to_string()),
+        },
+    }
+}
+
+/// The remote branch a `push` refspec such as `refs/heads/*:refs/heads/me/*` sends `branch`
+/// to, if it matches the branch.
+fn map_refspec(refspec: &str, branch: &str) -> Option<String> {
+    let refspec = refspec.trim_start_matches('+');
+    let (src, dst) = refspec.split_once(':').unwrap_or((refspec, refspec));
+    let full = format!("refs/heads/{}", branch);
+    let dst = match src.split_once('*') {
+        Some((prefix, suffix)) => {
+            let matched = full.strip_prefix(prefix)?.strip_suffix(suffix)?;
+            dst.replacen('*', matched, 1)
+        }
+        None if src == full || src == branch => dst.to_string(),
+        None => return None,
+    };
+    Some(dst.trim_start_matches("refs/heads/").to_string()).filter(|dst| !dst.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn to(branch: Option<&str>, remote: &str) -> PushTarget {
+        PushTarget {
+            remote: remote.to_string(),
+            branch: branch.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_read() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        fs::create_dir_all(repo.join(".git/refs/heads/feature"))?;
+        for branch in ["main", "feature/x", "renamed"] {
+            fs::write(repo.join(".git/refs/heads").join(branch), "0\n")?;
+        }
+        fs::write(
+            repo.join(".git/packed-refs"),
+            "# pack-refs with: peeled fully-peeled sorted\n\
+             0 refs/heads/local\n0 refs/remotes/origin/main\n",
+        )?;
+        fs::write(
+            repo.join(".git/config"),
+            "[remote \"origin\"]\n\turl = https://github.com/kthwaite/lg.git\n\
+             [branch \"main\"]\n\tremote = origin\n\tmerge = refs/heads/main\n\
+             [branch \"renamed\"]\n\tremote = origin\n\tmerge = refs/heads/old\n",
+        )?;
+        let push = read(repo)?;
+        assert_eq!(push.default_remote, None);
+        assert_eq!(
+            push.branches,
+            BTreeMap::from([
+                ("feature/x".to_string(), to(None, "origin")),
+                ("local".to_string(), to(None, "origin")),
+                ("main".to_string(), to(Some("main"), "origin")),
+                ("renamed".to_string(), to(None, "origin")),
+            ])
+        );
+
+        // A triangular workflow: fetch from origin, push to a fork.
+        fs::write(
+            repo.join(".git/config"),
+            "[remote]\n\tpushDefault = fork\n\
+             [branch \"main\"]\n\tremote = origin\n\tmerge = refs/heads/main\n\
+             [branch \"renamed\"]\n\tremote = origin\n\tmerge = refs/heads/old\n\
+             \tpushRemote = origin\n\
+             [push]\n\tdefault = upstream\n",
+        )?;
+        let push = read(repo)?;
+        assert_eq!(push.default_remote.as_deref(), Some("fork"));
+        assert_eq!(push.branches["main"], to(None, "fork"));
+        assert_eq!(push.branches["renamed"], to(Some("old"), "origin"));
+
+        fs::write(
+            repo.join(".git/config"),
+            "[remote]\n\tpushDefault = fork\n\
+             [remote \"fork\"]\n\tpush = refs/heads/*:refs/heads/me/*\n",
+        )?;
+        let push = read(repo)?;
+        assert_eq!(push.branches["feature/x"], to(Some("me/feature/x"), "fork"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_refspec() {
+        assert_eq!(map_refspec("main", "main").as_deref(), Some("main"));
+        assert_eq!(
+            map_refspec("+refs/heads/main:refs/heads/trunk", "main").as_deref(),
+            Some("trunk")
+        );
+        assert_eq!(map_refspec("refs/heads/main:refs/heads/trunk", "dev"), None);
+        assert_eq!(map_refspec("refs/heads/main:", "main"), None);
+    }
+}
@@ -0,0 +1,79 @@
+//! Flat, one-row-per-repository views of a scan, for tabular output formats.
+use std::path::Path;
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+use crate::url::RemoteUrl;
+use crate::GitDirectory;
+
+/// A column of a repository record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Column {
+    /// The repository's full path.
+    Path,
+    /// The repository's directory name.
+    Name,
+    /// The host of the primary remote.
+    Host,
+    /// The owner part of the primary remote's path.
+    Owner,
+    /// The name of the primary remote.
+    Remote,
+    /// The URL of the primary remote.
+    Url,
+    /// Every remote, as a record of name to URL.
+    Remotes,
+    /// The version control system, such as `git` or `hg`.
+    Vcs,
+}
+
+impl Column {
+    /// The columns shown when none are given, in order.
+    pub const DEFAULT: &'static [Column] = &[
+        Column::Path,
+        Column::Name,
+        Column::Host,
+        Column::Owner,
+        Column::Remote,
+        Column::Url,
+    ];
+
+    /// The column's name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Path => "path",
+            Column::Name => "name",
+            Column::Host => "host",
+            Column::Owner => "owner",
+            Column::Remote => "remote",
+            Column::Url => "url",
+            Column::Remotes => "remotes",
+            Column::Vcs => "vcs",
+        }
+    }
+
+    /// The column's value for the repository `repo` at `path`; `null` if it has none.
+    pub fn value(self, path: &Path, repo: &GitDirectory) -> Value {
+        let primary = repo.primary_remote();
+        let parsed = primary.and_then(|(_, url)| RemoteUrl::parse(url));
+        match self {
+            Column::Path => json!(path.to_string_lossy()),
+            Column::Name => json!(path.file_name().map(|n| n.to_string_lossy())),
+            Column::Host => json!(parsed.as_ref().map(|url| &url.host)),
+            Column::Owner => json!(parsed.as_ref().and_then(|url| url.owner())),
+            Column::Remote => json!(primary.map(|(name, _)| name)),
+            Column::Url => json!(primary.map(|(_, url)| url)),
+            Column::Remotes => json!(repo.remotes),
+            Column::Vcs => json!(repo.vcs.name()),
+        }
+    }
+}
+
+/// One row per repository in `dir`, with the values of `columns` in order.
+pub fn rows(dir: &GitDirectory, columns: &[Column]) -> Vec<Vec<Value>> {
+    dir.repos()
+        .iter()
+        .map(|(path, repo)| columns.iter().map(|c| c.value(path, repo)).collect())
+        .collect()
+}
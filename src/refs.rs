@@ -0,0 +1,481 @@
+//! Direct reads of the Git directory: locating it and resolving `HEAD`.
+//!
+//! The Git directory of a working tree is found as Git finds it: through `GIT_DIR` (with
+//! `GIT_WORK_TREE`, `core.worktree` and `GIT_COMMON_DIR`) for the working tree those select,
+//! else a `.git` directory, or a `.git` file naming one elsewhere, as worktrees, submodules and
+//! `git clone --separate-git-dir` create. The directory shared by worktrees comes from the Git
+//! directory's `commondir` file.
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::{gitconfig, paths};
+
+/// Environment variables that point Git at a repository other than the one it runs in. They
+/// are cleared for the commands `lg` runs, which name their repository with `-C`.
+const REPOSITORY_ENV: &[&str] = &[
+    "GIT_DIR",
+    "GIT_WORK_TREE",
+    "GIT_COMMON_DIR",
+    "GIT_INDEX_FILE",
+    "GIT_OBJECT_DIRECTORY",
+    "GIT_ALTERNATE_OBJECT_DIRECTORIES",
+    "GIT_NAMESPACE",
+    "GIT_PREFIX",
+];
+
+/// A `git -C path` command that acts on the repository at `path` even when run from a hook or
+/// another environment that sets `GIT_DIR`.
+pub fn git_command(path: &Path) -> Command {
+    let mut command = Command::new("git");
+    for var in REPOSITORY_ENV {
+        command.env_remove(var);
+    }
+    command.arg("-C").arg(path);
+    command
+}
+
+/// The repository selected by `GIT_DIR`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnvRepository {
+    /// `GIT_DIR`.
+    pub git_dir: PathBuf,
+    /// `GIT_COMMON_DIR`, if set.
+    pub common_dir: Option<PathBuf>,
+    /// `GIT_WORK_TREE`, else the repository's `core.worktree`, else the current directory;
+    /// canonical if it exists.
+    pub work_tree: PathBuf,
+}
+
+impl EnvRepository {
+    /// The repository selected by this process's environment, if `GIT_DIR` is set.
+    pub fn get() -> Option<&'static EnvRepository> {
+        static ENV: OnceLock<Option<EnvRepository>> = OnceLock::new();
+        ENV.get_or_init(|| {
+            let cwd = std::env::current_dir().ok()?;
+            Self::resolve(|name| std::env::var_os(name), &cwd)
+        })
+        .as_ref()
+    }
+
+    /// The repository selected by the variables `var` reads, if `GIT_DIR` is set.
+    /// * `var` - Reads an environment variable.
+    /// * `cwd` - The directory relative paths in variables are relative to.
+    pub fn resolve(var: impl Fn(&str) -> Option<OsString>, cwd: &Path) -> Option<Self> {
+        let path = |name: &str| var(name).filter(|v| !v.is_empty()).map(|v| cwd.join(v));
+        let git_dir = path("GIT_DIR")?;
+        let work_tree = path("GIT_WORK_TREE")
+            .or_else(|| core_worktree(&git_dir))
+            .unwrap_or_else(|| cwd.to_path_buf());
+        Some(EnvRepository {
+            common_dir: path("GIT_COMMON_DIR"),
+            work_tree: paths::canonicalize(&work_tree).unwrap_or(work_tree),
+            git_dir,
+        })
+    }
+
+    /// Whether this is the repository of the working tree at `path`.
+    pub fn applies_to(&self, path: &Path) -> bool {
+        paths::canonicalize(path).is_ok_and(|path| path == self.work_tree)
+    }
+}
+
+/// The working tree set by `core.worktree` in the config of `git_dir`, which is relative to it.
+fn core_worktree(git_dir: &Path) -> Option<PathBuf> {
+    let contents = fs::read_to_string(git_dir.join("config")).ok()?;
+    let worktree = gitconfig::parse(&contents)
+        .ok()?
+        .into_iter()
+        .rev()
+        .find(|entry| entry.is("core", "worktree") && entry.subsection.is_none())?
+        .value?;
+    Some(git_dir.join(worktree))
+}
+
+/// Locate the Git directory of the working tree at `path`.
+///
+/// Handles `GIT_DIR`, a `.git` directory, and a `.git` file containing `gitdir: <path>`, as used
+/// by worktrees and submodules. A `.git` symlink is resolved, and only makes `path` a working
+/// tree if it leads to a Git directory or a `.git` file; a link to any other directory is not
+/// followed.
+pub fn git_dir(path: &Path) -> Option<PathBuf> {
+    if let Some(env) = EnvRepository::get().filter(|env| env.applies_to(path)) {
+        return Some(env.git_dir.clone());
+    }
+    let dot_git = path.join(".git");
+    let metadata = fs::symlink_metadata(&dot_git).ok()?;
+    if metadata.is_symlink() {
+        let target = paths::canonicalize(&dot_git).ok()?;
+        if target.is_dir() {
+            return is_git_dir(&target).then_some(target);
+        }
+        return read_git_file(path, &target);
+    }
+    if metadata.is_dir() {
+        return Some(dot_git);
+    }
+    read_git_file(path, &dot_git)
+}
+
+/// The Git directory named by the `.git` file `file` of the working tree at `path`. As in Git,
+/// a relative name is relative to the working tree, even if `file` is a link to elsewhere.
+fn read_git_file(path: &Path, file: &Path) -> Option<PathBuf> {
+    let contents = fs::read_to_string(file).ok()?;
+    let target = contents.strip_prefix("gitdir:")?.trim();
+    Some(path.join(target))
+}
+
+/// Whether `dir` looks like a Git directory: one with a `HEAD`.
+fn is_git_dir(dir: &Path) -> bool {
+    dir.join("HEAD").is_file()
+}
+
+/// The Git directory of the working tree at `path`, resolved, if it is not a `.git` directory
+/// inside it: the target of a `.git` symlink or file, or `GIT_DIR`.
+pub fn external_git_dir(path: &Path) -> Option<PathBuf> {
+    let git_dir = git_dir(path)?;
+    let dot_git = path.join(".git");
+    if git_dir == dot_git && !dot_git.is_symlink() {
+        return None;
+    }
+    Some(paths::canonicalize(&git_dir).unwrap_or(git_dir))
+}
+
+/// Locate the directory holding what the working tree at `path` shares with other worktrees
+/// of its repository, such as its config and refs: `GIT_COMMON_DIR` where `GIT_DIR` applies,
+/// the directory named by the Git directory's `commondir` file, or else the Git directory.
+pub fn common_dir(path: &Path) -> Option<PathBuf> {
+    if let Some(common_dir) = EnvRepository::get()
+        .filter(|env| env.applies_to(path))
+        .and_then(|env| env.common_dir.clone())
+    {
+        return Some(common_dir);
+    }
+    let git_dir = git_dir(path)?;
+    match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(common_dir) => Some(git_dir.join(common_dir.trim())),
+        Err(_) => Some(git_dir),
+    }
+}
+
+/// The variables set in the config of the repository of the working tree at `path`, followed
+/// by those in the worktree's `config.worktree`, which take precedence; empty if there is no
+/// repository.
+pub fn config(path: &Path) -> anyhow::Result<Vec<gitconfig::Entry>> {
+    let (Some(git_dir), Some(common_dir)) = (git_dir(path), common_dir(path)) else {
+        return Ok(Vec::new());
+    };
+    let mut entries = Vec::new();
+    for config in [common_dir.join("config"), git_dir.join("config.worktree")] {
+        if config.is_file() {
+            entries.extend(gitconfig::read(&config)?);
+        }
+    }
+    Ok(entries)
+}
+
+/// The names of the refs under `prefix`, such as `refs/heads/`, with the prefix removed,
+/// sorted; both loose refs and those in `packed-refs` are read.
+pub fn list(path: &Path, prefix: &str) -> Vec<String> {
+    let Some(common_dir) = common_dir(path) else {
+        return Vec::new();
+    };
+    let mut names = Vec::new();
+    let mut dirs = vec![common_dir.join(prefix)];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                dirs.push(path);
+            } else if let Ok(name) = path.strip_prefix(common_dir.join(prefix)) {
+                let parts: Vec<_> = name.iter().map(|part| part.to_string_lossy()).collect();
+                names.push(parts.join("/"));
+            }
+        }
+    }
+    let packed = fs::read_to_string(common_dir.join("packed-refs")).unwrap_or_default();
+    names.extend(
+        packed
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.starts_with('^'))
+            .filter_map(|line| line.split_once(' ')?.1.strip_prefix(prefix))
+            .map(str::to_string),
+    );
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// When the working tree at `path` last fetched, in seconds since the Unix epoch: when
+/// `FETCH_HEAD` was written, else when a remote-tracking branch was last updated, as only is
+/// for a clone that has not fetched since.
+pub fn last_fetch(path: &Path) -> Option<u64> {
+    let mtime = |path: &Path| {
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()?;
+        modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()
+            .map(|since| since.as_secs())
+    };
+    let (git_dir, common_dir) = (git_dir(path)?, common_dir(path)?);
+    let fetch_head = [git_dir.join("FETCH_HEAD"), common_dir.join("FETCH_HEAD")]
+        .iter()
+        .filter_map(|fetch_head| mtime(fetch_head))
+        .max();
+    if fetch_head.is_some() {
+        return fetch_head;
+    }
+    let mut newest = None;
+    let mut dirs = vec![common_dir.join("refs/remotes")];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                dirs.push(entry.path());
+            } else {
+                newest = newest.max(mtime(&entry.path()));
+            }
+        }
+    }
+    newest
+}
+
+/// What `HEAD` points at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Head {
+    /// A branch, by short name (e.g. `main`).
+    Branch(String),
+    /// A detached commit, by object id.
+    Detached(String),
+}
+
+impl Head {
+    /// The branch name or commit id.
+    pub fn name(&self) -> &str {
+        match self {
+            Head::Branch(name) | Head::Detached(name) => name,
+        }
+    }
+}
+
+/// Read `HEAD` of the working tree at `path`.
+pub fn head(path: &Path) -> Option<Head> {
+    let contents = fs::read_to_string(git_dir(path)?.join("HEAD")).ok()?;
+    let contents = contents.trim();
+    match contents.strip_prefix("ref:") {
+        Some(target) => {
+            let target = target.trim();
+            Some(Head::Branch(
+                target
+                    .strip_prefix("refs/heads/")
+                    .unwrap_or(target)
+                    .to_string(),
+            ))
+        }
+        None if !contents.is_empty() => Some(Head::Detached(contents.to_string())),
+        None => None,
+    }
+}
+
+/// The default branch of the primary remote of the working tree at `path`, as recorded in
+/// `refs/remotes/<remote>/HEAD` by `git clone` or `git remote set-head`. The remote is the one
+/// `checkout.defaultRemoteName` names, else `origin`; see [`gitconfig::read_primary_remote`].
+pub fn default_branch(path: &Path) -> Option<String> {
+    // Remote-tracking refs are shared by worktrees.
+    let common_dir = common_dir(path)?;
+    let remote = gitconfig::read_primary_remote(&common_dir.join("config"))
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| "origin".to_string());
+    let contents =
+        fs::read_to_string(common_dir.join(format!("refs/remotes/{}/HEAD", remote))).ok()?;
+    let target = contents.trim().strip_prefix("ref:")?.trim();
+    let prefix = format!("refs/remotes/{}/", remote);
+    let branch = target.strip_prefix(&prefix).unwrap_or(target);
+    Some(branch.to_string()).filter(|branch| !branch.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_head() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path().join("repo");
+        fs::create_dir_all(repo.join(".git"))?;
+        assert_eq!(head(&repo), None);
+
+        fs::write(repo.join(".git/HEAD"), "ref: refs/heads/feature/x\n")?;
+        assert_eq!(head(&repo), Some(Head::Branch("feature/x".to_string())));
+
+        // A worktree whose `.git` file points at the real git directory.
+        let worktree = temp_dir.path().join("worktree");
+        fs::create_dir_all(repo.join(".git/worktrees/wt"))?;
+        fs::create_dir_all(&worktree)?;
+        fs::write(
+            worktree.join(".git"),
+            format!("gitdir: {}\n", repo.join(".git/worktrees/wt").display()),
+        )?;
+        fs::write(repo.join(".git/worktrees/wt/HEAD"), "0123abcd\n")?;
+        assert_eq!(
+            head(&worktree),
+            Some(Head::Detached("0123abcd".to_string()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_branch() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path().join("repo");
+        fs::create_dir_all(repo.join(".git/refs/remotes/origin"))?;
+        assert_eq!(default_branch(&repo), None);
+
+        fs::write(
+            repo.join(".git/refs/remotes/origin/HEAD"),
+            "ref: refs/remotes/origin/trunk\n",
+        )?;
+        assert_eq!(default_branch(&repo).as_deref(), Some("trunk"));
+
+        let worktree = temp_dir.path().join("worktree");
+        fs::create_dir_all(repo.join(".git/worktrees/wt"))?;
+        fs::create_dir_all(&worktree)?;
+        fs::write(repo.join(".git/worktrees/wt/commondir"), "../..\n")?;
+        fs::write(
+            worktree.join(".git"),
+            format!("gitdir: {}\n", repo.join(".git/worktrees/wt").display()),
+        )?;
+        assert_eq!(default_branch(&worktree).as_deref(), Some("trunk"));
+
+        // The remote named by `checkout.defaultRemoteName` takes the place of `origin`.
+        fs::write(
+            repo.join(".git/config"),
+            "[checkout]\n\tdefaultRemoteName = upstream\n\
+             [remote \"upstream\"]\n\turl = https://example.com/repo.git\n",
+        )?;
+        assert_eq!(default_branch(&repo), None);
+        fs::create_dir_all(repo.join(".git/refs/remotes/upstream"))?;
+        fs::write(
+            repo.join(".git/refs/remotes/upstream/HEAD"),
+            "ref: refs/remotes/upstream/develop\n",
+        )?;
+        assert_eq!(default_branch(&repo).as_deref(), Some("develop"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_last_fetch() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        fs::create_dir_all(repo.join(".git/refs/remotes/origin"))?;
+        assert_eq!(last_fetch(repo), None);
+
+        let set_modified = |path: &Path, secs| {
+            fs::File::options()
+                .write(true)
+                .open(path)?
+                .set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs))
+        };
+        // A fresh clone has only its remote-tracking branches.
+        fs::write(repo.join(".git/refs/remotes/origin/main"), "0\n")?;
+        set_modified(&repo.join(".git/refs/remotes/origin/main"), 1_717_234_200)?;
+        assert_eq!(last_fetch(repo), Some(1_717_234_200));
+
+        fs::write(repo.join(".git/FETCH_HEAD"), "")?;
+        set_modified(&repo.join(".git/FETCH_HEAD"), 1_700_000_000)?;
+        assert_eq!(last_fetch(repo), Some(1_700_000_000));
+        Ok(())
+    }
+
+    #[test]
+    fn test_env_repository() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cwd = paths::canonicalize(temp_dir.path())?;
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+        assert_eq!(EnvRepository::resolve(env(&[]), &cwd), None);
+        assert_eq!(EnvRepository::resolve(env(&[("GIT_DIR", "")]), &cwd), None);
+
+        // With only GIT_DIR, the working tree is the current directory.
+        let repo = EnvRepository::resolve(env(&[("GIT_DIR", ".git")]), &cwd).unwrap();
+        assert_eq!(repo.git_dir, cwd.join(".git"));
+        assert_eq!(repo.work_tree, cwd);
+        assert!(repo.applies_to(&cwd));
+
+        // A separate Git directory whose config names the working tree.
+        fs::create_dir_all(cwd.join("dotfiles.git"))?;
+        fs::create_dir_all(cwd.join("home"))?;
+        fs::write(
+            cwd.join("dotfiles.git/config"),
+            "[core]\n\tbare = false\n\tworktree = ../home\n",
+        )?;
+        let repo = EnvRepository::resolve(
+            env(&[("GIT_DIR", "dotfiles.git"), ("GIT_COMMON_DIR", "common")]),
+            &cwd,
+        )
+        .unwrap();
+        assert_eq!(repo.work_tree, cwd.join("home"));
+        assert_eq!(repo.common_dir, Some(cwd.join("common")));
+        assert!(repo.applies_to(&cwd.join("home")) && !repo.applies_to(&cwd));
+
+        let repo = EnvRepository::resolve(
+            env(&[("GIT_DIR", "dotfiles.git"), ("GIT_WORK_TREE", ".")]),
+            &cwd,
+        )
+        .unwrap();
+        assert_eq!(repo.work_tree, cwd);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_git_dir() -> anyhow::Result<()> {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new()?;
+        let store = paths::canonicalize(temp_dir.path())?.join("store");
+        fs::create_dir_all(store.join("dotfiles.git"))?;
+        fs::write(store.join("dotfiles.git/HEAD"), "ref: refs/heads/main\n")?;
+        fs::create_dir_all(store.join("project"))?;
+
+        let repo = temp_dir.path().join("repo");
+        fs::create_dir_all(&repo)?;
+        symlink(store.join("dotfiles.git"), repo.join(".git"))?;
+        assert_eq!(git_dir(&repo), Some(store.join("dotfiles.git")));
+        assert_eq!(external_git_dir(&repo), Some(store.join("dotfiles.git")));
+        assert_eq!(head(&repo), Some(Head::Branch("main".to_string())));
+
+        // A link to a `.git` file is read as that file, relative to the working tree.
+        fs::write(store.join("gitfile"), "gitdir: ../store/dotfiles.git\n")?;
+        let linked = temp_dir.path().join("linked");
+        fs::create_dir_all(&linked)?;
+        symlink(store.join("gitfile"), linked.join(".git"))?;
+        assert_eq!(external_git_dir(&linked), Some(store.join("dotfiles.git")));
+
+        // Links to other directories, or to nothing, are not repositories.
+        for (name, target) in [
+            ("other", store.join("project")),
+            ("dangling", store.join("gone")),
+        ] {
+            let dir = temp_dir.path().join(name);
+            fs::create_dir_all(&dir)?;
+            symlink(target, dir.join(".git"))?;
+            assert_eq!(git_dir(&dir), None);
+        }
+
+        let plain = temp_dir.path().join("plain");
+        fs::create_dir_all(plain.join(".git"))?;
+        assert_eq!(external_git_dir(&plain), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,1028 @@
+//! Walking a directory tree for Git repositories, and optionally those of other version
+//! control systems; see [`ScannerBuilder::vcs`].
+//!
+//! [`Scanner`] holds the options for a walk and is configured through [`Scanner::builder`].
+//! [`Scanner::scan`] returns the whole [`GitDirectory`] tree, [`Scanner::iter`] yields
+//! repositories one at a time as they are found, and [`scan_with`] calls back for each one,
+//! letting the callback decide whether to descend further.
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use rayon::prelude::*;
+use tracing::{debug, debug_span, trace_span, warn};
+
+use crate::backend::Backend;
+use crate::ci;
+use crate::license;
+use crate::locale;
+use crate::model::ScanError;
+use crate::netfs::{self, NetworkFs};
+use crate::paths;
+use crate::project;
+use crate::refs;
+use crate::vcs::{Vcs, VcsKind};
+use crate::GitDirectory;
+
+/// A repository's version control system and remotes.
+type Detected = (&'static dyn Vcs, HashMap<String, String>);
+
+/// What to do with symbolic links to directories.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Symlinks {
+    /// Descend into linked directories as if they were ordinary ones.
+    #[default]
+    Follow,
+    /// Ignore linked directories.
+    Skip,
+}
+
+/// What a [`scan_with`] callback wants to happen next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visit {
+    /// Keep walking, including beneath this repository.
+    Continue,
+    /// Keep walking, but not beneath this repository.
+    Skip,
+    /// End the walk.
+    Stop,
+}
+
+/// Optional repository metadata to read during a scan; everything is off by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Metadata {
+    /// Record the checked-out branch, or commit if detached, in [`GitDirectory::head`].
+    pub head: bool,
+    /// Measure the annexed content of git-annex repositories, in [`Annex::size`].
+    ///
+    /// [`Annex::size`]: crate::annex::Annex::size
+    pub annex_size: bool,
+    /// Look for a license file and identify its license, in [`GitDirectory::license`].
+    pub license: bool,
+    /// Work out where `git push` sends each branch, in [`GitDirectory::push`].
+    pub push: bool,
+    /// Compare each branch with its upstream, in [`GitDirectory::tracking`].
+    pub tracking: bool,
+    /// Find when the first commit was made, in [`GitDirectory::first_commit`]; this walks each
+    /// repository's history.
+    pub age: bool,
+}
+
+/// A configured directory walk.
+#[derive(Clone, Debug)]
+pub struct Scanner {
+    recurse: bool,
+    max_depth: Option<usize>,
+    excludes: GlobSet,
+    prune: HashSet<OsString>,
+    symlinks: Symlinks,
+    threads: usize,
+    backend: Backend,
+    metadata: Metadata,
+    strict: bool,
+    one_file_system: bool,
+    network_fs: NetworkFs,
+    vcs: Vec<VcsKind>,
+    interrupt: Option<Arc<AtomicBool>>,
+}
+
+/// Builds a [`Scanner`]; see [`Scanner::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct ScannerBuilder {
+    recurse: bool,
+    max_depth: Option<usize>,
+    excludes: Vec<String>,
+    prune: HashSet<OsString>,
+    symlinks: Symlinks,
+    threads: usize,
+    backend: Backend,
+    metadata: Metadata,
+    strict: bool,
+    one_file_system: bool,
+    network_fs: NetworkFs,
+    vcs: Vec<VcsKind>,
+    interrupt: Option<Arc<AtomicBool>>,
+}
+
+impl ScannerBuilder {
+    /// Search all subdirectories rather than only the immediate children of the root.
+    pub fn recurse(mut self, recurse: bool) -> Self {
+        self.recurse = recurse;
+        self
+    }
+
+    /// When recursing, descend at most this many levels below the root.
+    pub fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Skip directories whose name, or path relative to the root, matches the glob `pattern`.
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.excludes.push(pattern.into());
+        self
+    }
+
+    /// Skip directories called exactly `name`.
+    pub fn prune(mut self, name: impl Into<OsString>) -> Self {
+        self.prune.insert(name.into());
+        self
+    }
+
+    /// How to treat symbolic links to directories.
+    pub fn symlinks(mut self, symlinks: Symlinks) -> Self {
+        self.symlinks = symlinks;
+        self
+    }
+
+    /// Scan subdirectories on this many threads; 0 or 1 scans on the calling thread.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// The backend used to read repository metadata.
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Optional metadata to read for each repository.
+    pub fn metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Skip directories on a different filesystem from the root, such as mount points or, on
+    /// Windows, junctions to another drive.
+    pub fn one_file_system(mut self, one_file_system: bool) -> Self {
+        self.one_file_system = one_file_system;
+        self
+    }
+
+    /// What to do on reaching a directory where a network filesystem is mounted; see
+    /// [`NetworkFs`].
+    pub fn network_fs(mut self, network_fs: NetworkFs) -> Self {
+        self.network_fs = network_fs;
+        self
+    }
+
+    /// Fail on the first path that cannot be read, instead of recording it in
+    /// [`GitDirectory::errors`] and carrying on.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// The version control systems whose repositories to detect, in order of precedence for
+    /// a directory that is a repository of several; Git alone by default.
+    ///
+    /// Jujutsu always comes first, since the `.git` of a colocated repository is its store.
+    pub fn vcs(mut self, vcs: impl IntoIterator<Item = VcsKind>) -> Self {
+        self.vcs = vcs.into_iter().collect();
+        self
+    }
+
+    /// Stop the walk once `flag` is set, as by a Ctrl-C handler, keeping what was found so
+    /// far. An interrupted scan records an [`ScanError::INTERRUPTED`] error at the root.
+    pub fn interrupt(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.interrupt = Some(flag);
+        self
+    }
+
+    /// Check the options and create the scanner. Fails if an exclude pattern is invalid.
+    pub fn build(self) -> Result<Scanner> {
+        let mut excludes = GlobSetBuilder::new();
+        for pattern in &self.excludes {
+            excludes.add(Glob::new(pattern).with_context(|| {
+                locale::text(
+                    "invalid-exclude",
+                    &[("pattern", &format_args!("{:?}", pattern))],
+                )
+            })?);
+        }
+        Ok(Scanner {
+            recurse: self.recurse,
+            max_depth: self.max_depth,
+            excludes: excludes.build()?,
+            prune: self.prune,
+            symlinks: self.symlinks,
+            threads: self.threads,
+            backend: self.backend,
+            metadata: self.metadata,
+            strict: self.strict,
+            one_file_system: self.one_file_system,
+            network_fs: self.network_fs,
+            vcs: if self.vcs.is_empty() {
+                vec![VcsKind::Git]
+            } else {
+                let mut vcs = self.vcs;
+                vcs.sort_by_key(|vcs| *vcs != VcsKind::Jj);
+                vcs
+            },
+            interrupt: self.interrupt,
+        })
+    }
+}
+
+impl Scanner {
+    /// A builder with the defaults: immediate children only, no excludes, following symlinks,
+    /// one thread, the native backend, no optional metadata and skipping unreadable paths.
+    pub fn builder() -> ScannerBuilder {
+        ScannerBuilder::default()
+    }
+
+    /// The deepest level below the root whose subdirectories are searched.
+    fn depth_limit(&self) -> usize {
+        if self.recurse {
+            self.max_depth.unwrap_or(usize::MAX)
+        } else {
+            1
+        }
+    }
+
+    /// Whether the walk has been interrupted; see [`ScannerBuilder::interrupt`].
+    fn interrupted(&self) -> bool {
+        self.interrupt
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+    }
+
+    /// Scan `dir` and return the tree of repositories under it.
+    ///
+    /// Unless the scanner is strict, paths that cannot be read are skipped and listed in the
+    /// root's [`GitDirectory::errors`].
+    pub fn scan(&self, dir: &Path) -> Result<GitDirectory> {
+        check_root(dir)?;
+        let root = paths::verbatim(dir);
+        let visited = Visited::new(&root);
+        let tree = if self.threads > 1 {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(self.threads)
+                .build()?;
+            pool.install(|| self.scan_dir(&root, &root, 0, &visited))?
+        } else {
+            self.scan_dir(&root, &root, 0, &visited)?
+        };
+        let mut tree = GitDirectory {
+            path: dir.to_path_buf(),
+            ..tree
+        };
+        if self.interrupted() {
+            tree.errors.push(ScanError::interrupted(dir));
+        }
+        Ok(tree)
+    }
+
+    /// Lazily walk `dir`, yielding each repository with its full path as it is found.
+    ///
+    /// Repositories are yielded without children; the walk continues beneath them.
+    pub fn iter<'a>(&'a self, dir: &Path) -> Iter<'a> {
+        let root = paths::verbatim(dir);
+        Iter {
+            scanner: self,
+            stack: vec![(root.clone(), 0)],
+            visited: Visited::new(&root),
+            root,
+        }
+    }
+
+    /// The repositories that a scan of `root` would find at or beneath `dir`, with their full
+    /// paths and no children, for updating the result of an earlier scan when `dir` changes.
+    ///
+    /// Empty if `dir` no longer exists, or the scan would not reach it.
+    pub fn rescan(&self, root: &Path, dir: &Path) -> Result<Vec<GitDirectory>> {
+        if !self.reaches(root, dir) || !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let depth = dir.strip_prefix(root)?.components().count();
+        let dir = paths::verbatim(dir);
+        let mut repos = Vec::new();
+        self.visit_dir(
+            &paths::verbatim(root),
+            &dir,
+            depth,
+            &Visited::new(&dir),
+            &mut |repo| {
+                repos.push(repo.clone());
+                Visit::Continue
+            },
+        )?;
+        Ok(repos)
+    }
+
+    /// Whether a scan of `root` would reach `dir`: it is beneath `root`, within the depth
+    /// limit, and neither it nor a directory above it is excluded.
+    pub fn reaches(&self, root: &Path, dir: &Path) -> bool {
+        let Ok(relative) = dir.strip_prefix(root) else {
+            return false;
+        };
+        let depth = relative.components().count();
+        depth <= self.depth_limit()
+            && dir
+                .ancestors()
+                .take(depth)
+                .all(|path| !self.excluded(root, path, path.file_name().unwrap_or_default()))
+    }
+
+    /// Read the repository at `path` alone, if it is one, with its full path and no children.
+    pub fn read_repo(&self, path: &Path) -> Result<Option<GitDirectory>> {
+        self.read(&paths::verbatim(path))
+    }
+
+    /// Depth-first walk calling `visit` for each repository; returns `false` once stopped.
+    fn visit_dir(
+        &self,
+        root: &Path,
+        dir: &Path,
+        depth: usize,
+        visited: &Visited,
+        visit: &mut impl FnMut(&GitDirectory) -> Visit,
+    ) -> Result<bool> {
+        let _span = debug_span!("visit_dir", path = %dir.display(), depth).entered();
+        if self.interrupted() {
+            return Ok(false);
+        }
+        let mut descend = depth < self.depth_limit();
+        let mut errors = Vec::new();
+        if let Some(repo) = self.recover(dir, self.read(dir), &mut errors)?.flatten() {
+            if !repo.remotes.is_empty() {
+                match visit(&repo) {
+                    Visit::Continue => {}
+                    Visit::Skip => descend = false,
+                    Visit::Stop => return Ok(false),
+                }
+            }
+        }
+        if descend {
+            let subdirs = self.subdirs(root, dir, visited, &mut errors);
+            let subdirs = self.recover(dir, subdirs, &mut errors)?;
+            for subdir in subdirs.unwrap_or_default() {
+                if !self.visit_dir(root, &subdir, depth + 1, visited, visit)? {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// The value of `result`, or what to do about its error: fail if the scanner is strict,
+    /// otherwise log it, add it to `errors` against `path`, and return `None`.
+    fn recover<T>(
+        &self,
+        path: &Path,
+        result: Result<T>,
+        errors: &mut Vec<ScanError>,
+    ) -> Result<Option<T>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if self.strict => Err(e.context(format!("Failed to read {}", path.display()))),
+            Err(e) => {
+                let error = ScanError::new(&paths::simplify(path), &e);
+                error.warn();
+                errors.push(error);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Read the repository at `path`, if it is one; the result's path is `path`.
+    fn read(&self, path: &Path) -> Result<Option<GitDirectory>> {
+        let _span = trace_span!("read", path = %path.display()).entered();
+        let Some((system, remotes)) = self.detect(path)? else {
+            return Ok(None);
+        };
+        let vcs = system.kind();
+        debug!(
+            path = %path.display(),
+            vcs = vcs.name(),
+            remotes = remotes.len(),
+            "found repository"
+        );
+        let head = self.metadata.head.then(|| system.head(path)).flatten();
+        let partial_clone = system.partial_clone(path)?;
+        Ok(Some(GitDirectory {
+            path: paths::simplify(path),
+            primary_remote: system.primary_remote(path, &remotes)?,
+            remotes,
+            urls: system.urls(path)?,
+            vcs,
+            git_dir: system.git_dir(path),
+            annex: system.annex(path, self.metadata.annex_size)?,
+            sparse_checkout: system.sparse_checkout(path)?,
+            partial_clone: partial_clone.is_some(),
+            partial_clone_filter: partial_clone.and_then(|partial| partial.filter),
+            in_progress: system.in_progress(path),
+            project_types: project::detect(path),
+            ci: ci::detect(path),
+            license: self
+                .metadata
+                .license
+                .then(|| license::detect(path))
+                .flatten(),
+            push: if self.metadata.push {
+                system.push(path)?
+            } else {
+                None
+            },
+            tracking: if self.metadata.tracking {
+                system.tracking(path)?
+            } else {
+                BTreeMap::new()
+            },
+            last_fetch: system.last_fetch(path),
+            remote_checks: BTreeMap::new(),
+            first_commit: if self.metadata.age {
+                system.first_commit(path)?
+            } else {
+                None
+            },
+            head,
+            plugins: BTreeMap::new(),
+            children: Vec::new(),
+            errors: Vec::new(),
+        }))
+    }
+
+    /// The first of the version control systems to detect that `path` is a repository of, and
+    /// the repository's remotes.
+    fn detect(&self, path: &Path) -> Result<Option<Detected>> {
+        for &vcs in &self.vcs {
+            let system = vcs.system(self.backend);
+            if system.detect(path) {
+                return Ok(Some((system, system.remotes(path)?)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The subdirectories of `dir` to search, in directory order, marking them in `visited` and
+    /// leaving out those the walk has already entered by another path. Unless the scanner is
+    /// strict, an entry that cannot be read is added to `errors` and left out.
+    fn subdirs(
+        &self,
+        root: &Path,
+        dir: &Path,
+        visited: &Visited,
+        errors: &mut Vec<ScanError>,
+    ) -> Result<Vec<PathBuf>> {
+        let mut subdirs = Vec::new();
+        let filesystem = if self.one_file_system {
+            Some(paths::filesystem(root).context("Failed to read root directory")?)
+        } else {
+            None
+        };
+        // A Git directory holds objects and refs, never working trees to search, so neither
+        // `.git` nor a Git directory kept elsewhere in the repository is entered.
+        let git_dir = refs::external_git_dir(dir);
+        for entry in fs::read_dir(dir).context("Failed to read directory")? {
+            let Some(entry) =
+                self.recover(dir, entry.context("Failed to read directory entry"), errors)?
+            else {
+                continue;
+            };
+            let path = entry.path();
+            let name = entry.file_name();
+            if name == ".git" || self.excluded(root, &path, &name) {
+                continue;
+            }
+            // Decided before anything reads the directory, which may hang.
+            if !self.network_fs_allows(&path, errors)? || !path.is_dir() {
+                continue;
+            }
+            if git_dir.is_some() && paths::canonicalize(&path).ok() == git_dir {
+                continue;
+            }
+            if self.symlinks == Symlinks::Skip {
+                let file_type = entry.file_type().context("Failed to read file type");
+                match self.recover(&path, file_type, errors)? {
+                    Some(file_type) if !file_type.is_symlink() => {}
+                    _ => continue,
+                }
+            }
+            if let Some(filesystem) = &filesystem {
+                if paths::filesystem(&path).ok().as_ref() != Some(filesystem) {
+                    continue;
+                }
+            }
+            if !visited.insert(&path) {
+                debug!(path = %path.display(), "skipping directory already visited");
+                continue;
+            }
+            subdirs.push(path);
+        }
+        Ok(subdirs)
+    }
+
+    /// Whether the walk may enter `path`, if a network filesystem is mounted there. Warns, and
+    /// unless the scanner is strict records an error and refuses if it does not respond in
+    /// time, or refuses quietly, as the scanner's [`NetworkFs`] setting says.
+    fn network_fs_allows(&self, path: &Path, errors: &mut Vec<ScanError>) -> Result<bool> {
+        let Some(fstype) = netfs::mount_type(path) else {
+            return Ok(true);
+        };
+        match self.network_fs {
+            NetworkFs::Scan => Ok(true),
+            NetworkFs::Skip => {
+                debug!(path = %path.display(), fstype, "skipping network filesystem");
+                Ok(false)
+            }
+            NetworkFs::Warn => {
+                warn!(
+                    code = "network_fs",
+                    path = %path.display(),
+                    fstype,
+                    "scanning network filesystem"
+                );
+                if netfs::responds(path, netfs::TIMEOUT) {
+                    return Ok(true);
+                }
+                let error =
+                    anyhow::Error::new(io::Error::from(io::ErrorKind::TimedOut)).context(format!(
+                        "{} filesystem did not respond within {}s",
+                        fstype,
+                        netfs::TIMEOUT.as_secs()
+                    ));
+                self.recover(path, Err::<(), _>(error), errors)?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Whether a walk from `root` leaves out the directory `path`, named `name`.
+    fn excluded(&self, root: &Path, path: &Path, name: &OsStr) -> bool {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        self.prune.contains(name)
+            || self.excludes.is_match(name)
+            || self.excludes.is_match(relative)
+    }
+
+    fn scan_dir(
+        &self,
+        root: &Path,
+        dir: &Path,
+        depth: usize,
+        visited: &Visited,
+    ) -> Result<GitDirectory> {
+        let _span = debug_span!("scan_dir", path = %dir.display(), depth).entered();
+        let mut errors = Vec::new();
+        let repo = self.recover(dir, self.read(dir), &mut errors)?.flatten();
+        let mut current_dir = repo.unwrap_or_else(|| GitDirectory::directory(dir.to_path_buf()));
+        if depth < self.depth_limit() && !self.interrupted() {
+            let subdirs = self.subdirs(root, dir, visited, &mut errors);
+            let subdirs = self.recover(dir, subdirs, &mut errors)?.unwrap_or_default();
+            let children: Vec<_> = if self.threads > 1 {
+                subdirs
+                    .par_iter()
+                    .map(|path| self.scan_child(root, dir, path, depth + 1, visited))
+                    .collect::<Result<_>>()?
+            } else {
+                subdirs
+                    .iter()
+                    .map(|path| self.scan_child(root, dir, path, depth + 1, visited))
+                    .collect::<Result<_>>()?
+            };
+            for (child, mut child_errors) in children {
+                current_dir.children.extend(child);
+                errors.append(&mut child_errors);
+            }
+        }
+        current_dir.errors = errors;
+        Ok(current_dir)
+    }
+
+    /// Scan the subdirectory `path` of `parent`, returning it relative to `parent` if it is or
+    /// contains a repository, and the errors from beneath it.
+    fn scan_child(
+        &self,
+        root: &Path,
+        parent: &Path,
+        path: &Path,
+        depth: usize,
+        visited: &Visited,
+    ) -> Result<(Option<GitDirectory>, Vec<ScanError>)> {
+        let mut errors = Vec::new();
+        if self.interrupted() {
+            return Ok((None, errors));
+        }
+        let child_dir = if depth >= self.depth_limit() {
+            // At the limit a directory is kept if it is a repository, even with no remotes.
+            self.recover(path, self.read(path), &mut errors)?.flatten()
+        } else {
+            let mut child_dir = self.scan_dir(root, path, depth, visited)?;
+            errors = std::mem::take(&mut child_dir.errors);
+            Some(child_dir).filter(|d| !d.children.is_empty() || !d.remotes.is_empty())
+        };
+        let child_dir = match child_dir {
+            Some(child_dir) => Some(GitDirectory {
+                path: path.strip_prefix(parent)?.to_path_buf(),
+                ..child_dir
+            }),
+            None => None,
+        };
+        Ok((child_dir, errors))
+    }
+}
+
+/// The directories a walk has entered, so that one reached again, through a bind mount, a
+/// firmlink or a symlink cycle, is not walked twice. Directories are told apart by
+/// [`paths::file_id`], whether or not the walk follows symlinks.
+struct Visited(Mutex<HashSet<paths::FileId>>);
+
+impl Visited {
+    /// A walk that has entered `root`.
+    fn new(root: &Path) -> Self {
+        let visited = Visited(Mutex::default());
+        visited.insert(root);
+        visited
+    }
+
+    /// Record that the walk enters `dir`; `false` if it already has. A directory that cannot be
+    /// identified is entered, so that reading it reports why.
+    fn insert(&self, dir: &Path) -> bool {
+        let Ok(id) = paths::file_id(dir) else {
+            return true;
+        };
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).insert(id)
+    }
+}
+
+/// Fail unless `dir` can be listed; only paths beneath the root are skipped on error.
+///
+/// The error is a [`ScanError`], so callers can report the path and kind of failure.
+fn check_root(dir: &Path) -> Result<()> {
+    if let Err(e) = fs::read_dir(dir) {
+        let e = anyhow::Error::new(e).context("Failed to read directory");
+        return Err(ScanError::new(dir, &e).into());
+    }
+    Ok(())
+}
+
+/// An iterator over the repositories under a directory; see [`Scanner::iter`].
+pub struct Iter<'a> {
+    scanner: &'a Scanner,
+    root: PathBuf,
+    stack: Vec<(PathBuf, usize)>,
+    visited: Visited,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = Result<GitDirectory>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((dir, depth)) = self.stack.pop() {
+            if depth < self.scanner.depth_limit() {
+                // Unreadable entries are logged; the iterator has nowhere to keep them.
+                let subdirs =
+                    self.scanner
+                        .subdirs(&self.root, &dir, &self.visited, &mut Vec::new());
+                match subdirs {
+                    Ok(subdirs) => self
+                        .stack
+                        .extend(subdirs.into_iter().rev().map(|path| (path, depth + 1))),
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            match self.scanner.read(&dir) {
+                Ok(Some(repo)) if !repo.remotes.is_empty() => return Some(Ok(repo)),
+                Ok(_) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+}
+
+/// Walk `dir` with `scanner`, calling `visit` for each repository as it is found.
+///
+/// Repositories are visited depth-first, in directory order, with their full path and no
+/// children; no tree is built. The scanner's thread count is ignored. Unless the scanner is
+/// strict, paths that cannot be read are logged and skipped.
+pub fn scan_with(
+    dir: &Path,
+    scanner: &Scanner,
+    mut visit: impl FnMut(&GitDirectory) -> Visit,
+) -> Result<()> {
+    check_root(dir)?;
+    let root = paths::verbatim(dir);
+    scanner.visit_dir(&root, &root, 0, &Visited::new(&root), &mut visit)?;
+    Ok(())
+}
+
+/// Search for .git/config files in the given directory, optionally recursively.
+/// * `dir` - The directory to search in.
+/// * `recurse` - Whether to recursively search subdirectories.
+/// * `backend` - The backend used to read repository metadata.
+///
+/// A shorthand for a [`Scanner`] with only these options set.
+pub fn find_git_configs(dir: &Path, recurse: bool, backend: Backend) -> Result<GitDirectory> {
+    Scanner::builder()
+        .recurse(recurse)
+        .backend(backend)
+        .build()?
+        .scan(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::create_git_config;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_git_config_in_subdir() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_git_config(
+            temp_dir.path(),
+            "[remote \"origin\"]\n    url = https://github.com/user/repo.git\n",
+        )?;
+
+        let sub_dir = temp_dir.path().join("subdir");
+        std::fs::create_dir(&sub_dir)?;
+        create_git_config(
+            &sub_dir,
+            "[remote \"origin\"]\n    url = https://github.com/user/subrepo.git\n",
+        )?;
+
+        let result = find_git_configs(temp_dir.path(), true, Backend::Native)?;
+        println!("{:?}", result);
+        assert_eq!(result.remotes.len(), 1);
+        assert_eq!(
+            result.remotes.get("origin"),
+            Some(&"https://github.com/user/repo.git".to_string())
+        );
+        assert_eq!(result.children.len(), 1);
+
+        assert_eq!(result.children[0].remotes.len(), 1);
+        assert_eq!(
+            result.children[0].remotes.get("origin"),
+            Some(&"https://github.com/user/subrepo.git".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scanner_options() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let url = "[remote \"origin\"]\n    url = https://github.com/user/repo.git\n";
+        for path in ["a", "a/b", "a/b/c", "node_modules/d"] {
+            create_git_config(&temp_dir.path().join(path), url)?;
+        }
+        std::fs::write(
+            temp_dir.path().join("a/b/.git/HEAD"),
+            "ref: refs/heads/main\n",
+        )?;
+
+        let scanner = Scanner::builder()
+            .recurse(true)
+            .max_depth(Some(2))
+            .prune("node_modules")
+            .threads(2)
+            .one_file_system(true)
+            .metadata(Metadata {
+                head: true,
+                ..Metadata::default()
+            })
+            .build()?;
+        let tree = scanner.scan(temp_dir.path())?;
+        let mut paths: Vec<_> = tree.repos().into_iter().map(|(p, _)| p).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![temp_dir.path().join("a"), temp_dir.path().join("a/b")]
+        );
+        assert_eq!(tree.children[0].children[0].head.as_deref(), Some("main"));
+
+        let mut found: Vec<_> = scanner
+            .iter(temp_dir.path())
+            .map(|repo| repo.map(|r| r.path))
+            .collect::<Result<_>>()?;
+        found.sort();
+        assert_eq!(found, paths);
+
+        assert!(Scanner::builder().exclude("a[").build().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_git_dirs_not_searched() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let url = "[remote \"origin\"]\n    url = https://github.com/user/repo.git\n";
+        let repo = temp_dir.path().join("repo");
+        create_git_config(&repo, url)?;
+        // Test fixtures kept inside the Git directory look like repositories but are not.
+        create_git_config(&repo.join(".git/lg-fixtures/fake"), url)?;
+
+        // A Git directory kept inside the working tree it belongs to.
+        let separate = temp_dir.path().join("separate");
+        create_git_config(&separate.join("store/fake"), url)?;
+        std::fs::write(separate.join("store/config"), url)?;
+        std::fs::write(separate.join(".git"), "gitdir: store\n")?;
+
+        let scanner = Scanner::builder().recurse(true).build()?;
+        let tree = scanner.scan(temp_dir.path())?;
+        let mut paths: Vec<_> = tree.repos().into_iter().map(|(p, _)| p).collect();
+        paths.sort();
+        assert_eq!(paths, vec![repo, separate]);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_loops() -> Result<()> {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new()?;
+        let url = "[remote \"origin\"]\n    url = https://github.com/user/repo.git\n";
+        let repo = temp_dir.path().join("a/repo");
+        create_git_config(&repo, url)?;
+        // A link back to an ancestor, and a second path to the repository.
+        symlink(temp_dir.path(), temp_dir.path().join("a/cycle"))?;
+        symlink(&repo, temp_dir.path().join("b"))?;
+
+        for threads in [1, 2] {
+            let scanner = Scanner::builder().recurse(true).threads(threads).build()?;
+            let tree = scanner.scan(temp_dir.path())?;
+            assert_eq!(tree.repos().len(), 1);
+            assert_eq!(scanner.iter(temp_dir.path()).count(), 1);
+        }
+        let scanner = Scanner::builder().recurse(true).build()?;
+        let mut found = 0;
+        scan_with(temp_dir.path(), &scanner, |_| {
+            found += 1;
+            Visit::Continue
+        })?;
+        assert_eq!(found, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_interrupt() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let url = "[remote \"origin\"]\n    url = https://github.com/user/repo.git\n";
+        create_git_config(&temp_dir.path().join("a"), url)?;
+
+        let flag = Arc::new(AtomicBool::new(false));
+        let scanner = Scanner::builder().interrupt(flag.clone()).build()?;
+        let tree = scanner.scan(temp_dir.path())?;
+        assert_eq!(tree.repos().len(), 1);
+        assert!(tree.errors.is_empty());
+
+        // Interrupted, the scan stops and says so; the output is marked partial.
+        flag.store(true, Ordering::Relaxed);
+        let tree = scanner.scan(temp_dir.path())?;
+        assert!(tree.repos().is_empty());
+        assert_eq!(tree.errors, [ScanError::interrupted(temp_dir.path())]);
+        let mut out = Vec::new();
+        crate::output::write(
+            &tree,
+            crate::output::OutputFormat::Json,
+            &[],
+            Default::default(),
+            true,
+            &mut out,
+        )?;
+        assert!(String::from_utf8(out)?.contains("\"partial\":true"));
+        let mut found = 0;
+        scan_with(temp_dir.path(), &scanner, |_| {
+            found += 1;
+            Visit::Continue
+        })?;
+        assert_eq!(found, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_partial_failures() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let url = "[remote \"origin\"]\n    url = https://github.com/user/repo.git\n";
+        create_git_config(&temp_dir.path().join("good"), url)?;
+        let bad = temp_dir.path().join("nested/bad");
+        std::fs::create_dir_all(bad.join(".git"))?;
+        std::fs::write(bad.join(".git/config"), b"[remote \"origin\"]\n\xff\n")?;
+
+        let scanner = Scanner::builder().recurse(true).build()?;
+        let tree = scanner.scan(temp_dir.path())?;
+        let paths: Vec<_> = tree.repos().into_iter().map(|(p, _)| p).collect();
+        assert_eq!(paths, vec![temp_dir.path().join("good")]);
+        assert_eq!(tree.errors.len(), 1);
+        assert_eq!(tree.errors[0].path, bad);
+
+        let strict = Scanner::builder().recurse(true).strict(true).build()?;
+        assert!(strict.scan(temp_dir.path()).is_err());
+        assert!(scanner.scan(&temp_dir.path().join("missing")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_vcs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let url = "[remote \"origin\"]\n    url = https://github.com/user/repo.git\n";
+        create_git_config(&temp_dir.path().join("git"), url)?;
+        for path in ["hg", "both"] {
+            std::fs::create_dir_all(temp_dir.path().join(path).join(".hg"))?;
+            std::fs::write(
+                temp_dir.path().join(path).join(".hg/hgrc"),
+                "[paths]\ndefault = https://hg.example.com/repo\n",
+            )?;
+        }
+        create_git_config(&temp_dir.path().join("both"), url)?;
+
+        let found = |scanner: Scanner| -> Result<Vec<(PathBuf, VcsKind)>> {
+            let tree = scanner.scan(temp_dir.path())?;
+            let mut found: Vec<_> = tree
+                .repos()
+                .into_iter()
+                .map(|(path, repo)| (path.strip_prefix(temp_dir.path()).unwrap().into(), repo.vcs))
+                .collect();
+            found.sort();
+            Ok(found)
+        };
+        assert_eq!(
+            found(Scanner::builder().build()?)?,
+            vec![("both".into(), VcsKind::Git), ("git".into(), VcsKind::Git)]
+        );
+        assert_eq!(
+            found(
+                Scanner::builder()
+                    .vcs([VcsKind::Hg, VcsKind::Git])
+                    .build()?
+            )?,
+            vec![
+                ("both".into(), VcsKind::Hg),
+                ("git".into(), VcsKind::Git),
+                ("hg".into(), VcsKind::Hg)
+            ]
+        );
+        let hg = Scanner::builder()
+            .vcs([VcsKind::Hg])
+            .metadata(Metadata {
+                head: true,
+                ..Metadata::default()
+            })
+            .build()?
+            .read_repo(&temp_dir.path().join("hg"))?
+            .unwrap();
+        assert_eq!(
+            hg.primary_remote(),
+            Some(("default", "https://hg.example.com/repo"))
+        );
+        assert_eq!(hg.head.as_deref(), Some("default"));
+
+        // A colocated Jujutsu repository is jj's, whatever the order.
+        std::fs::create_dir_all(temp_dir.path().join("git/.jj/repo/store"))?;
+        std::fs::write(
+            temp_dir.path().join("git/.jj/repo/store/git_target"),
+            "../../../.git",
+        )?;
+        let jj = Scanner::builder()
+            .vcs([VcsKind::Git, VcsKind::Jj])
+            .build()?
+            .read_repo(&temp_dir.path().join("git"))?
+            .unwrap();
+        assert_eq!(jj.vcs, VcsKind::Jj);
+        assert_eq!(jj.remotes.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_with() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let url = "[remote \"origin\"]\n    url = https://github.com/user/repo.git\n";
+        for path in ["a", "a/b", "c", "d"] {
+            create_git_config(&temp_dir.path().join(path), url)?;
+        }
+        let scanner = Scanner::builder().recurse(true).build()?;
+
+        let mut seen = Vec::new();
+        scan_with(temp_dir.path(), &scanner, |repo| {
+            seen.push(repo.path.clone());
+            if repo.path.ends_with("a") {
+                Visit::Skip
+            } else {
+                Visit::Continue
+            }
+        })?;
+        seen.sort();
+        let expected: Vec<_> = ["a", "c", "d"]
+            .iter()
+            .map(|p| temp_dir.path().join(p))
+            .collect();
+        assert_eq!(seen, expected);
+
+        let mut count = 0;
+        scan_with(temp_dir.path(), &scanner, |_| {
+            count += 1;
+            Visit::Stop
+        })?;
+        assert_eq!(count, 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+//! Shell integration: quoting, running command templates, and jump functions.
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+
+use crate::locale;
+
+/// Quote `s` for a POSIX shell.
+pub fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Split `s` into words as a POSIX shell would, honouring quotes and backslashes.
+///
+/// No expansions are performed. Fails if a quote is not closed.
+pub fn split(s: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => bail!(unterminated(s)),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some(c) => word.extend(['\\', c]),
+                            None => bail!(unterminated(s)),
+                        },
+                        Some(c) => word.push(c),
+                        None => bail!(unterminated(s)),
+                    }
+                }
+            }
+            '\\' => word.get_or_insert_with(String::new).extend(chars.next()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// The error for `s`, in which a quote is not closed.
+fn unterminated(s: &str) -> String {
+    locale::text("unterminated-quote", &[("text", &format_args!("{:?}", s))])
+}
+
+/// A command that runs `script` with the platform shell (`sh -c`, or `cmd /C` on Windows).
+pub fn command(script: &str) -> Command {
+    let mut command = if cfg!(windows) {
+        Command::new("cmd")
+    } else {
+        Command::new("sh")
+    };
+    command
+        .arg(if cfg!(windows) { "/C" } else { "-c" })
+        .arg(script);
+    command
+}
+
+/// Substitute the quoted `path` for `{path}` in `template`, or append it if there is none.
+pub fn expand_path(template: &str, path: &Path) -> String {
+    let quoted = quote(&path.to_string_lossy());
+    if template.contains("{path}") {
+        template.replace("{path}", &quoted)
+    } else {
+        format!("{} {}", template, quoted)
+    }
+}
+
+/// The user's editor command: `$LG_EDITOR`, `$VISUAL` or `$EDITOR`, falling back to `vi`.
+pub fn editor() -> String {
+    ["LG_EDITOR", "VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// The command that opens a URL in the user's browser: `$BROWSER`, falling back to the
+/// platform's opener (`open` on macOS, `start` on Windows, otherwise `xdg-open`).
+pub fn browser() -> String {
+    std::env::var("BROWSER")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(target_os = "macos") {
+                "open".to_string()
+            } else if cfg!(windows) {
+                "start \"\"".to_string()
+            } else {
+                "xdg-open".to_string()
+            }
+        })
+}
+
+/// A shell `lg init` can emit a jump function for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// The shell code defining a function `name` that changes to the best match for its arguments.
+pub fn init(shell: Shell, name: &str) -> String {
+    match shell {
+        Shell::Bash | Shell::Zsh => format!(
+            r#"{name}() {{
+    local dir
+    dir="$(command lg jump -- "$@")" && builtin cd -- "$dir"
+}}
+"#
+        ),
+        Shell::Fish => format!(
+            r#"function {name} --description 'Jump to a repository known to lg'
+    set -l dir (command lg jump -- $argv); and builtin cd -- $dir
+end
+"#
+        ),
+    }
+}
+
+/// The subcommands of `lg` whose arguments are completed with repository names.
+const REPO_SUBCOMMANDS: &[&str] = &["jump", "edit", "open", "tmux"];
+
+/// The shell code completing the arguments of `lg jump`, `lg edit`, `lg open`, `lg tmux` and the
+/// jump function `name` with the names of indexed repositories, which `lg complete-repos` lists.
+pub fn completion(shell: Shell, name: &str) -> String {
+    let subcommands = REPO_SUBCOMMANDS.join(" ");
+    match shell {
+        Shell::Bash => format!(
+            r#"_lg_complete_repos() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    if [[ $1 == lg ]]; then
+        (( COMP_CWORD >= 2 )) || return
+        case " {subcommands} " in *" ${{COMP_WORDS[1]}} "*) ;; *) return ;; esac
+    fi
+    [[ $cur == -* ]] && return
+    local IFS=$'\n'
+    COMPREPLY=($(command lg complete-repos -- "$cur" 2>/dev/null))
+}}
+complete -o default -F _lg_complete_repos lg
+complete -F _lg_complete_repos {name}
+"#
+        ),
+        Shell::Zsh => format!(
+            r#"_lg_complete_repos() {{
+    if [[ $words[1] == lg ]]; then
+        if (( CURRENT <= 2 )) || [[ " {subcommands} " != *" $words[2] "* ]]; then
+            _files
+            return
+        fi
+    fi
+    local -a repos
+    repos=(${{(f)"$(command lg complete-repos -- "$PREFIX" 2>/dev/null)"}})
+    compadd -a repos
+}}
+(( $+functions[compdef] )) && compdef _lg_complete_repos lg {name}
+"#
+        ),
+        Shell::Fish => {
+            let repos = "'(command lg complete-repos -- (commandline -ct) 2>/dev/null)'";
+            format!(
+                "complete -c lg -n '__fish_seen_subcommand_from {subcommands}' -f -a {repos}\n\
+                 complete -c {name} -f -a {repos}\n"
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() -> Result<()> {
+        assert_eq!(
+            split(r#"-t  --exclude 'a b' "c \"d\"" e\ f ''"#)?,
+            vec!["-t", "--exclude", "a b", "c \"d\"", "e f", ""]
+        );
+        assert!(split("'open").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_path() {
+        let path = Path::new("/src/it's");
+        assert_eq!(expand_path("code", path), r"code '/src/it'\''s'");
+        assert_eq!(
+            expand_path("tmux new -c {path}", path),
+            r"tmux new -c '/src/it'\''s'"
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Sparse checkouts, whose working trees hold only some of the repository's files, as
+//! monorepos are often checked out.
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{gitconfig, refs};
+
+/// What a sparse checkout includes.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SparseCheckout {
+    /// Whether it is in cone mode, where whole directories are checked out.
+    #[serde(default)]
+    pub cone: bool,
+    /// What is checked out, as `git sparse-checkout list` prints it: the directories in cone
+    /// mode, otherwise the patterns.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
+}
+
+/// Read the sparse checkout of the Git working tree at `path`, if `core.sparseCheckout` is
+/// set in the repository's config or the worktree's `config.worktree`.
+pub fn read(path: &Path) -> Result<Option<SparseCheckout>> {
+    let Some(git_dir) = refs::git_dir(path) else {
+        return Ok(None);
+    };
+    let entries = refs::config(path)?;
+    let enabled = |key| {
+        entries
+            .iter()
+            .rev()
+            .find(|entry| entry.is("core", key) && entry.subsection.is_none())
+            .is_some_and(gitconfig::Entry::is_true)
+    };
+    if !enabled("sparsecheckout") {
+        return Ok(None);
+    }
+    let cone = enabled("sparsecheckoutcone");
+    let contents =
+        fs::read_to_string(git_dir.join("info").join("sparse-checkout")).unwrap_or_default();
+    let patterns = contents
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    let patterns = if cone {
+        cone_dirs(patterns)
+    } else {
+        patterns.map(str::to_string).collect()
+    };
+    Ok(Some(SparseCheckout { cone, patterns }))
+}
+
+/// The directories checked out by cone mode patterns. Each is written as `/dir/`, and each of
+/// its parents as `/parent/` followed by `!/parent/*/`, which excludes the parent's other
+/// subdirectories.
+fn cone_dirs<'a>(patterns: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let patterns: Vec<_> = patterns.collect();
+    patterns
+        .iter()
+        .filter_map(|pattern| pattern.strip_prefix('/')?.strip_suffix('/'))
+        .filter(|dir| !dir.is_empty() && !dir.ends_with('*'))
+        .filter(|dir| !patterns.contains(&format!("!/{}/*/", dir).as_str()))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        fs::create_dir_all(repo.join(".git/info"))?;
+        fs::write(repo.join(".git/config"), "[core]\n\tbare = false\n")?;
+        fs::write(
+            repo.join(".git/info/sparse-checkout"),
+            "/*\n!/*/\n/services/\n!/services/*/\n/services/api/\n/docs/\n",
+        )?;
+        assert_eq!(read(repo)?, None);
+
+        fs::write(
+            repo.join(".git/config"),
+            "[core]\n\tsparseCheckout = true\n\tsparseCheckoutCone = true\n",
+        )?;
+        assert_eq!(
+            read(repo)?,
+            Some(SparseCheckout {
+                cone: true,
+                patterns: vec!["services/api".to_string(), "docs".to_string()],
+            })
+        );
+
+        // `git sparse-checkout` sets it in `config.worktree` when worktrees have their own.
+        fs::write(
+            repo.join(".git/config"),
+            "[extensions]\n\tworktreeConfig = true\n",
+        )?;
+        fs::write(
+            repo.join(".git/config.worktree"),
+            "[core]\n\tsparseCheckout\n",
+        )?;
+        fs::write(
+            repo.join(".git/info/sparse-checkout"),
+            "# generated\n/*.md\n!/vendor/\n",
+        )?;
+        assert_eq!(
+            read(repo)?,
+            Some(SparseCheckout {
+                cone: false,
+                patterns: vec!["/*.md".to_string(), "!/vendor/".to_string()],
+            })
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+//! The stash entries of a repository, read from `git stash list`, for finding work stashed and
+//! forgotten.
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+
+use crate::refs;
+
+/// A stash entry.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Stash {
+    /// The entry's name, e.g. `stash@{0}`.
+    pub name: String,
+    /// When the entry was made, in seconds since the Unix epoch.
+    pub time: u64,
+    /// The entry's message, e.g. `WIP on main: 1a2b3c4 Subject`.
+    pub message: String,
+}
+
+/// The format of the `git stash list` output read by [`parse`].
+const FORMAT: &str = "--format=%gd%x00%ct%x00%gs";
+
+/// Read the stash entries of the Git working tree at `path`, newest first.
+pub fn list(path: &Path) -> Result<Vec<Stash>> {
+    let output = refs::git_command(path)
+        .args(["stash", "list", FORMAT])
+        .output()
+        .context("Failed to run git stash list")?;
+    if !output.status.success() {
+        bail!(
+            "git stash list failed in {:?}: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(parse(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse the output of `git stash list` with [`FORMAT`]: per entry, its name, commit time and
+/// message.
+pub fn parse(output: &str) -> Vec<Stash> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\0');
+            let (Some(name), Some(time), Some(message)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return None;
+            };
+            Some(Stash {
+                name: name.to_string(),
+                time: time.parse().ok()?,
+                message: message.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse(
+                "stash@{0}\x001700000000\x00On main: before rebase\n\
+                 stash@{1}\x001500000000\x00WIP on fix: 1a2b3c4 Fix it\n"
+            ),
+            [
+                Stash {
+                    name: "stash@{0}".to_string(),
+                    time: 1_700_000_000,
+                    message: "On main: before rebase".to_string(),
+                },
+                Stash {
+                    name: "stash@{1}".to_string(),
+                    time: 1_500_000_000,
+                    message: "WIP on fix: 1a2b3c4 Fix it".to_string(),
+                },
+            ]
+        );
+        assert_eq!(parse(""), []);
+    }
+}
@@ -0,0 +1,324 @@
+//! Working tree status, read from `git status --porcelain=v2 --branch`, and how each branch
+//! compares with its upstream, read from `git for-each-ref`.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::locale;
+use crate::operation::{self, Operation};
+use crate::refs;
+
+/// A summary of a repository's branch and working tree state.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Status {
+    /// The checked-out branch, or `None` when `HEAD` is detached.
+    pub branch: Option<String>,
+    /// The upstream of the branch, e.g. `origin/main`.
+    pub upstream: Option<String>,
+    /// Commits on the branch that are not on its upstream.
+    pub ahead: u32,
+    /// Commits on the upstream that are not on the branch.
+    pub behind: u32,
+    /// Paths with changes in the index.
+    pub staged: usize,
+    /// Paths with changes in the working tree that are not staged.
+    pub modified: usize,
+    /// Paths with unresolved merge conflicts.
+    pub conflicted: usize,
+    /// Untracked paths.
+    pub untracked: usize,
+    /// A merge, rebase, cherry-pick or revert left in progress.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_progress: Option<Operation>,
+}
+
+impl Status {
+    /// Read the status of the working tree at `path` by running `git status`.
+    pub fn read(path: &Path) -> Result<Self> {
+        let output = refs::git_command(path)
+            .args(["status", "--porcelain=v2", "--branch"])
+            .output()
+            .context("Failed to run git status")?;
+        if !output.status.success() {
+            bail!(
+                "git status failed in {:?}: {}",
+                path,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(Status {
+            in_progress: operation::read(path),
+            ..Self::parse(&String::from_utf8_lossy(&output.stdout))
+        })
+    }
+
+    /// Parse the output of `git status --porcelain=v2 --branch`, which does not say what
+    /// operation is in progress.
+    pub fn parse(porcelain: &str) -> Self {
+        let mut status = Status::default();
+        for line in porcelain.lines() {
+            if let Some(header) = line.strip_prefix("# ") {
+                let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+                match key {
+                    "branch.head" if value != "(detached)" => {
+                        status.branch = Some(value.to_string())
+                    }
+                    "branch.upstream" => status.upstream = Some(value.to_string()),
+                    "branch.ab" => {
+                        for count in value.split_whitespace() {
+                            if let Some(ahead) = count.strip_prefix('+') {
+                                status.ahead = ahead.parse().unwrap_or(0);
+                            } else if let Some(behind) = count.strip_prefix('-') {
+                                status.behind = behind.parse().unwrap_or(0);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            let mut fields = line.splitn(3, ' ');
+            match (fields.next(), fields.next()) {
+                (Some("1" | "2"), Some(xy)) => {
+                    let mut xy = xy.chars();
+                    if xy.next().is_some_and(|x| x != '.') {
+                        status.staged += 1;
+                    }
+                    if xy.next().is_some_and(|y| y != '.') {
+                        status.modified += 1;
+                    }
+                }
+                (Some("u"), _) => status.conflicted += 1,
+                (Some("?"), _) => status.untracked += 1,
+                _ => {}
+            }
+        }
+        status
+    }
+
+    /// Whether the working tree or index has any changes.
+    pub fn is_dirty(&self) -> bool {
+        self.staged + self.modified + self.conflicted + self.untracked > 0
+    }
+}
+
+/// How a local branch compares with the remote branch it tracks.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tracking {
+    /// The remote of the upstream, or `.` for a local branch.
+    pub remote: String,
+    /// The upstream branch on the remote, as in `branch.<name>.merge`, e.g. `refs/heads/main`.
+    pub merge: String,
+    /// Commits on the branch that are not on its upstream.
+    #[serde(default)]
+    pub ahead: u32,
+    /// Commits on the upstream that are not on the branch.
+    #[serde(default)]
+    pub behind: u32,
+    /// Whether the upstream's remote-tracking branch no longer exists, so `ahead` and `behind`
+    /// are unknown.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub gone: bool,
+}
+
+/// The format of the `git for-each-ref` output read by [`parse_tracking`].
+const TRACKING_FORMAT: &str = "%(refname:short)%00%(upstream:remotename)%00\
+                               %(upstream:remoteref)%00%(upstream:track,nobracket)";
+
+/// Read how each local branch of the repository at `path` that has an upstream compares with
+/// it, by branch name, by running `git for-each-ref`.
+pub fn read_tracking(path: &Path) -> Result<BTreeMap<String, Tracking>> {
+    let output = refs::git_command(path)
+        .args(["for-each-ref", "--format", TRACKING_FORMAT, "refs/heads"])
+        .output()
+        .context("Failed to run git for-each-ref")?;
+    if !output.status.success() {
+        bail!(
+            "git for-each-ref failed in {:?}: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(parse_tracking(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse the output of `git for-each-ref` with [`TRACKING_FORMAT`]: per branch, its name,
+/// upstream remote, upstream branch and a track such as `ahead 2, behind 1` or `gone`.
+pub fn parse_tracking(output: &str) -> BTreeMap<String, Tracking> {
+    let mut branches = BTreeMap::new();
+    for line in output.lines() {
+        let mut fields = line.split('\0');
+        let (Some(branch), Some(remote), Some(merge), track) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        if remote.is_empty() {
+            continue;
+        }
+        let mut tracking = Tracking {
+            remote: remote.to_string(),
+            merge: merge.to_string(),
+            ..Tracking::default()
+        };
+        for part in track.unwrap_or_default().split(", ") {
+            match part.split_once(' ') {
+                Some(("ahead", count)) => tracking.ahead = count.parse().unwrap_or(0),
+                Some(("behind", count)) => tracking.behind = count.parse().unwrap_or(0),
+                _ if part == "gone" => tracking.gone = true,
+                _ => {}
+            }
+        }
+        branches.insert(branch.to_string(), tracking);
+    }
+    branches
+}
+
+impl fmt::Display for Status {
+    /// One line in the style of `git status -sb`, e.g.
+    /// `main...origin/main [ahead 2, behind 1] 3 staged, 1 untracked`, or `clean` at the end,
+    /// followed by any operation in progress, e.g. `(rebase in progress)`, in the current
+    /// locale.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.branch {
+            Some(branch) => write!(f, "{}", branch)?,
+            None => write!(f, "{}", locale::text("status-detached", &[]))?,
+        }
+        if let Some(upstream) = &self.upstream {
+            write!(f, "...{}", upstream)?;
+        }
+        let divergence = divergence(self.ahead, self.behind);
+        if !divergence.is_empty() {
+            write!(f, " [{}]", divergence.join(", "))?;
+        }
+        let changes: Vec<_> = [
+            ("status-staged", self.staged),
+            ("status-modified", self.modified),
+            ("status-conflicted", self.conflicted),
+            ("status-untracked", self.untracked),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(id, count)| locale::text(id, &[("count", &count)]))
+        .collect();
+        if changes.is_empty() {
+            write!(f, " {}", locale::text("status-clean", &[]))?;
+        } else {
+            write!(f, " {}", changes.join(", "))?;
+        }
+        if let Some(operation) = self.in_progress {
+            let in_progress = locale::text("status-in-progress", &[("operation", &operation)]);
+            write!(f, " ({})", in_progress)?;
+        }
+        Ok(())
+    }
+}
+
+/// How far a branch is ahead of and behind its upstream, e.g. `ahead 2` and `behind 1`, in the
+/// current locale; a count of zero is left out.
+pub fn divergence(ahead: u32, behind: u32) -> Vec<String> {
+    [("ahead", ahead), ("behind", behind)]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(id, count)| locale::text(id, &[("count", &count)]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let status = Status::parse(
+            "# branch.oid 0123abcd\n\
+             # branch.head main\n\
+             # branch.upstream origin/main\n\
+             # branch.ab +2 -1\n\
+             1 M. N... 100644 100644 100644 aaaa bbbb src/main.rs\n\
+             1 .M N... 100644 100644 100644 aaaa bbbb README.md\n\
+             1 MM N... 100644 100644 100644 aaaa bbbb Cargo.toml\n\
+             2 R. N... 100644 100644 100644 aaaa bbbb R100 new.rs\told.rs\n\
+             u UU N... 100644 100644 100644 100644 aaaa bbbb cccc conflict.rs\n\
+             ? untracked.txt\n\
+             ! ignored.txt\n",
+        );
+        assert_eq!(
+            status,
+            Status {
+                branch: Some("main".to_string()),
+                upstream: Some("origin/main".to_string()),
+                ahead: 2,
+                behind: 1,
+                staged: 3,
+                modified: 2,
+                conflicted: 1,
+                untracked: 1,
+                in_progress: None,
+            }
+        );
+        assert!(status.is_dirty());
+        assert_eq!(
+            status.to_string(),
+            "main...origin/main [ahead 2, behind 1] 3 staged, 2 modified, 1 conflicted, 1 untracked"
+        );
+
+        let detached = Status::parse("# branch.oid 0123abcd\n# branch.head (detached)\n");
+        assert_eq!(detached.branch, None);
+        assert!(!detached.is_dirty());
+        assert_eq!(detached.to_string(), "HEAD (detached) clean");
+        let rebasing = Status {
+            in_progress: Some(Operation::Rebase),
+            ..detached
+        };
+        assert_eq!(
+            rebasing.to_string(),
+            "HEAD (detached) clean (rebase in progress)"
+        );
+    }
+
+    #[test]
+    fn test_parse_tracking() {
+        let branches = parse_tracking(
+            "main\0origin\0refs/heads/main\0ahead 2, behind 1\n\
+             fix\0fork\0refs/heads/fix-1\0\n\
+             old\0origin\0refs/heads/old\0gone\n\
+             local\0\0\0\n",
+        );
+        assert_eq!(
+            branches,
+            BTreeMap::from([
+                (
+                    "main".to_string(),
+                    Tracking {
+                        remote: "origin".to_string(),
+                        merge: "refs/heads/main".to_string(),
+                        ahead: 2,
+                        behind: 1,
+                        gone: false,
+                    }
+                ),
+                (
+                    "fix".to_string(),
+                    Tracking {
+                        remote: "fork".to_string(),
+                        merge: "refs/heads/fix-1".to_string(),
+                        ..Tracking::default()
+                    }
+                ),
+                (
+                    "old".to_string(),
+                    Tracking {
+                        remote: "origin".to_string(),
+                        merge: "refs/heads/old".to_string(),
+                        gone: true,
+                        ..Tracking::default()
+                    }
+                ),
+            ])
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! What the terminal `lg` writes to can show, consulted before writing anything but plain text:
+//! colour, hyperlinks, redrawn progress lines, and how wide lines can be.
+//!
+//! Escapes are only written to a terminal, so output piped or redirected to a file is plain.
+//! The conventions honoured are:
+//! * `NO_COLOR`, set and not empty, turns colour off (<https://no-color.org>);
+//! * `CLICOLOR_FORCE`, set and not `0`, turns colour on even when not writing to a terminal;
+//! * `TERM=dumb` marks a terminal that understands no escapes;
+//! * `COLUMNS` sets the width, as shells do.
+use std::io::IsTerminal;
+
+/// A stream `lg` writes to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// What a stream can show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// The stream is a terminal.
+    tty: bool,
+    /// `NO_COLOR` is set.
+    no_color: bool,
+    /// `CLICOLOR_FORCE` is set.
+    force_color: bool,
+    /// `TERM` is `dumb`.
+    dumb: bool,
+    /// The terminal's width in columns, if known.
+    columns: Option<usize>,
+}
+
+impl Capabilities {
+    /// The capabilities of `stream`, from the environment and whether it is a terminal.
+    pub fn detect(stream: Stream) -> Self {
+        let tty = match stream {
+            Stream::Stdout => std::io::stdout().is_terminal(),
+            Stream::Stderr => std::io::stderr().is_terminal(),
+        };
+        let size = || {
+            crossterm::terminal::size()
+                .ok()
+                .map(|(columns, _)| columns as usize)
+        };
+        Capabilities::from_env(tty, |name| std::env::var(name).ok(), size)
+    }
+
+    /// The capabilities of a stream that is a terminal if `tty`, with the environment variables
+    /// `var` gives and the width `size` reports for a terminal.
+    fn from_env(
+        tty: bool,
+        var: impl Fn(&str) -> Option<String>,
+        size: impl FnOnce() -> Option<usize>,
+    ) -> Self {
+        let set = |name| var(name).filter(|value| !value.is_empty());
+        let columns = set("COLUMNS")
+            .and_then(|columns| columns.trim().parse().ok())
+            .filter(|&columns| columns > 0)
+            .or_else(|| if tty { size() } else { None });
+        Capabilities {
+            tty,
+            no_color: set("NO_COLOR").is_some(),
+            force_color: set("CLICOLOR_FORCE").is_some_and(|value| value != "0"),
+            dumb: var("TERM").as_deref() == Some("dumb"),
+            columns,
+        }
+    }
+
+    /// Whether to colour and style text. `NO_COLOR` wins over `CLICOLOR_FORCE`.
+    pub fn color(&self) -> bool {
+        !self.no_color && (self.force_color || self.interactive())
+    }
+
+    /// Whether the stream is a terminal that can be drawn on: moving the cursor and redrawing
+    /// lines, as progress and full-screen interfaces do. Never forced, since the escapes are
+    /// meaningless anywhere else.
+    pub fn interactive(&self) -> bool {
+        self.tty && !self.dumb
+    }
+
+    /// Whether to make links clickable with OSC 8 escapes.
+    pub fn hyperlinks(&self) -> bool {
+        self.interactive() && !self.no_color
+    }
+
+    /// `text` linking to `url` if the stream shows hyperlinks, else `text` alone.
+    pub fn hyperlink(&self, url: &str, text: &str) -> String {
+        if self.hyperlinks() {
+            format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// How many columns lines can take before wrapping: `$COLUMNS`, else the terminal's width.
+    /// `None` if the stream is not a terminal and `COLUMNS` is not set, when lines should not
+    /// be truncated.
+    pub fn width(&self) -> Option<usize> {
+        self.columns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(tty: bool, vars: &[(&str, &str)]) -> Capabilities {
+        Capabilities::from_env(
+            tty,
+            |name| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            },
+            || Some(120),
+        )
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let terminal = detect(true, &[("TERM", "xterm-256color")]);
+        assert!(terminal.color() && terminal.interactive() && terminal.hyperlinks());
+        assert_eq!(terminal.width(), Some(120));
+        assert_eq!(
+            terminal.hyperlink("https://example.com", "x"),
+            "\x1b]8;;https://example.com\x1b\\x\x1b]8;;\x1b\\"
+        );
+
+        // Piped output is plain unless colour is forced, and is not truncated.
+        let piped = detect(false, &[]);
+        assert!(!piped.color() && !piped.interactive() && !piped.hyperlinks());
+        assert_eq!(piped.width(), None);
+        assert_eq!(piped.hyperlink("https://example.com", "x"), "x");
+        assert!(detect(false, &[("CLICOLOR_FORCE", "1")]).color());
+        assert!(!detect(false, &[("CLICOLOR_FORCE", "0")]).color());
+        assert_eq!(detect(false, &[("COLUMNS", "60")]).width(), Some(60));
+
+        // NO_COLOR wins, and an empty NO_COLOR is unset.
+        assert!(!detect(true, &[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")]).color());
+        assert!(detect(true, &[("NO_COLOR", "")]).color());
+
+        let dumb = detect(true, &[("TERM", "dumb")]);
+        assert!(!dumb.color() && !dumb.interactive() && !dumb.hyperlinks());
+        assert!(detect(true, &[("TERM", "dumb"), ("CLICOLOR_FORCE", "1")]).color());
+    }
+}
@@ -0,0 +1,34 @@
+//! Helpers shared by the unit tests and the command-line tests.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Write `content` as the `.git/config` of a repository in `dir`, creating the directory if
+/// needed, and return the file's path.
+pub(crate) fn create_git_config(dir: &Path, content: &str) -> std::io::Result<PathBuf> {
+    fs::create_dir_all(dir.join(".git"))?;
+    let path = dir.join(".git/config");
+    fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// Create a repository in `dir` whose only remote, `origin`, is `url`.
+pub(crate) fn create_repo(dir: &Path, url: &str) -> std::io::Result<()> {
+    create_git_config(dir, &format!("[remote \"origin\"]\n    url = {}\n", url)).map(|_| ())
+}
+
+/// Run `git` with `args` in `dir`, committing as `lg`, and fail the test if it fails.
+pub(crate) fn git(dir: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(["-c", "user.name=lg", "-c", "user.email=lg@example.com"])
+        .args(args)
+        .output()
+        .expect("failed to run git");
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,246 @@
+//! Formatting timestamps for display, and reading them and durations back.
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::locale;
+
+/// How times are shown to people. Structured output always writes them as ISO 8601.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeFormat {
+    /// How long ago, e.g. `3 weeks ago`
+    Relative,
+    /// An ISO 8601 UTC time, e.g. `2024-06-01T09:30:00Z`
+    #[default]
+    Iso,
+    /// Seconds since the Unix epoch
+    Unix,
+}
+
+impl TimeFormat {
+    /// Format `secs` since the Unix epoch.
+    pub fn format(self, secs: u64) -> String {
+        self.format_at(secs, now())
+    }
+
+    /// Format `secs` since the Unix epoch as it would be shown at the time `now`.
+    pub fn format_at(self, secs: u64, now: u64) -> String {
+        match self {
+            TimeFormat::Relative => format_relative(secs, now),
+            TimeFormat::Iso => format_utc(secs),
+            TimeFormat::Unix => secs.to_string(),
+        }
+    }
+}
+
+/// Format `secs` since the Unix epoch as an ISO 8601 UTC time, e.g. `2024-06-01T09:30:00Z`.
+pub fn format_utc(secs: u64) -> String {
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since the epoch, after Howard Hinnant's `civil_from_days`.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+/// Format how long before `now` the time `secs` was, e.g. `3 weeks ago`, in the largest unit
+/// that keeps a useful precision, as `git log --date=relative` does, in the current locale.
+pub fn format_relative(secs: u64, now: u64) -> String {
+    let Some(ago) = now.checked_sub(secs) else {
+        return locale::text("ago-future", &[]);
+    };
+    let (count, unit) = match ago {
+        0..=89 => (ago, "second"),
+        90..=5_399 => ((ago + 30) / 60, "minute"),
+        5_400..=129_599 => ((ago + 1_800) / 3_600, "hour"),
+        _ => {
+            let days = (ago + 43_200) / 86_400;
+            match days {
+                0..=13 => (days, "day"),
+                14..=69 => ((days + 3) / 7, "week"),
+                70..=364 => ((days + 15) / 30, "month"),
+                _ => ((days + 182) / 365, "year"),
+            }
+        }
+    };
+    locale::plural(&format!("ago-{}", unit), count as usize, &[])
+}
+
+/// Read an ISO 8601 UTC time as written by [`format_utc`], giving seconds since the Unix
+/// epoch; fractional seconds are ignored.
+pub fn parse_utc(time: &str) -> Option<u64> {
+    let (date, clock) = time.strip_suffix('Z')?.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let clock = clock.split('.').next()?;
+    let mut clock = clock.splitn(3, ':').map(str::parse::<u64>);
+    let (hour, minute, second) = (
+        clock.next()?.ok()?,
+        clock.next()?.ok()?,
+        clock.next()?.ok()?,
+    );
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+    // Days since the epoch from a civil date, after Howard Hinnant's `days_from_civil`.
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = u64::try_from(era * 146_097 + doe - 719_468).ok()?;
+    Some(days * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+/// Read a date as `YYYY`, `YYYY-MM` or `YYYY-MM-DD`, giving the seconds since the Unix epoch
+/// at its start, in UTC.
+pub fn parse_date(date: &str) -> Result<u64> {
+    let date = date.trim();
+    let padded = match date.matches('-').count() {
+        0 => format!("{}-01-01", date),
+        1 => format!("{}-01", date),
+        _ => date.to_string(),
+    };
+    parse_utc(&format!("{}T00:00:00Z", padded))
+        .with_context(|| locale::text("invalid-date", &[("date", &format_args!("{:?}", date))]))
+}
+
+/// The current time in seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+/// Read a duration such as `30d`: a whole number followed by `s`, `m`, `h`, `d` or `w` for
+/// seconds, minutes, hours, days or weeks.
+pub fn parse_duration(duration: &str) -> Result<Duration> {
+    let duration = duration.trim();
+    let split = duration
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(duration.len());
+    let (count, unit) = duration.split_at(split);
+    let count: u64 = count.parse().with_context(|| {
+        locale::text(
+            "invalid-duration",
+            &[("duration", &format_args!("{:?}", duration))],
+        )
+    })?;
+    let unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 604_800,
+        _ => bail!(locale::text(
+            "invalid-duration-unit",
+            &[("duration", &format_args!("{:?}", duration))]
+        )),
+    };
+    Ok(Duration::from_secs(count.saturating_mul(unit)))
+}
+
+/// Serde support for an optional time in seconds since the Unix epoch, written as an ISO 8601
+/// UTC time. Use as
+/// `#[serde(default, skip_serializing_if = "Option::is_none", with = "time::utc")]`.
+pub mod utc {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Write `secs` as an ISO 8601 UTC time.
+    pub fn serialize<S: Serializer>(secs: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+        secs.map(super::format_utc).serialize(serializer)
+    }
+
+    /// Read a time written by [`serialize`].
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u64>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|time| {
+                super::parse_utc(&time)
+                    .ok_or_else(|| D::Error::custom(format!("invalid time {:?}", time)))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_utc(1_717_234_200), "2024-06-01T09:30:00Z");
+    }
+
+    #[test]
+    fn test_format_relative() {
+        let now = 1_717_234_200;
+        assert_eq!(format_relative(now, now), "0 seconds ago");
+        assert_eq!(format_relative(now - 60, now), "60 seconds ago");
+        assert_eq!(format_relative(now - 90, now), "2 minutes ago");
+        assert_eq!(format_relative(now - 3_600, now), "60 minutes ago");
+        assert_eq!(format_relative(now - 7_200, now), "2 hours ago");
+        assert_eq!(format_relative(now - 86_400 * 2, now), "2 days ago");
+        assert_eq!(format_relative(now - 86_400 * 21, now), "3 weeks ago");
+        assert_eq!(format_relative(now - 86_400 * 100, now), "3 months ago");
+        assert_eq!(format_relative(now - 86_400 * 400, now), "1 year ago");
+        assert_eq!(format_relative(now + 60, now), "in the future");
+        assert_eq!(TimeFormat::Unix.format_at(now, 0), "1717234200");
+        assert_eq!(TimeFormat::Iso.format_at(now, 0), "2024-06-01T09:30:00Z");
+    }
+
+    #[test]
+    fn test_parse_utc() {
+        for secs in [0, 951_782_400, 1_717_234_200, 4_102_444_799] {
+            assert_eq!(parse_utc(&format_utc(secs)), Some(secs));
+        }
+        assert_eq!(parse_utc("2024-06-01T09:30:00.250Z"), Some(1_717_234_200));
+        assert_eq!(parse_utc("2024-13-01T09:30:00Z"), None);
+        assert_eq!(parse_utc("2024-06-01 09:30:00"), None);
+    }
+
+    #[test]
+    fn test_parse_date() -> Result<()> {
+        assert_eq!(parse_date("2018")?, 1_514_764_800);
+        assert_eq!(parse_date("2024-06")?, parse_date("2024-06-01")?);
+        assert_eq!(
+            format_utc(parse_date("2000-02-29")?),
+            "2000-02-29T00:00:00Z"
+        );
+        assert!(parse_date("June 2018").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_duration() -> Result<()> {
+        assert_eq!(parse_duration("30d")?, Duration::from_secs(30 * 86_400));
+        assert_eq!(parse_duration("2w")?, Duration::from_secs(14 * 86_400));
+        assert_eq!(parse_duration("90s")?, Duration::from_secs(90));
+        assert!(parse_duration("30").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("1y").is_err());
+        Ok(())
+    }
+}
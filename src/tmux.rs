@@ -0,0 +1,61 @@
+//! Opening repositories in tmux sessions.
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+
+use crate::locale;
+
+/// A tmux session name for a repository directory called `name`.
+///
+/// tmux does not allow `.` or `:` in session names, so they are replaced with `_`.
+pub fn session_name(name: &str) -> String {
+    name.replace(['.', ':'], "_")
+}
+
+fn tmux(args: &[&str]) -> Result<bool> {
+    let status = Command::new("tmux")
+        .args(args)
+        .status()
+        .with_context(|| locale::text("failed-run-program", &[("program", &"tmux")]))?;
+    Ok(status.success())
+}
+
+/// Attach to the session `name`, first creating it with `path` as its working directory if it
+/// does not exist. Inside tmux the current client is switched to the session instead.
+pub fn open(name: &str, path: &Path) -> Result<()> {
+    let target = format!("={}", name);
+    let exists = Command::new("tmux")
+        .args(["has-session", "-t", &target])
+        .stderr(Stdio::null())
+        .status()
+        .with_context(|| locale::text("failed-run-program", &[("program", &"tmux")]))?
+        .success();
+    if !exists {
+        let path = path.to_string_lossy();
+        if !tmux(&["new-session", "-d", "-s", name, "-c", &path])? {
+            bail!(locale::failed("create-tmux-session", &name));
+        }
+    }
+    let attach = if std::env::var_os("TMUX").is_some() {
+        "switch-client"
+    } else {
+        "attach-session"
+    };
+    if !tmux(&[attach, "-t", &target])? {
+        bail!(locale::failed("attach-tmux-session", &name));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_name() {
+        assert_eq!(session_name("lg"), "lg");
+        assert_eq!(session_name("dotfiles.nvim"), "dotfiles_nvim");
+        assert_eq!(session_name("a:b"), "a_b");
+    }
+}
@@ -0,0 +1,241 @@
+//! Updating `lg` in place from the project's GitHub releases, as `lg self-update` does.
+//!
+//! A release has a binary for each platform, named as [`asset_name`] gives, and a `SHA256SUMS`
+//! file listing each binary's SHA-256 checksum as `sha256sum` prints it. A binary is only
+//! installed if its checksum matches.
+use std::env::consts::{ARCH, EXE_SUFFIX, OS};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+
+use crate::locale;
+
+/// The GitHub API endpoint for the latest release.
+pub const RELEASES_URL: &str = "https://api.github.com/repos/kthwaite/lg/releases/latest";
+
+/// The name of the release asset listing the checksums of the others.
+const CHECKSUMS: &str = "SHA256SUMS";
+
+/// The most a binary downloaded may weigh.
+const MAX_BINARY: u64 = 256 * 1024 * 1024;
+
+/// A release, as the GitHub API describes it.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Release {
+    /// The release's tag, e.g. `v0.3.0`.
+    pub tag_name: String,
+    pub assets: Vec<Asset>,
+}
+
+/// A file attached to a release.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    /// The release's version, without the tag's `v`.
+    pub fn version(&self) -> &str {
+        self.tag_name.trim_start_matches('v')
+    }
+
+    /// Download this platform's binary, and check it against the release's checksums.
+    pub fn download(&self) -> Result<Vec<u8>> {
+        let name = asset_name();
+        let binary = self.asset(&name).ok_or_else(|| {
+            anyhow!(locale::text(
+                "release-no-binary",
+                &[("release", &self.tag_name), ("name", &name)]
+            ))
+        })?;
+        let checksums = self.asset(CHECKSUMS).ok_or_else(|| {
+            anyhow!(locale::text(
+                "release-no-checksums",
+                &[("release", &self.tag_name), ("file", &CHECKSUMS)]
+            ))
+        })?;
+        let sums = String::from_utf8(get(&checksums.browser_download_url, 1024 * 1024)?)
+            .with_context(|| locale::text("release-checksums-not-text", &[("file", &CHECKSUMS)]))?;
+        let expected = checksum_for(&sums, &name).ok_or_else(|| {
+            anyhow!(locale::text(
+                "release-checksum-missing",
+                &[("file", &CHECKSUMS), ("name", &name)]
+            ))
+        })?;
+        let bytes = get(&binary.browser_download_url, MAX_BINARY)?;
+        let actual = sha256_hex(&bytes);
+        if !actual.eq_ignore_ascii_case(expected) {
+            bail!(locale::text(
+                "release-checksum-mismatch",
+                &[
+                    ("name", &name),
+                    ("expected", &expected),
+                    ("actual", &actual)
+                ]
+            ));
+        }
+        Ok(bytes)
+    }
+
+    fn asset(&self, name: &str) -> Option<&Asset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// The name of this platform's binary in a release, e.g. `lg-linux-x86_64` or
+/// `lg-windows-x86_64.exe`.
+pub fn asset_name() -> String {
+    format!("lg-{}-{}{}", OS, ARCH, EXE_SUFFIX)
+}
+
+/// Read the latest release from `url`, such as [`RELEASES_URL`].
+pub fn latest(url: &str) -> Result<Release> {
+    ureq::get(url)
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", concat!("lg/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .with_context(|| locale::text("failed-get", &[("url", &url)]))?
+        .body_mut()
+        .read_json()
+        .with_context(|| locale::text("failed-parse-release", &[("url", &url)]))
+}
+
+/// Whether the version `candidate` is newer than `current`, comparing their dot-separated
+/// numbers. Versions that are not numbers are never newer.
+pub fn is_newer(candidate: &str, current: &str) -> bool {
+    let numbers = |version: &str| -> Option<Vec<u64>> {
+        version
+            .trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect()
+    };
+    match (numbers(candidate), numbers(current)) {
+        (Some(candidate), Some(current)) => candidate > current,
+        _ => false,
+    }
+}
+
+/// Replace the executable at `exe` with `binary`, by writing it alongside and renaming it over
+/// `exe`, so that the executable is never left half-written.
+pub fn replace_exe(exe: &Path, binary: &[u8]) -> Result<()> {
+    let new = exe.with_extension("new");
+    fs::write(&new, binary).with_context(|| locale::failed("write", &new))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&new, fs::Permissions::from_mode(0o755))
+            .with_context(|| locale::failed("make-executable", &new))?;
+    }
+    // Windows will not replace a running executable, but will rename it.
+    #[cfg(windows)]
+    {
+        let old = exe.with_extension("old");
+        let _ = fs::remove_file(&old);
+        fs::rename(exe, &old).with_context(|| locale::failed("move-aside", &exe))?;
+    }
+    fs::rename(&new, exe).with_context(|| locale::failed("replace", &exe))
+}
+
+/// GET `url`, reading at most `limit` bytes of the body.
+fn get(url: &str, limit: u64) -> Result<Vec<u8>> {
+    ureq::get(url)
+        .header("User-Agent", concat!("lg/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .with_context(|| locale::text("failed-get", &[("url", &url)]))?
+        .body_mut()
+        .with_config()
+        .limit(limit)
+        .read_to_vec()
+        .with_context(|| locale::text("failed-download", &[("url", &url)]))
+}
+
+/// The checksum `sums`, in the format `sha256sum` prints, gives the file `name`.
+fn checksum_for<'a>(sums: &'a str, name: &str) -> Option<&'a str> {
+    sums.lines().find_map(|line| {
+        let (sum, file) = line.split_once(char::is_whitespace)?;
+        // `sha256sum -b` marks binary files with `*`.
+        (file.trim_start().trim_start_matches('*') == name).then_some(sum)
+    })
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, bytes)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("v0.10.0", "0.9.3"));
+        assert!(is_newer("1.0.1", "1.0.0"));
+        assert!(!is_newer("v1.0.0", "1.0.0"));
+        assert!(!is_newer("0.9.0", "0.10.0"));
+        assert!(!is_newer("nightly", "0.1.0"));
+    }
+
+    #[test]
+    fn test_checksum_for() {
+        let sums = "0123  lg-linux-x86_64\nabcd *lg-windows-x86_64.exe\n";
+        assert_eq!(checksum_for(sums, "lg-linux-x86_64"), Some("0123"));
+        assert_eq!(checksum_for(sums, "lg-windows-x86_64.exe"), Some("abcd"));
+        assert_eq!(checksum_for(sums, "lg-macos-aarch64"), None);
+    }
+
+    /// Serve a release whose binary is `binary` and whose checksums list `sum` for it.
+    fn serve_release(binary: &'static [u8], sum: String) -> (Release, thread::JoinHandle<()>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let handle = thread::spawn(move || {
+            for _ in 0..2 {
+                let request = server.recv().unwrap();
+                let body = match request.url() {
+                    "/SHA256SUMS" => format!("{}  {}\n", sum, asset_name()).into_bytes(),
+                    _ => binary.to_vec(),
+                };
+                request
+                    .respond(tiny_http::Response::from_data(body))
+                    .unwrap();
+            }
+        });
+        let asset = |name: &str| Asset {
+            name: name.to_string(),
+            browser_download_url: format!("{}/{}", base, name),
+        };
+        let release = Release {
+            tag_name: "v9.9.9".to_string(),
+            assets: vec![asset(&asset_name()), asset(CHECKSUMS)],
+        };
+        (release, handle)
+    }
+
+    #[test]
+    fn test_download() -> Result<()> {
+        let (release, handle) = serve_release(b"new lg", sha256_hex(b"new lg"));
+        assert_eq!(release.download()?, b"new lg");
+        handle.join().unwrap();
+
+        let (release, handle) = serve_release(b"tampered", sha256_hex(b"new lg"));
+        assert!(release.download().is_err());
+        handle.join().unwrap();
+
+        let temp_dir = TempDir::new()?;
+        let exe = temp_dir.path().join("lg");
+        fs::write(&exe, "old lg")?;
+        replace_exe(&exe, b"new lg")?;
+        assert_eq!(fs::read(&exe)?, b"new lg");
+        assert!(!exe.with_extension("new").exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+//! Parsing of Git remote URLs into their host and repository path.
+use std::fmt;
+
+/// A remote URL broken into the parts used for layouts and exports.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteUrl {
+    /// The host name, without user or port.
+    pub host: String,
+    /// The repository path on the host, without a leading slash or `.git` suffix.
+    pub path: String,
+}
+
+impl RemoteUrl {
+    /// Parse a remote URL. Returns `None` for local paths and `file://` URLs, which have no host.
+    /// * `url` - A URL as it appears in `remote.<name>.url`.
+    ///
+    /// Accepts `scheme://[user@]host[:port]/path` and scp-like `[user@]host:path`.
+    pub fn parse(url: &str) -> Option<Self> {
+        let (authority, path) = if let Some((scheme, rest)) = url.split_once("://") {
+            if scheme == "file" {
+                return None;
+            }
+            let (authority, path) = rest.split_once('/')?;
+            let host = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+            let host = match host.strip_prefix('[') {
+                // IPv6 literal, e.g. `ssh://[::1]:22/repo`
+                Some(v6) => v6.split_once(']')?.0,
+                None => host.split_once(':').map_or(host, |(h, _)| h),
+            };
+            (host, path)
+        } else {
+            // scp-like syntax is only recognised when there is no slash before the first colon.
+            let (authority, path) = url.split_once(':')?;
+            // A single letter before the colon is a Windows drive, not a host.
+            if authority.contains('/') || authority.len() < 2 {
+                return None;
+            }
+            (
+                authority.rsplit_once('@').map_or(authority, |(_, h)| h),
+                path,
+            )
+        };
+        let path = path.trim_matches('/');
+        let path = path
+            .strip_suffix(".git")
+            .unwrap_or(path)
+            .trim_end_matches('/');
+        if authority.is_empty() || path.is_empty() {
+            return None;
+        }
+        // sourcehut owners are `~user`, which some clients escape.
+        let path = match path.get(..3) {
+            Some(escaped) if escaped.eq_ignore_ascii_case("%7e") => format!("~{}", &path[3..]),
+            _ => path.to_string(),
+        };
+        Some(RemoteUrl {
+            host: authority.to_lowercase(),
+            path,
+        })
+    }
+
+    /// The owner (user, organisation or group) part of the path, if any; on sourcehut, `~user`.
+    pub fn owner(&self) -> Option<&str> {
+        self.path.rsplit_once('/').map(|(owner, _)| owner)
+    }
+
+    /// Whether the repository is hosted on sourcehut, at `git.sr.ht`, `hg.sr.ht` or another
+    /// of its services.
+    pub fn is_sourcehut(&self) -> bool {
+        self.host == "sr.ht" || self.host.ends_with(".sr.ht")
+    }
+
+    /// The repository's web page. GitHub, GitLab, sourcehut and most other forges serve it at
+    /// `https://host/path`, whichever protocol the remote uses; sourcehut's SSH and HTTPS
+    /// remotes share the service's host, such as `git.sr.ht`.
+    pub fn web_url(&self) -> String {
+        let host = match self.host.strip_prefix("ssh.") {
+            // `ssh.github.com` and `ssh.gitlab.com` serve SSH on port 443 for their forges.
+            Some(forge) if !self.is_sourcehut() => forge,
+            _ => &self.host,
+        };
+        format!("https://{}/{}", host, self.path)
+    }
+}
+
+/// Split a remote URL into a fetch base and the repository path on that base.
+///
+/// scp-like URLs are rewritten to the equivalent `ssh://` form so that the base can be joined
+/// with a path again, e.g. `git@host:a/b.git` becomes `("ssh://git@host/", "a/b.git")`.
+/// Returns `None` for local paths.
+pub fn split_base(url: &str) -> Option<(String, String)> {
+    RemoteUrl::parse(url)?;
+    if let Some((scheme, rest)) = url.split_once("://") {
+        let (authority, path) = rest.split_once('/')?;
+        Some((format!("{}://{}/", scheme, authority), path.to_string()))
+    } else {
+        let (authority, path) = url.split_once(':')?;
+        Some((
+            format!("ssh://{}/", authority),
+            path.trim_start_matches('/').to_string(),
+        ))
+    }
+}
+
+/// `s` with its `%XX` escapes decoded, as the userinfo and path of a URL are written. Escapes
+/// that are not two hex digits are kept as they are.
+pub fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+impl fmt::Display for RemoteUrl {
+    /// Formats as `host/path`, the layout used by ghq and Go import paths.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.host, self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(url: &str) -> Option<(String, String)> {
+        RemoteUrl::parse(url).map(|u| (u.host, u.path))
+    }
+
+    #[test]
+    fn test_parse_remote_urls() {
+        let expected = Some(("github.com".to_string(), "user/repo".to_string()));
+        assert_eq!(parse("https://github.com/user/repo.git"), expected);
+        assert_eq!(parse("https://github.com/user/repo"), expected);
+        assert_eq!(parse("git@github.com:user/repo.git"), expected);
+        assert_eq!(parse("ssh://git@github.com:22/user/repo.git"), expected);
+        assert_eq!(parse("git://GitHub.com/user/repo/"), expected);
+        assert_eq!(
+            parse("https://gitlab.com/group/sub/repo.git"),
+            Some(("gitlab.com".to_string(), "group/sub/repo".to_string()))
+        );
+        assert_eq!(parse("/srv/git/repo.git"), None);
+        assert_eq!(parse("../repo"), None);
+        assert_eq!(parse("file:///srv/git/repo.git"), None);
+        assert_eq!(parse("C:\\src\\repo"), None);
+    }
+
+    #[test]
+    fn test_sourcehut() {
+        let expected = Some(("git.sr.ht".to_string(), "~user/repo".to_string()));
+        assert_eq!(parse("git@git.sr.ht:~user/repo"), expected);
+        assert_eq!(parse("https://git.sr.ht/~user/repo"), expected);
+        assert_eq!(parse("https://git.sr.ht/%7Euser/repo"), expected);
+        assert_eq!(parse("ssh://git@git.sr.ht/~user/repo"), expected);
+
+        let url = RemoteUrl::parse("ssh://hg@hg.sr.ht/~user/repo").unwrap();
+        assert!(url.is_sourcehut());
+        assert_eq!(url.owner(), Some("~user"));
+        assert_eq!(url.web_url(), "https://hg.sr.ht/~user/repo");
+        assert!(!RemoteUrl::parse("git@github.com:user/repo.git")
+            .unwrap()
+            .is_sourcehut());
+    }
+
+    #[test]
+    fn test_web_url() {
+        let web_url = |url| RemoteUrl::parse(url).unwrap().web_url();
+        assert_eq!(
+            web_url("git@github.com:user/repo.git"),
+            "https://github.com/user/repo"
+        );
+        assert_eq!(
+            web_url("ssh://git@ssh.github.com:443/user/repo.git"),
+            "https://github.com/user/repo"
+        );
+        assert_eq!(
+            web_url("git@git.sr.ht:~user/repo"),
+            "https://git.sr.ht/~user/repo"
+        );
+    }
+
+    #[test]
+    fn test_split_base() {
+        assert_eq!(
+            split_base("https://github.com/user/repo.git"),
+            Some((
+                "https://github.com/".to_string(),
+                "user/repo.git".to_string()
+            ))
+        );
+        assert_eq!(
+            split_base("git@github.com:user/repo.git"),
+            Some((
+                "ssh://git@github.com/".to_string(),
+                "user/repo.git".to_string()
+            ))
+        );
+        assert_eq!(split_base("/srv/git/repo.git"), None);
+    }
+
+    #[test]
+    fn test_owner_and_display() {
+        let url = RemoteUrl::parse("https://gitlab.com/group/sub/repo.git").unwrap();
+        assert_eq!(url.owner(), Some("group/sub"));
+        assert_eq!(url.to_string(), "gitlab.com/group/sub/repo");
+    }
+}
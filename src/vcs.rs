@@ -0,0 +1,227 @@
+//! The version control systems a scan can detect.
+//!
+//! Each system implements [`Vcs`], which recognises its repositories and reads their remotes
+//! and state. [`VcsKind`] names the systems, on the command line and in
+//! [`GitDirectory::vcs`](crate::GitDirectory::vcs), and [`VcsKind::system`] gives each one's
+//! implementation; supporting another system means a submodule implementing [`Vcs`] and a
+//! variant of [`VcsKind`].
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::annex::Annex;
+use crate::backend::Backend;
+use crate::gitconfig::PartialClone;
+use crate::locale;
+use crate::model::{primary_remote, RemoteUrls};
+use crate::operation::Operation;
+use crate::push::Push;
+use crate::sparse::SparseCheckout;
+use crate::status::{Status, Tracking};
+
+mod fossil;
+mod git;
+mod hg;
+mod jj;
+mod pijul;
+mod svn;
+
+pub use fossil::Fossil;
+pub use git::Git;
+pub use hg::Hg;
+pub use jj::Jj;
+pub use pijul::Pijul;
+pub use svn::Svn;
+
+/// A version control system, as named on the command line and in output.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum VcsKind {
+    /// Git: a `.git` directory, with remotes from its config.
+    #[default]
+    Git,
+    /// Mercurial: a `.hg` directory, with remotes from the `[paths]` of `.hg/hgrc`.
+    Hg,
+    /// Subversion: a `.svn` directory, with the working copy's URL as the remote `default`.
+    Svn,
+    /// Jujutsu: a `.jj` directory, colocated with a `.git` or not, with the remotes of the Git
+    /// repository that backs it.
+    Jj,
+    /// Pijul: a `.pijul` directory, with remotes from `.pijul/config` (requires the `pijul`
+    /// feature).
+    Pijul,
+    /// Fossil: a `.fslckout` or `_FOSSIL_` checkout database, with the URL from
+    /// `fossil remote` as the remote `default`.
+    Fossil,
+}
+
+impl VcsKind {
+    /// The name used in output and on the command line, e.g. "hg".
+    pub fn name(self) -> &'static str {
+        match self {
+            VcsKind::Git => "git",
+            VcsKind::Hg => "hg",
+            VcsKind::Svn => "svn",
+            VcsKind::Jj => "jj",
+            VcsKind::Pijul => "pijul",
+            VcsKind::Fossil => "fossil",
+        }
+    }
+
+    /// Check that support for this version control system was compiled into the binary.
+    pub fn ensure_available(self) -> Result<()> {
+        match self {
+            VcsKind::Pijul if !cfg!(feature = "pijul") => {
+                Err(anyhow!(locale::text("pijul-unsupported", &[])))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether this is Git, which output leaves unsaid.
+    pub fn is_git(&self) -> bool {
+        *self == VcsKind::Git
+    }
+
+    /// The implementation of this version control system.
+    /// * `backend` - How Git repositories are read.
+    pub fn system(self, backend: Backend) -> &'static dyn Vcs {
+        match self {
+            VcsKind::Git => Git::with_backend(backend),
+            VcsKind::Hg => &Hg,
+            VcsKind::Svn => &Svn,
+            VcsKind::Jj => &Jj,
+            VcsKind::Pijul => &Pijul,
+            VcsKind::Fossil => &Fossil,
+        }
+    }
+}
+
+/// A version control system: how to recognise its repositories and read them.
+///
+/// Listing repositories needs only [`Vcs::detect`] and [`Vcs::remotes`]; the rest default to
+/// saying nothing, for systems that cannot answer.
+pub trait Vcs: Send + Sync {
+    /// Which system this is.
+    fn kind(&self) -> VcsKind;
+
+    /// Whether `path` is the working directory of one of this system's repositories.
+    fn detect(&self, path: &Path) -> bool;
+
+    /// The remote names and URLs of the repository at `path`.
+    fn remotes(&self, path: &Path) -> Result<HashMap<String, String>>;
+
+    /// The name of the remote that identifies the repository at `path`, one of `remotes`.
+    fn primary_remote(
+        &self,
+        _path: &Path,
+        remotes: &HashMap<String, String>,
+    ) -> Result<Option<String>> {
+        Ok(primary_remote(remotes).map(|(name, _)| name.to_string()))
+    }
+
+    /// Every URL of the repository's remotes that have more than one, by remote name.
+    fn urls(&self, _path: &Path) -> Result<BTreeMap<String, RemoteUrls>> {
+        Ok(BTreeMap::new())
+    }
+
+    /// Where the repository at `path` keeps its metadata, if not in the usual place inside it.
+    fn git_dir(&self, _path: &Path) -> Option<PathBuf> {
+        None
+    }
+
+    /// The repository's git-annex, if it uses one.
+    /// * `size` - Measure the annexed content present.
+    fn annex(&self, _path: &Path, _size: bool) -> Result<Option<Annex>> {
+        Ok(None)
+    }
+
+    /// What the working tree at `path` checks out, if it is a sparse checkout.
+    fn sparse_checkout(&self, _path: &Path) -> Result<Option<SparseCheckout>> {
+        Ok(None)
+    }
+
+    /// The promisor remote of the repository at `path`, if it is a partial clone.
+    fn partial_clone(&self, _path: &Path) -> Result<Option<PartialClone>> {
+        Ok(None)
+    }
+
+    /// The operation left in progress in the working tree at `path`, such as a merge.
+    fn in_progress(&self, _path: &Path) -> Option<Operation> {
+        None
+    }
+
+    /// The push configuration of the repository at `path`, and where each branch is pushed.
+    fn push(&self, _path: &Path) -> Result<Option<Push>> {
+        Ok(None)
+    }
+
+    /// How each local branch of the repository at `path` with an upstream compares with it.
+    fn tracking(&self, _path: &Path) -> Result<BTreeMap<String, Tracking>> {
+        Ok(BTreeMap::new())
+    }
+
+    /// When the repository at `path` last fetched, in seconds since the Unix epoch.
+    fn last_fetch(&self, _path: &Path) -> Option<u64> {
+        None
+    }
+
+    /// When the first commit of the repository at `path` was made, in seconds since the Unix
+    /// epoch; reading it can walk the whole history.
+    fn first_commit(&self, _path: &Path) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    /// The checked-out branch, or this system's nearest equivalent, if it can be read.
+    fn head(&self, _path: &Path) -> Option<String> {
+        None
+    }
+
+    /// The state of the working copy at `path`.
+    fn status(&self, _path: &Path) -> Result<Status> {
+        bail!(locale::text(
+            "status-unsupported",
+            &[("vcs", &self.kind().name())]
+        ))
+    }
+
+    /// The repository's main line of development, if it can be read.
+    fn default_branch(&self, _path: &Path) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_system() -> Result<()> {
+        for kind in VcsKind::value_variants() {
+            assert_eq!(kind.system(Backend::Native).kind(), *kind);
+        }
+        let temp_dir = TempDir::new()?;
+        assert!(VcsKind::Hg
+            .system(Backend::Native)
+            .status(temp_dir.path())
+            .is_err());
+        Ok(())
+    }
+}
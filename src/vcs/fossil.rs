@@ -0,0 +1,75 @@
+//! Fossil checkouts.
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use super::{Vcs, VcsKind};
+
+/// Fossil: a checkout database, `.fslckout` or on Windows `_FOSSIL_`, with the URL printed by
+/// `fossil remote` as the remote `default`. Fossil must be installed.
+#[derive(Clone, Copy, Debug)]
+pub struct Fossil;
+
+impl Vcs for Fossil {
+    fn kind(&self) -> VcsKind {
+        VcsKind::Fossil
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        [".fslckout", "_FOSSIL_"]
+            .iter()
+            .any(|marker| path.join(marker).is_file())
+    }
+
+    fn remotes(&self, path: &Path) -> Result<HashMap<String, String>> {
+        let output = Command::new("fossil")
+            .arg("remote")
+            .current_dir(path)
+            .output()
+            .context("Failed to run fossil remote; is Fossil installed?")?;
+        if !output.status.success() {
+            bail!(
+                "fossil remote failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let remote = parse_remote(&String::from_utf8_lossy(&output.stdout));
+        Ok(remote
+            .map(|url| ("default".to_string(), url))
+            .into_iter()
+            .collect())
+    }
+
+    fn default_branch(&self, _path: &Path) -> Option<String> {
+        Some("trunk".to_string())
+    }
+}
+
+/// The URL printed by `fossil remote`, which is `off` when there is none.
+fn parse_remote(output: &str) -> Option<String> {
+    let url = output.trim();
+    (!url.is_empty() && url != "off").then(|| url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fossil() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        assert!(!Fossil.detect(temp_dir.path()));
+        std::fs::write(temp_dir.path().join(".fslckout"), "")?;
+        assert!(Fossil.detect(temp_dir.path()));
+
+        assert_eq!(
+            parse_remote("https://sqlite.org/src\n").as_deref(),
+            Some("https://sqlite.org/src")
+        );
+        assert_eq!(parse_remote("off\n"), None);
+        Ok(())
+    }
+}
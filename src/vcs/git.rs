@@ -0,0 +1,237 @@
+//! Git, the system every scan detects unless told otherwise.
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use super::{Vcs, VcsKind};
+use crate::annex::{self, Annex};
+use crate::backend::Backend;
+use crate::gitconfig::{read_partial_clone, read_primary_remote, read_remote_urls, PartialClone};
+use crate::model::{primary_remote, RemoteUrls};
+use crate::operation::{self, Operation};
+use crate::push::{self, Push};
+use crate::refs;
+use crate::sparse::{self, SparseCheckout};
+use crate::status::{self, Status, Tracking};
+
+/// Git repositories, read with a [`Backend`].
+#[derive(Clone, Copy, Debug)]
+pub struct Git {
+    backend: Backend,
+}
+
+impl Git {
+    /// Git, reading remotes with `backend`.
+    pub fn with_backend(backend: Backend) -> &'static Git {
+        match backend {
+            Backend::Native => &Git {
+                backend: Backend::Native,
+            },
+            Backend::Git2 => &Git {
+                backend: Backend::Git2,
+            },
+            Backend::Gix => &Git {
+                backend: Backend::Gix,
+            },
+        }
+    }
+}
+
+impl Vcs for Git {
+    fn kind(&self) -> VcsKind {
+        VcsKind::Git
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        match self.backend {
+            // The built-in parser needs the config it reads, wherever the Git directory is.
+            Backend::Native => {
+                refs::common_dir(path).is_some_and(|dir| dir.join("config").is_file())
+            }
+            Backend::Git2 | Backend::Gix => refs::git_dir(path).is_some(),
+        }
+    }
+
+    fn remotes(&self, path: &Path) -> Result<HashMap<String, String>> {
+        Ok(self.backend.remotes(path)?.unwrap_or_default())
+    }
+
+    /// Honours `checkout.defaultRemoteName`, read with the built-in parser.
+    fn primary_remote(
+        &self,
+        path: &Path,
+        remotes: &HashMap<String, String>,
+    ) -> Result<Option<String>> {
+        let Some(config) = refs::common_dir(path)
+            .map(|dir| dir.join("config"))
+            .filter(|config| config.is_file())
+        else {
+            return Ok(primary_remote(remotes).map(|(name, _)| name.to_string()));
+        };
+        Ok(read_primary_remote(&config)?.filter(|name| remotes.contains_key(name)))
+    }
+
+    /// Read from the repository's config with the built-in parser, whatever the backend.
+    fn urls(&self, path: &Path) -> Result<BTreeMap<String, RemoteUrls>> {
+        let Some(config) = refs::common_dir(path)
+            .map(|dir| dir.join("config"))
+            .filter(|config| config.is_file())
+        else {
+            return Ok(BTreeMap::new());
+        };
+        let mut urls = read_remote_urls(&config)?;
+        urls.retain(|_, urls| urls.is_multiple());
+        Ok(urls)
+    }
+
+    fn git_dir(&self, path: &Path) -> Option<PathBuf> {
+        refs::external_git_dir(path)
+    }
+
+    fn annex(&self, path: &Path, size: bool) -> Result<Option<Annex>> {
+        annex::read(path, size)
+    }
+
+    fn sparse_checkout(&self, path: &Path) -> Result<Option<SparseCheckout>> {
+        sparse::read(path)
+    }
+
+    fn in_progress(&self, path: &Path) -> Option<Operation> {
+        operation::read(path)
+    }
+
+    fn push(&self, path: &Path) -> Result<Option<Push>> {
+        push::read(path).map(Some)
+    }
+
+    fn tracking(&self, path: &Path) -> Result<BTreeMap<String, Tracking>> {
+        status::read_tracking(path)
+    }
+
+    fn last_fetch(&self, path: &Path) -> Option<u64> {
+        refs::last_fetch(path)
+    }
+
+    /// The earliest of the root commits reachable from `HEAD`; none for an unborn branch.
+    fn first_commit(&self, path: &Path) -> Result<Option<u64>> {
+        let output = refs::git_command(path)
+            .args(["log", "--max-parents=0", "--format=%ct", "HEAD"])
+            .output()
+            .context("Failed to run git log")?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.trim().parse().ok())
+            .min())
+    }
+
+    fn partial_clone(&self, path: &Path) -> Result<Option<PartialClone>> {
+        match refs::common_dir(path).map(|dir| dir.join("config")) {
+            Some(config) if config.is_file() => read_partial_clone(&config),
+            _ => Ok(None),
+        }
+    }
+
+    fn head(&self, path: &Path) -> Option<String> {
+        refs::head(path).map(|head| head.name().to_string())
+    }
+
+    fn status(&self, path: &Path) -> Result<Status> {
+        Status::read(path)
+    }
+
+    fn default_branch(&self, path: &Path) -> Option<String> {
+        refs::default_branch(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    use crate::paths;
+
+    #[test]
+    fn test_git() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        let git = Git::with_backend(Backend::Native);
+        assert!(!git.detect(repo));
+
+        fs::create_dir_all(repo.join(".git/refs/remotes/origin"))?;
+        fs::write(
+            repo.join(".git/config"),
+            "[remote \"origin\"]\n    url = https://github.com/user/repo.git\n",
+        )?;
+        fs::write(repo.join(".git/HEAD"), "ref: refs/heads/feature\n")?;
+        fs::write(
+            repo.join(".git/refs/remotes/origin/HEAD"),
+            "ref: refs/remotes/origin/main\n",
+        )?;
+        assert!(git.detect(repo));
+        assert_eq!(git.remotes(repo)?.len(), 1);
+        assert_eq!(git.head(repo).as_deref(), Some("feature"));
+        assert_eq!(git.default_branch(repo).as_deref(), Some("main"));
+        assert!(git.urls(repo)?.is_empty());
+        assert_eq!(git.git_dir(repo), None);
+
+        fs::write(
+            repo.join(".git/config"),
+            "[remote \"origin\"]\n\turl = https://github.com/user/repo.git\n\
+             \tpushurl = git@github.com:user/repo.git\n",
+        )?;
+        assert_eq!(
+            git.urls(repo)?["origin"].push,
+            ["git@github.com:user/repo.git"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_separate_git_dir() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let git = Git::with_backend(Backend::Native);
+
+        // As `git clone --separate-git-dir` leaves it.
+        let repo = temp_dir.path().join("repo");
+        fs::create_dir_all(temp_dir.path().join("store/repo.git"))?;
+        fs::create_dir_all(&repo)?;
+        fs::write(repo.join(".git"), "gitdir: ../store/repo.git\n")?;
+        fs::write(
+            temp_dir.path().join("store/repo.git/config"),
+            "[remote \"origin\"]\n    url = https://github.com/user/repo.git\n",
+        )?;
+        assert!(git.detect(&repo));
+        assert_eq!(
+            git.git_dir(&repo),
+            Some(paths::canonicalize(
+                &temp_dir.path().join("store/repo.git")
+            )?)
+        );
+        assert_eq!(
+            git.remotes(&repo)?["origin"],
+            "https://github.com/user/repo.git"
+        );
+
+        // A linked worktree, whose config is in the main repository's Git directory.
+        let worktree = temp_dir.path().join("worktree");
+        let worktree_git_dir = temp_dir.path().join("store/repo.git/worktrees/worktree");
+        fs::create_dir_all(&worktree_git_dir)?;
+        fs::create_dir_all(&worktree)?;
+        fs::write(worktree_git_dir.join("commondir"), "../..\n")?;
+        fs::write(worktree_git_dir.join("HEAD"), "ref: refs/heads/topic\n")?;
+        fs::write(
+            worktree.join(".git"),
+            "gitdir: ../store/repo.git/worktrees/worktree\n",
+        )?;
+        assert!(git.detect(&worktree));
+        assert_eq!(git.remotes(&worktree)?.len(), 1);
+        assert_eq!(git.head(&worktree).as_deref(), Some("topic"));
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+//! Mercurial repositories.
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::{Vcs, VcsKind};
+
+/// Mercurial: a `.hg` directory, whose `hgrc` names the remotes in `[paths]`.
+#[derive(Clone, Copy, Debug)]
+pub struct Hg;
+
+impl Vcs for Hg {
+    fn kind(&self) -> VcsKind {
+        VcsKind::Hg
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        path.join(".hg").is_dir()
+    }
+
+    fn remotes(&self, path: &Path) -> Result<HashMap<String, String>> {
+        let hgrc = path.join(".hg").join("hgrc");
+        if !hgrc.is_file() {
+            return Ok(HashMap::new());
+        }
+        parse_hgrc(&hgrc).with_context(|| format!("Error parsing {:?}", hgrc))
+    }
+
+    /// The active bookmark, or else the branch.
+    fn head(&self, path: &Path) -> Option<String> {
+        let dot_hg = path.join(".hg");
+        ["bookmarks.current", "branch"]
+            .iter()
+            .filter_map(|name| fs::read_to_string(dot_hg.join(name)).ok())
+            .map(|contents| contents.trim().to_string())
+            .find(|name| !name.is_empty())
+            .or_else(|| dot_hg.is_dir().then(|| "default".to_string()))
+    }
+
+    fn default_branch(&self, _path: &Path) -> Option<String> {
+        Some("default".to_string())
+    }
+}
+
+/// Parse the `[paths]` section of a Mercurial config file into names and URLs.
+///
+/// Sub-options such as `default:pushurl`, continuation lines and `%include` directives are
+/// ignored.
+fn parse_hgrc(config_path: &Path) -> Result<HashMap<String, String>> {
+    let contents = fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to read Mercurial config file: {:?}", config_path))?;
+    let mut remotes = HashMap::new();
+    let mut in_paths = false;
+    for line in contents.lines() {
+        if line.starts_with([' ', '\t']) {
+            continue;
+        }
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(['#', ';', '%']) {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_paths = section.trim() == "paths";
+        } else if let Some((name, url)) = line.split_once('=').filter(|_| in_paths) {
+            let (name, url) = (name.trim(), url.trim());
+            if !name.contains(':') && !url.is_empty() {
+                remotes.insert(name.to_string(), url.to_string());
+            }
+        }
+    }
+    Ok(remotes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hg() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        assert!(!Hg.detect(repo));
+
+        fs::create_dir(repo.join(".hg"))?;
+        assert!(Hg.detect(repo));
+        assert_eq!(Hg.remotes(repo)?, HashMap::new());
+        assert_eq!(Hg.head(repo).as_deref(), Some("default"));
+
+        fs::write(
+            repo.join(".hg/hgrc"),
+            "# comment\n[ui]\nusername = me\n\n[paths]\n\
+             default = https://hg.example.com/repo\n  continued\n\
+             default:pushurl = ssh://hg@example.com/repo\n\
+             upstream=ssh://hg@example.com/up\n%include other.rc\n[extensions]\nrebase =\n",
+        )?;
+        let remotes = Hg.remotes(repo)?;
+        assert_eq!(remotes.len(), 2);
+        assert_eq!(remotes["default"], "https://hg.example.com/repo");
+        assert_eq!(remotes["upstream"], "ssh://hg@example.com/up");
+
+        fs::write(repo.join(".hg/branch"), "stable\n")?;
+        assert_eq!(Hg.head(repo).as_deref(), Some("stable"));
+        fs::write(repo.join(".hg/bookmarks.current"), "feature")?;
+        assert_eq!(Hg.head(repo).as_deref(), Some("feature"));
+        Ok(())
+    }
+}
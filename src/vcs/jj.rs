@@ -0,0 +1,125 @@
+//! Jujutsu repositories.
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use tracing::debug;
+
+use super::{Vcs, VcsKind};
+use crate::parse_git_config;
+
+/// Jujutsu: a `.jj` directory, with the remotes of the Git repository that backs it, which for
+/// a colocated repository is the `.git` beside `.jj`. A repository with another backend has
+/// no remotes.
+#[derive(Clone, Copy, Debug)]
+pub struct Jj;
+
+impl Vcs for Jj {
+    fn kind(&self) -> VcsKind {
+        VcsKind::Jj
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        path.join(".jj").is_dir()
+    }
+
+    fn remotes(&self, path: &Path) -> Result<HashMap<String, String>> {
+        let Some(git_dir) = git_dir(&path.join(".jj"))? else {
+            return Ok(HashMap::new());
+        };
+        let git_config = git_dir.join("config");
+        parse_git_config(&git_config).with_context(|| format!("Error parsing {:?}", git_config))
+    }
+
+    /// The bookmarks on the working-copy change, or else its short change id, from `jj log`;
+    /// `None` if jj is not installed or fails.
+    fn head(&self, path: &Path) -> Option<String> {
+        let output = Command::new("jj")
+            .arg("--repository")
+            .arg(path)
+            .args(["--ignore-working-copy", "--no-pager", "--color", "never"])
+            .args(["log", "--no-graph", "--revisions", "@", "--template"])
+            .arg(r#"if(bookmarks, bookmarks.join(" "), change_id.short())"#)
+            .output();
+        match output {
+            Ok(output) if output.status.success() => {
+                let head = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                Some(head).filter(|head| !head.is_empty())
+            }
+            Ok(output) => {
+                debug!(
+                    path = %path.display(),
+                    "jj log failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+                None
+            }
+            Err(e) => {
+                debug!(path = %path.display(), "failed to run jj: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// The Git directory backing the Jujutsu repository whose `.jj` is `dot_jj`, if it has one.
+fn git_dir(dot_jj: &Path) -> Result<Option<PathBuf>> {
+    // In secondary workspaces, `.jj/repo` is a file holding the path of the shared one.
+    let repo = dot_jj.join("repo");
+    let repo = if repo.is_file() {
+        let target =
+            fs::read_to_string(&repo).with_context(|| format!("Failed to read {:?}", repo))?;
+        dot_jj.join(target.trim())
+    } else {
+        repo
+    };
+    let store = repo.join("store");
+    let git_target = store.join("git_target");
+    if !git_target.is_file() {
+        return Ok(None);
+    }
+    let target = fs::read_to_string(&git_target)
+        .with_context(|| format!("Failed to read {:?}", git_target))?;
+    Ok(Some(store.join(target.trim())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_jj() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let origin = "[remote \"origin\"]\n    url = https://github.com/user/repo.git\n";
+        assert!(!Jj.detect(temp_dir.path()));
+
+        let colocated = temp_dir.path().join("colocated");
+        fs::create_dir_all(colocated.join(".jj/repo/store"))?;
+        fs::create_dir_all(colocated.join(".git"))?;
+        fs::write(colocated.join(".jj/repo/store/git_target"), "../../../.git")?;
+        fs::write(colocated.join(".git/config"), origin)?;
+        assert!(Jj.detect(&colocated));
+        assert_eq!(
+            Jj.remotes(&colocated)?["origin"],
+            "https://github.com/user/repo.git"
+        );
+
+        let internal = temp_dir.path().join("internal");
+        fs::create_dir_all(internal.join(".jj/repo/store/git"))?;
+        fs::write(internal.join(".jj/repo/store/git_target"), "git")?;
+        fs::write(internal.join(".jj/repo/store/git/config"), origin)?;
+        assert_eq!(Jj.remotes(&internal)?.len(), 1);
+
+        let workspace = temp_dir.path().join("workspace");
+        fs::create_dir_all(workspace.join(".jj"))?;
+        fs::write(workspace.join(".jj/repo"), "../../internal/.jj/repo")?;
+        assert_eq!(Jj.remotes(&workspace)?.len(), 1);
+
+        fs::remove_file(internal.join(".jj/repo/store/git_target"))?;
+        assert_eq!(Jj.remotes(&internal)?, HashMap::new());
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+//! Pijul repositories, with the `pijul` feature.
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Result;
+
+use super::{Vcs, VcsKind};
+
+/// Pijul: a `.pijul` directory, with remotes from `.pijul/config`.
+#[derive(Clone, Copy, Debug)]
+pub struct Pijul;
+
+impl Vcs for Pijul {
+    fn kind(&self) -> VcsKind {
+        VcsKind::Pijul
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        path.join(".pijul").is_dir()
+    }
+
+    fn remotes(&self, path: &Path) -> Result<HashMap<String, String>> {
+        config_remotes(path)
+    }
+
+    fn default_branch(&self, _path: &Path) -> Option<String> {
+        Some("main".to_string())
+    }
+}
+
+#[cfg(feature = "pijul")]
+fn config_remotes(path: &Path) -> Result<HashMap<String, String>> {
+    use std::fs;
+
+    use anyhow::Context;
+    use serde::Deserialize;
+
+    /// The parts of `.pijul/config` that name remotes.
+    #[derive(Deserialize)]
+    struct PijulConfig {
+        default_remote: Option<String>,
+        #[serde(default)]
+        remotes: Vec<PijulRemote>,
+    }
+
+    #[derive(Deserialize)]
+    struct PijulRemote {
+        name: String,
+        ssh: Option<String>,
+        http: Option<String>,
+    }
+
+    let config_path = path.join(".pijul").join("config");
+    if !config_path.is_file() {
+        return Ok(HashMap::new());
+    }
+    let contents = fs::read_to_string(&config_path)
+        .with_context(|| format!("Failed to read Pijul config file: {:?}", config_path))?;
+    let config: PijulConfig =
+        toml::from_str(&contents).with_context(|| format!("Error parsing {:?}", config_path))?;
+    let mut remotes: HashMap<String, String> = config
+        .remotes
+        .into_iter()
+        .filter_map(|remote| Some((remote.name, remote.ssh.or(remote.http)?)))
+        .collect();
+    // The default remote is a URL, or the name of one of the remotes.
+    if let Some(default) = config.default_remote {
+        if !remotes.contains_key(&default) {
+            remotes.insert("default".to_string(), default);
+        }
+    }
+    Ok(remotes)
+}
+
+#[cfg(not(feature = "pijul"))]
+fn config_remotes(_path: &Path) -> Result<HashMap<String, String>> {
+    VcsKind::Pijul.ensure_available().map(|_| HashMap::new())
+}
+
+#[cfg(all(test, feature = "pijul"))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pijul() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        assert!(!Pijul.detect(repo));
+
+        fs::create_dir(repo.join(".pijul"))?;
+        assert!(Pijul.detect(repo));
+        assert_eq!(Pijul.remotes(repo)?, HashMap::new());
+
+        fs::write(
+            repo.join(".pijul/config"),
+            "default_remote = \"https://nest.pijul.com/me/repo\"\n\n\
+             [[remotes]]\nname = \"mirror\"\nssh = \"me@example.com:repo\"\n\n\
+             [hooks]\nrecord = []\n",
+        )?;
+        let remotes = Pijul.remotes(repo)?;
+        assert_eq!(remotes["default"], "https://nest.pijul.com/me/repo");
+        assert_eq!(remotes["mirror"], "me@example.com:repo");
+
+        fs::write(
+            repo.join(".pijul/config"),
+            "default_remote = \"mirror\"\n[[remotes]]\nname = \"mirror\"\n\
+             http = \"https://example.com/repo\"\n",
+        )?;
+        assert_eq!(Pijul.remotes(repo)?.len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+//! Subversion working copies.
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::{Vcs, VcsKind};
+
+/// Subversion: a `.svn` directory at the root of a working copy, whose URL is reported as the
+/// remote `default`.
+///
+/// With the `svn` feature the URL is read from the working copy database, `.svn/wc.db`;
+/// otherwise from `svn info`, which must be installed.
+#[derive(Clone, Copy, Debug)]
+pub struct Svn;
+
+impl Vcs for Svn {
+    fn kind(&self) -> VcsKind {
+        VcsKind::Svn
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        path.join(".svn").is_dir()
+    }
+
+    fn remotes(&self, path: &Path) -> Result<HashMap<String, String>> {
+        let url = url(path)?;
+        Ok(HashMap::from([("default".to_string(), url)]))
+    }
+}
+
+#[cfg(feature = "svn")]
+fn url(path: &Path) -> Result<String> {
+    use rusqlite::{Connection, OpenFlags};
+
+    let db = path.join(".svn").join("wc.db");
+    let connection = Connection::open_with_flags(&db, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open {:?}", db))?;
+    // The working copy root's base node names its repository and path within it.
+    let (root, relpath): (String, Option<String>) = connection
+        .query_row(
+            "SELECT repository.root, nodes.repos_path FROM nodes \
+             JOIN repository ON repository.id = nodes.repos_id \
+             WHERE nodes.local_relpath = '' AND nodes.op_depth = 0",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .with_context(|| format!("Failed to read the working copy URL from {:?}", db))?;
+    Ok(match relpath.as_deref() {
+        None | Some("") => root,
+        Some(relpath) => format!("{}/{}", root.trim_end_matches('/'), relpath),
+    })
+}
+
+#[cfg(not(feature = "svn"))]
+fn url(path: &Path) -> Result<String> {
+    let output = std::process::Command::new("svn")
+        .args(["info", "--show-item", "url", "--no-newline"])
+        .arg(path)
+        .output()
+        .context("Failed to run svn info; install Subversion or build with `--features svn`")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "svn info failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8(output.stdout).context("svn info printed a URL that is not UTF-8")
+}
+
+#[cfg(all(test, feature = "svn"))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_svn() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        assert!(!Svn.detect(repo));
+
+        fs::create_dir(repo.join(".svn"))?;
+        assert!(Svn.detect(repo));
+        assert!(Svn.remotes(repo).is_err());
+
+        let connection = rusqlite::Connection::open(repo.join(".svn/wc.db"))?;
+        connection.execute_batch(
+            "CREATE TABLE repository (id INTEGER PRIMARY KEY, root TEXT, uuid TEXT);
+             CREATE TABLE nodes (wc_id INTEGER, local_relpath TEXT, op_depth INTEGER,
+                                 repos_id INTEGER, repos_path TEXT);
+             INSERT INTO repository VALUES (1, 'https://svn.example.com/repo', 'uuid');
+             INSERT INTO nodes VALUES (1, '', 0, 1, 'trunk');
+             INSERT INTO nodes VALUES (1, 'src', 0, 1, 'trunk/src');",
+        )?;
+        drop(connection);
+        assert_eq!(
+            Svn.remotes(repo)?["default"],
+            "https://svn.example.com/repo/trunk"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+//! Reading and writing [vcstool](https://github.com/dirk-thomas/vcstool) `.repos` files.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::clone::{clone_repo, CloneOutcome};
+use crate::export::relative_repos;
+use crate::locale;
+use crate::GitDirectory;
+use crate::{paths, refs};
+
+/// The contents of a `.repos` file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Repositories keyed by their path relative to the manifest's root.
+    pub repositories: BTreeMap<String, Repository>,
+}
+
+/// A single entry in a `.repos` file.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Repository {
+    /// The version control system; only `git` is supported for cloning.
+    #[serde(rename = "type")]
+    pub vcs: String,
+    pub url: String,
+    /// The branch, tag or commit to check out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl Manifest {
+    /// Build a manifest from every repository with a remote in `dir`.
+    pub fn from_tree(dir: &GitDirectory) -> Self {
+        let mut manifest = Manifest::default();
+        for (path, repo) in relative_repos(dir) {
+            let Some((_, url)) = repo.primary_remote() else {
+                continue;
+            };
+            let version = refs::head(&dir.path.join(&path)).map(|h| h.name().to_string());
+            manifest.repositories.insert(
+                path.to_string_lossy().replace('\\', "/"),
+                Repository {
+                    vcs: "git".to_string(),
+                    url: url.to_string(),
+                    version,
+                },
+            );
+        }
+        manifest
+    }
+
+    /// Read a manifest from a `.repos` file.
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).with_context(|| locale::failed("read", &path))?;
+        serde_yaml::from_str(&contents).with_context(|| locale::failed("parse", &path))
+    }
+
+    /// Clone every git repository in the manifest under `root`, skipping those already present.
+    /// Nothing is cloned if any path would be outside `root`.
+    /// * `root` - The directory the manifest's paths are relative to.
+    /// * `dry_run` - Print what would be cloned without cloning.
+    pub fn clone_all(&self, root: &Path, dry_run: bool) -> Result<()> {
+        if let Some(path) = self
+            .repositories
+            .keys()
+            .find(|path| !paths::is_contained(Path::new(path)))
+        {
+            bail!(locale::text(
+                "vcstool-outside-root",
+                &[("path", &format_args!("{:?}", path))]
+            ));
+        }
+        let mut failed = 0;
+        for (path, repo) in &self.repositories {
+            if repo.vcs != "git" {
+                warn!("skipping {}: unsupported type {:?}", path, repo.vcs);
+                continue;
+            }
+            let target = root.join(path);
+            if clone_repo(&repo.url, &target, repo.version.as_deref(), dry_run)?
+                == CloneOutcome::Failed
+            {
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            bail!(locale::text("clone-failed", &[("count", &failed)]));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_manifest_round_trip() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path().join("src/lg");
+        fs::create_dir_all(repo.join(".git"))?;
+        fs::write(repo.join(".git/HEAD"), "ref: refs/heads/main\n")?;
+
+        let tree = GitDirectory {
+            children: vec![GitDirectory {
+                remotes: HashMap::from([(
+                    "origin".to_string(),
+                    "https://github.com/kthwaite/lg.git".to_string(),
+                )]),
+                ..GitDirectory::directory(PathBuf::from("src/lg"))
+            }],
+            ..GitDirectory::directory(temp_dir.path().to_path_buf())
+        };
+        let yaml = serde_yaml::to_string(&Manifest::from_tree(&tree))?;
+        assert_eq!(
+            yaml,
+            "repositories:
+  src/lg:
+    type: git
+    url: https://github.com/kthwaite/lg.git
+    version: main
+"
+        );
+
+        let path = temp_dir.path().join("lg.repos");
+        fs::write(&path, yaml)?;
+        let manifest = Manifest::read(&path)?;
+        assert_eq!(
+            manifest.repositories.get("src/lg"),
+            Some(&Repository {
+                vcs: "git".to_string(),
+                url: "https://github.com/kthwaite/lg.git".to_string(),
+                version: Some("main".to_string()),
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_clone_all_outside_root() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path().join("root");
+        for path in ["../escaped", "src/../../escaped", "/tmp/escaped"] {
+            let manifest = Manifest {
+                repositories: BTreeMap::from([(
+                    path.to_string(),
+                    Repository {
+                        vcs: "git".to_string(),
+                        url: "https://example.com/repo.git".to_string(),
+                        version: None,
+                    },
+                )]),
+            };
+            assert!(manifest.clone_all(&root, false).is_err(), "{}", path);
+        }
+        assert!(!temp_dir.path().join("escaped").exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+//! Checking repositories for corruption with `git fsck`, as `lg verify` does, to catch the bit
+//! rot of clones kept on disks that are not looked at often.
+use std::fmt;
+use std::path::Path;
+use std::process::Stdio;
+
+use crate::bulk::Outcome;
+use crate::locale;
+use crate::refs;
+
+/// What checking a repository found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verified {
+    /// No problems.
+    Ok,
+    /// `git fsck` reported these problems, one per line.
+    Corrupt(Vec<String>),
+    /// `git fsck` could not be run, with this message.
+    Failed(String),
+}
+
+impl Outcome for Verified {
+    fn label(&self) -> Option<&'static str> {
+        match self {
+            Verified::Ok => Some("ok"),
+            Verified::Corrupt(_) => Some("corrupt"),
+            Verified::Failed(_) => Some("failed"),
+        }
+    }
+}
+
+impl fmt::Display for Verified {
+    /// What was found, in the current locale.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Verified::Ok => locale::text("outcome-ok", &[]),
+            Verified::Corrupt(problems) => locale::plural("verified-corrupt", problems.len(), &[]),
+            Verified::Failed(message) => {
+                locale::text("outcome-failed-with", &[("message", message)])
+            }
+        };
+        f.write_str(&text)
+    }
+}
+
+/// Check the objects and refs of the Git repository at `path` with `git fsck`. Unreachable
+/// objects are not problems. With `quick`, only that every object reachable exists is checked,
+/// not that each is intact, which is much faster.
+pub fn verify(path: &Path, quick: bool) -> Verified {
+    let mut command = refs::git_command(path);
+    command.args(["fsck", "--no-dangling", "--no-progress"]);
+    if quick {
+        command.arg("--connectivity-only");
+    }
+    let output = match command.stdin(Stdio::null()).output() {
+        Ok(output) => output,
+        Err(e) => return Verified::Failed(format!("Failed to run git fsck: {}", e)),
+    };
+    // Git exits successfully with only warnings, such as for a bad timezone in an old commit.
+    if output.status.success() {
+        return Verified::Ok;
+    }
+    let mut problems: Vec<_> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .chain(String::from_utf8_lossy(&output.stderr).lines())
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect();
+    if problems.is_empty() {
+        problems.push(format!("git fsck exited with {}", output.status));
+    }
+    Verified::Corrupt(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(["-c", "user.name=lg", "-c", "user.email=lg@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    #[test]
+    fn test_verify() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        git(repo, &["init", "-q", "-b", "main"]);
+        fs::write(repo.join("README"), "lg\n")?;
+        git(repo, &["add", "README"]);
+        git(repo, &["commit", "-q", "-m", "Initial commit"]);
+        assert_eq!(verify(repo, false), Verified::Ok);
+
+        // Lose the README's blob, as a disk might.
+        let blob = git(repo, &["rev-parse", "HEAD:README"]);
+        fs::remove_file(repo.join(".git/objects").join(&blob[..2]).join(&blob[2..]))?;
+        let Verified::Corrupt(problems) = verify(repo, true) else {
+            panic!("expected corruption");
+        };
+        assert!(problems.iter().any(|line| line.contains(&blob)));
+        Ok(())
+    }
+}
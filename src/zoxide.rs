@@ -0,0 +1,27 @@
+//! Seeding the [zoxide](https://github.com/ajeetdsouza/zoxide) database.
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use crate::locale;
+
+/// Add `paths` to the zoxide database with `zoxide add`.
+pub fn add(paths: &[PathBuf]) -> Result<()> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    let status = Command::new("zoxide")
+        .arg("add")
+        .arg("--")
+        .args(paths)
+        .status()
+        .with_context(|| locale::text("failed-run-zoxide", &[]))?;
+    if !status.success() {
+        bail!(locale::text(
+            "exited-with",
+            &[("command", &"zoxide add"), ("status", &status)]
+        ));
+    }
+    Ok(())
+}